@@ -0,0 +1,679 @@
+//!
+//! Locally maintained order books, fed by the websocket `book` channel.
+//!
+
+use super::{Channel, Decimal, DepthPair, DepthPairTuple, PublicClient, PublicEvent, Subscription};
+use crc32fast::Hasher;
+use std::collections::{BTreeMap, HashMap};
+
+/// Number of levels per side covered by kraken's checksum.
+const CHECKSUM_LEVELS: usize = 10;
+
+/// A price level of an order book
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PriceLevel {
+    pub price: Decimal,
+    pub volume: Decimal,
+}
+
+/// A level as sent by kraken, the strings are kept for the checksum.
+#[derive(Debug, Clone)]
+struct Level {
+    price: String,
+    volume: String,
+    volume_decimal: Decimal,
+}
+
+///
+/// Order book of a pair, built from a snapshot and kept up to date by applying updates.
+///
+/// Every update which carries a checksum is verified against the book.  After a mismatch the
+/// book is cleared and stays out of sync until the next snapshot.
+///
+#[derive(Debug, Clone)]
+pub struct OrderBook {
+    depth: usize,
+    asks: BTreeMap<Decimal, Level>,
+    bids: BTreeMap<Decimal, Level>,
+    synced: bool,
+}
+
+impl OrderBook {
+    /// Creates an empty book which keeps at most `depth` levels per side.
+    pub fn new(depth: u32) -> OrderBook {
+        OrderBook {
+            depth: depth as usize,
+            asks: BTreeMap::new(),
+            bids: BTreeMap::new(),
+            synced: false,
+        }
+    }
+
+    /// Whether the book was built from a snapshot and no checksum mismatched since.
+    pub fn is_synced(&self) -> bool {
+        self.synced
+    }
+
+    /// Removes all levels, the book is out of sync until the next snapshot.
+    pub fn clear(&mut self) {
+        self.asks.clear();
+        self.bids.clear();
+        self.synced = false;
+    }
+
+    /// Replaces all levels with those of a snapshot.  An invalid snapshot leaves the book cleared.
+    pub fn apply_snapshot(&mut self, snapshot: &DepthPair) -> Result<(), String> {
+        self.clear();
+
+        let result = apply_levels(&mut self.asks, &snapshot.asks)
+            .and_then(|_| apply_levels(&mut self.bids, &snapshot.bids));
+
+        if let Err(e) = result {
+            self.clear();
+            return Err(e);
+        }
+
+        self.truncate();
+        self.synced = true;
+
+        Ok(())
+    }
+
+    ///
+    /// Applies changed levels and verifies the checksum, if given.
+    ///
+    /// Levels with a volume of zero are removed, levels beyond the book's depth are dropped.
+    ///
+    /// # Note
+    ///
+    /// Updates to a book which is out of sync are ignored.
+    ///
+    pub fn apply_update(&mut self, update: &DepthPair, checksum: Option<u32>) -> Result<(), String> {
+        if !self.synced {
+            return Ok(());
+        }
+
+        let result = apply_levels(&mut self.asks, &update.asks)
+            .and_then(|_| apply_levels(&mut self.bids, &update.bids));
+
+        if let Err(e) = result {
+            self.clear();
+            return Err(e);
+        }
+
+        self.truncate();
+
+        match checksum {
+            Some(expected) if expected != self.checksum() => {
+                let actual = self.checksum();
+
+                self.clear();
+
+                Err(format!("book checksum mismatch: expected {}, got {}", expected, actual))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    ///
+    /// CRC32 over the best ten asks and bids, as calculated by kraken.
+    ///
+    /// Each level adds its price and volume with the decimal point and leading zeros removed.
+    ///
+    pub fn checksum(&self) -> u32 {
+        let mut hasher = Hasher::new();
+
+        let asks = self.asks.values().take(CHECKSUM_LEVELS);
+        let bids = self.bids.values().rev().take(CHECKSUM_LEVELS);
+
+        for level in asks.chain(bids) {
+            hasher.update(checksum_digits(&level.price).as_bytes());
+            hasher.update(checksum_digits(&level.volume).as_bytes());
+        }
+
+        hasher.finalize()
+    }
+
+    fn truncate(&mut self) {
+        while self.asks.len() > self.depth {
+            let worst = *self.asks.keys().next_back().unwrap();
+            self.asks.remove(&worst);
+        }
+
+        while self.bids.len() > self.depth {
+            let worst = *self.bids.keys().next().unwrap();
+            self.bids.remove(&worst);
+        }
+    }
+
+    pub fn best_ask(&self) -> Option<PriceLevel> {
+        self.asks(1).into_iter().next()
+    }
+
+    pub fn best_bid(&self) -> Option<PriceLevel> {
+        self.bids(1).into_iter().next()
+    }
+
+    /// Difference between best ask and best bid.
+    pub fn spread(&self) -> Option<Decimal> {
+        spread(self.best_bid(), self.best_ask())
+    }
+
+    /// Middle between best ask and best bid.
+    pub fn mid_price(&self) -> Option<Decimal> {
+        mid_price(self.best_bid(), self.best_ask())
+    }
+
+    /// The best `count` asks, lowest price first.
+    pub fn asks(&self, count: usize) -> Vec<PriceLevel> {
+        self.asks
+            .iter()
+            .take(count)
+            .map(|(price, level)| PriceLevel {
+                price: *price,
+                volume: level.volume_decimal,
+            })
+            .collect()
+    }
+
+    /// The best `count` bids, highest price first.
+    pub fn bids(&self, count: usize) -> Vec<PriceLevel> {
+        self.bids
+            .iter()
+            .rev()
+            .take(count)
+            .map(|(price, level)| PriceLevel {
+                price: *price,
+                volume: level.volume_decimal,
+            })
+            .collect()
+    }
+}
+
+fn apply_levels(side: &mut BTreeMap<Decimal, Level>, levels: &[DepthPairTuple]) -> Result<(), String> {
+    for level in levels {
+        let price = level.0.parse::<Decimal>()?;
+        let volume = level.1.parse::<Decimal>()?;
+
+        if volume.is_zero() {
+            side.remove(&price);
+        } else {
+            side.insert(
+                price,
+                Level {
+                    price: level.0.clone(),
+                    volume: level.1.clone(),
+                    volume_decimal: volume,
+                },
+            );
+        }
+    }
+
+    Ok(())
+}
+
+fn checksum_digits(value: &str) -> String {
+    let digits = value.replace('.', "");
+    let digits = digits.trim_start_matches('0');
+
+    String::from(digits)
+}
+
+fn spread(bid: Option<PriceLevel>, ask: Option<PriceLevel>) -> Option<Decimal> {
+    match (bid, ask) {
+        (Some(bid), Some(ask)) => Some(ask.price - bid.price),
+        _ => None,
+    }
+}
+
+fn mid_price(bid: Option<PriceLevel>, ask: Option<PriceLevel>) -> Option<Decimal> {
+    match (bid, ask) {
+        (Some(bid), Some(ask)) => Some((ask.price + bid.price) / Decimal::from(2)),
+        _ => None,
+    }
+}
+
+fn price_levels(levels: &[DepthPairTuple], count: usize) -> Result<Vec<PriceLevel>, String> {
+    levels
+        .iter()
+        .take(count)
+        .map(|level| {
+            Ok(PriceLevel {
+                price: level.0.parse()?,
+                volume: level.1.parse()?,
+            })
+        })
+        .collect()
+}
+
+/// The same queries on the order book returned by `order_book`.
+impl DepthPair {
+    pub fn best_ask(&self) -> Result<Option<PriceLevel>, String> {
+        self.asks(1).map(|levels| levels.into_iter().next())
+    }
+
+    pub fn best_bid(&self) -> Result<Option<PriceLevel>, String> {
+        self.bids(1).map(|levels| levels.into_iter().next())
+    }
+
+    /// Difference between best ask and best bid.
+    pub fn spread(&self) -> Result<Option<Decimal>, String> {
+        Ok(spread(self.best_bid()?, self.best_ask()?))
+    }
+
+    /// Middle between best ask and best bid.
+    pub fn mid_price(&self) -> Result<Option<Decimal>, String> {
+        Ok(mid_price(self.best_bid()?, self.best_ask()?))
+    }
+
+    /// The best `count` asks, lowest price first.
+    pub fn asks(&self, count: usize) -> Result<Vec<PriceLevel>, String> {
+        price_levels(&self.asks, count)
+    }
+
+    /// The best `count` bids, highest price first.
+    pub fn bids(&self, count: usize) -> Result<Vec<PriceLevel>, String> {
+        price_levels(&self.bids, count)
+    }
+}
+
+///
+/// Maintains the order books of several pairs from the websocket feed.
+///
+/// A book whose snapshot or update is invalid or whose checksum mismatches is subscribed again,
+/// which makes kraken send a new snapshot.
+/// After a reconnect all books are rebuilt from the snapshots sent for the renewed subscription.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// extern crate kraken;
+///
+/// use kraken::BookFeed;
+///
+/// fn main() {
+///   let mut feed = BookFeed::connect(&["XBT/USD", "ETH/USD"], 25).expect("could not connect");
+///
+///   loop {
+///     match feed.next_update() {
+///       Ok(pair) => {
+///         let book = feed.book(&pair).unwrap();
+///         println!("{}: {:?} / {:?}", pair, book.best_bid(), book.best_ask());
+///       }
+///       Err(e) => println!("feed error: {}", e),
+///     }
+///   }
+/// }
+/// ```
+///
+#[derive(Debug)]
+pub struct BookFeed {
+    client: PublicClient,
+    books: Books,
+}
+
+impl BookFeed {
+    ///
+    /// Connects and subscribes to the books of `pairs`.
+    ///
+    /// # Arguments
+    ///
+    /// + `pairs` - pair names in the websocket form, e.g. "XBT/USD".
+    /// + `depth` - levels per side: 10, 25, 100, 500, 1000.
+    ///
+    pub fn connect(pairs: &[&str], depth: u32) -> Result<BookFeed, String> {
+        let mut client = PublicClient::connect()?;
+
+        client.subscribe(Subscription::new(Channel::Book(depth), pairs))?;
+
+        Ok(BookFeed {
+            client,
+            books: Books::new(pairs, depth),
+        })
+    }
+
+    /// The book of `pair`, if it is in sync.
+    pub fn book(&self, pair: &str) -> Option<&OrderBook> {
+        self.books.get(pair)
+    }
+
+    ///
+    /// Waits until a book changed and returns its pair.
+    ///
+    /// Invalid snapshots, invalid updates and checksum mismatches are returned as errors, the book is
+    /// requested again in the background.
+    ///
+    pub fn next_update(&mut self) -> Result<String, String> {
+        loop {
+            let event = self.client.next_event()?;
+
+            match self.books.apply(event) {
+                Some(BookChange::Changed(pair)) => return Ok(pair),
+                Some(BookChange::OutOfSync(pair, e)) => {
+                    let subscription = Subscription::new(Channel::Book(self.books.depth), &[&pair]);
+
+                    self.client.resubscribe(&subscription)?;
+
+                    return Err(format!("{}: {}", pair, e));
+                }
+                Some(BookChange::Failed(e)) => return Err(e),
+                None => {}
+            }
+        }
+    }
+}
+
+/// Outcome of a feed event for the books
+#[derive(Debug, PartialEq)]
+enum BookChange {
+    /// the book of the pair changed and is in sync
+    Changed(String),
+    /// the book of the pair was cleared because of an invalid snapshot, update or checksum
+    OutOfSync(String, String),
+    Failed(String),
+}
+
+/// Books of several pairs, kept up to date with the events of the feed
+#[derive(Debug)]
+struct Books {
+    depth: u32,
+    books: HashMap<String, OrderBook>,
+}
+
+impl Books {
+    fn new(pairs: &[&str], depth: u32) -> Books {
+        Books {
+            depth,
+            books: pairs
+                .iter()
+                .map(|pair| (String::from(*pair), OrderBook::new(depth)))
+                .collect(),
+        }
+    }
+
+    fn get(&self, pair: &str) -> Option<&OrderBook> {
+        self.books.get(pair).filter(|book| book.is_synced())
+    }
+
+    fn apply(&mut self, event: PublicEvent) -> Option<BookChange> {
+        let (pair, result) = match event {
+            PublicEvent::BookSnapshot { pair, book, .. } => {
+                let depth = self.depth;
+                let result = self.books
+                    .entry(pair.clone())
+                    .or_insert_with(|| OrderBook::new(depth))
+                    .apply_snapshot(&book);
+
+                (pair, result)
+            }
+            PublicEvent::BookUpdate {
+                pair,
+                book,
+                checksum,
+                ..
+            } => {
+                let result = match self.books.get_mut(&pair) {
+                    Some(local) if local.is_synced() => local.apply_update(&book, checksum),
+                    _ => return None,
+                };
+
+                (pair, result)
+            }
+            PublicEvent::Reconnected => {
+                for book in self.books.values_mut() {
+                    book.clear();
+                }
+
+                return None;
+            }
+            PublicEvent::SubscriptionError { pair, message, .. } => {
+                return Some(BookChange::Failed(format!("{}: {}", pair.unwrap_or_default(), message)));
+            }
+            _ => return None,
+        };
+
+        match result {
+            Ok(()) => Some(BookChange::Changed(pair)),
+            Err(e) => Some(BookChange::OutOfSync(pair, e)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json;
+
+    fn levels(levels: &[(&str, &str)]) -> Vec<DepthPairTuple> {
+        levels
+            .iter()
+            .map(|&(price, volume)| DepthPairTuple(String::from(price), String::from(volume), 1534614248))
+            .collect()
+    }
+
+    fn depth_pair(asks: &[(&str, &str)], bids: &[(&str, &str)]) -> DepthPair {
+        DepthPair {
+            asks: levels(asks),
+            bids: levels(bids),
+        }
+    }
+
+    fn level(price: &str, volume: &str) -> PriceLevel {
+        PriceLevel {
+            price: price.parse().unwrap(),
+            volume: volume.parse().unwrap(),
+        }
+    }
+
+    fn book() -> OrderBook {
+        let mut book = OrderBook::new(10);
+
+        book.apply_snapshot(&depth_pair(
+            &[("5541.30000", "2.50700000"), ("5541.80000", "0.33000000")],
+            &[("5541.20000", "1.52900000"), ("5539.90000", "0.30000000")],
+        )).unwrap();
+
+        book
+    }
+
+    #[test]
+    fn kraken_checksum() {
+        let snapshot: DepthPair = serde_json::from_str(include_str!("../tests/fixtures/book_checksum.json")).unwrap();
+        let mut book = OrderBook::new(10);
+
+        book.apply_snapshot(&snapshot).unwrap();
+
+        assert_eq!(book.checksum(), 974947235);
+        assert!(book.apply_update(&depth_pair(&[], &[]), Some(974947235)).is_ok());
+        assert!(book.is_synced());
+    }
+
+    #[test]
+    fn checksum_digits_drop_point_and_leading_zeros() {
+        assert_eq!(checksum_digits("0.00000500"), "500");
+        assert_eq!(checksum_digits("5541.30000"), "554130000");
+        assert_eq!(checksum_digits("0.05005"), "5005");
+    }
+
+    #[test]
+    fn snapshot() {
+        let book = book();
+
+        assert!(book.is_synced());
+        assert_eq!(book.best_ask(), Some(level("5541.3", "2.507")));
+        assert_eq!(book.best_bid(), Some(level("5541.2", "1.529")));
+        assert_eq!(book.asks(5), vec![level("5541.3", "2.507"), level("5541.8", "0.33")]);
+        assert_eq!(book.bids(5), vec![level("5541.2", "1.529"), level("5539.9", "0.3")]);
+        assert_eq!(book.spread().unwrap().to_string(), "0.10000");
+        assert_eq!(book.mid_price().unwrap(), "5541.25".parse().unwrap());
+    }
+
+    #[test]
+    fn snapshot_replaces_levels() {
+        let mut book = book();
+
+        book.apply_snapshot(&depth_pair(&[("5600.0", "1.0")], &[])).unwrap();
+
+        assert_eq!(book.asks(5), vec![level("5600.0", "1.0")]);
+        assert!(book.bids(5).is_empty());
+    }
+
+    #[test]
+    fn invalid_snapshot_clears_book() {
+        let mut book = book();
+
+        assert!(book.apply_snapshot(&depth_pair(&[("5600.0", "1.0")], &[("abc", "1.0")])).is_err());
+        assert!(!book.is_synced());
+        assert!(book.asks(5).is_empty());
+    }
+
+    #[test]
+    fn level_replacement() {
+        let mut book = book();
+
+        book.apply_update(&depth_pair(&[("5541.30000", "1.00000000")], &[]), None).unwrap();
+
+        assert_eq!(book.best_ask(), Some(level("5541.3", "1")));
+        assert_eq!(book.asks(5).len(), 2);
+    }
+
+    #[test]
+    fn level_deletion() {
+        let mut book = book();
+
+        book.apply_update(&depth_pair(&[], &[("5541.20000", "0.00000000")]), None).unwrap();
+
+        assert_eq!(book.bids(5), vec![level("5539.9", "0.3")]);
+
+        // deleting a level which is not in the book is fine
+        book.apply_update(&depth_pair(&[("5999.0", "0.0")], &[]), None).unwrap();
+
+        assert_eq!(book.asks(5).len(), 2);
+    }
+
+    #[test]
+    fn depth_truncation() {
+        let mut book = OrderBook::new(2);
+
+        book.apply_snapshot(&depth_pair(
+            &[("101", "1"), ("102", "1")],
+            &[("99", "1"), ("98", "1")],
+        )).unwrap();
+        book.apply_update(&depth_pair(&[("100.5", "2")], &[("99.5", "2")]), None).unwrap();
+
+        assert_eq!(book.asks(5), vec![level("100.5", "2"), level("101", "1")]);
+        assert_eq!(book.bids(5), vec![level("99.5", "2"), level("99", "1")]);
+
+        // updates beyond the depth are dropped
+        book.apply_update(&depth_pair(&[("105", "1")], &[("90", "1")]), None).unwrap();
+
+        assert_eq!(book.asks(5), vec![level("100.5", "2"), level("101", "1")]);
+        assert_eq!(book.bids(5), vec![level("99.5", "2"), level("99", "1")]);
+    }
+
+    #[test]
+    fn checksum_mismatch() {
+        let mut book = book();
+        let checksum = book.checksum();
+
+        assert!(book.apply_update(&depth_pair(&[], &[]), Some(checksum)).is_ok());
+        assert!(book.apply_update(&depth_pair(&[], &[]), Some(checksum + 1)).is_err());
+        assert!(!book.is_synced());
+        assert!(book.asks(5).is_empty());
+
+        // updates are ignored until the next snapshot
+        assert!(book.apply_update(&depth_pair(&[("5541.3", "1.0")], &[]), Some(0)).is_ok());
+        assert!(book.asks(5).is_empty());
+    }
+
+    fn books() -> Books {
+        let mut books = Books::new(&["XBT/USD"], 10);
+
+        books.apply(PublicEvent::BookSnapshot {
+            pair: String::from("XBT/USD"),
+            depth: 10,
+            book: book_levels(),
+        });
+
+        books
+    }
+
+    fn book_levels() -> DepthPair {
+        depth_pair(&[("5541.30000", "2.50700000")], &[("5541.20000", "1.52900000")])
+    }
+
+    fn update(book: DepthPair, checksum: Option<u32>) -> PublicEvent {
+        PublicEvent::BookUpdate {
+            pair: String::from("XBT/USD"),
+            depth: 10,
+            book,
+            checksum,
+        }
+    }
+
+    #[test]
+    fn feed_updates() {
+        let mut books = books();
+
+        assert!(books.get("XBT/USD").is_some());
+        assert!(books.get("ETH/USD").is_none());
+
+        let mut expected = OrderBook::new(10);
+
+        expected.apply_snapshot(&book_levels()).unwrap();
+        expected.apply_update(&depth_pair(&[("5541.40000", "1.00000000")], &[]), None).unwrap();
+
+        assert_eq!(
+            books.apply(update(depth_pair(&[("5541.40000", "1.00000000")], &[]), Some(expected.checksum()))),
+            Some(BookChange::Changed(String::from("XBT/USD")))
+        );
+        assert_eq!(books.get("XBT/USD").unwrap().asks(5).len(), 2);
+    }
+
+    #[test]
+    fn feed_resyncs_after_checksum_mismatch() {
+        let mut books = books();
+
+        match books.apply(update(depth_pair(&[], &[]), Some(1))) {
+            Some(BookChange::OutOfSync(pair, _)) => assert_eq!(pair, "XBT/USD"),
+            other => panic!("unexpected change: {:?}", other),
+        }
+
+        assert!(books.get("XBT/USD").is_none());
+        assert_eq!(books.apply(update(depth_pair(&[], &[]), None)), None);
+
+        books.apply(PublicEvent::BookSnapshot {
+            pair: String::from("XBT/USD"),
+            depth: 10,
+            book: book_levels(),
+        });
+
+        assert!(books.get("XBT/USD").is_some());
+    }
+
+    #[test]
+    fn feed_resyncs_after_invalid_snapshot() {
+        let mut books = books();
+
+        let change = books.apply(PublicEvent::BookSnapshot {
+            pair: String::from("XBT/USD"),
+            depth: 10,
+            book: depth_pair(&[("abc", "1.0")], &[]),
+        });
+
+        match change {
+            Some(BookChange::OutOfSync(pair, _)) => assert_eq!(pair, "XBT/USD"),
+            other => panic!("unexpected change: {:?}", other),
+        }
+
+        assert!(books.get("XBT/USD").is_none());
+    }
+
+    #[test]
+    fn feed_clears_books_on_reconnect() {
+        let mut books = books();
+
+        assert_eq!(books.apply(PublicEvent::Reconnected), None);
+        assert!(books.get("XBT/USD").is_none());
+    }
+}
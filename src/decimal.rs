@@ -0,0 +1,828 @@
+//!
+//! Exact decimal numbers as used by kraken for prices, volumes and balances.
+//!
+
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::ops::{Add, Div, Mul, Neg, Sub};
+use std::str::FromStr;
+
+/// Maximum number of decimal places a `Decimal` can carry.
+pub const MAX_SCALE: u32 = 28;
+
+fn pow10(exp: u32) -> i128 {
+    10i128.pow(exp)
+}
+
+/// Like `pow10`, but `None` above 10^38.
+fn checked_pow10(exp: u32) -> Option<i128> {
+    10i128.checked_pow(exp)
+}
+
+/// How to round away decimal places which don't fit.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RoundingMode {
+    /// to the nearest value, ties away from zero
+    HalfUp,
+    /// to the nearest value, ties to the even neighbour
+    HalfEven,
+    /// towards zero, i.e. truncate
+    Down,
+    /// away from zero
+    Up,
+    /// towards negative infinity
+    Floor,
+    /// towards positive infinity
+    Ceiling,
+}
+
+/// Divides `value` by the positive `divisor`, rounding according to `mode`.
+fn div_rounded(value: i128, divisor: i128, mode: RoundingMode) -> i128 {
+    let quotient = value / divisor;
+    let remainder = (value % divisor).abs();
+
+    if remainder == 0 {
+        return quotient;
+    }
+
+    let away = quotient + value.signum();
+    let half = remainder.cmp(&(divisor - remainder));
+
+    let round_away = match mode {
+        RoundingMode::HalfUp => half != Ordering::Less,
+        RoundingMode::HalfEven => {
+            half == Ordering::Greater || (half == Ordering::Equal && quotient % 2 != 0)
+        }
+        RoundingMode::Down => false,
+        RoundingMode::Up => true,
+        RoundingMode::Floor => value < 0,
+        RoundingMode::Ceiling => value > 0,
+    };
+
+    if round_away { away } else { quotient }
+}
+
+/// Unsigned 256 bit integer in little endian 64 bit limbs, holds exact intermediate results.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Wide([u64; 4]);
+
+impl Wide {
+    fn from_u128(value: u128) -> Wide {
+        Wide([value as u64, (value >> 64) as u64, 0, 0])
+    }
+
+    fn to_u128(self) -> Option<u128> {
+        if self.0[2] == 0 && self.0[3] == 0 {
+            Some(u128::from(self.0[0]) | u128::from(self.0[1]) << 64)
+        } else {
+            None
+        }
+    }
+
+    /// Product, truncated to 256 bits.  Products of two 128 bit values always fit.
+    fn mul(self, other: Wide) -> Wide {
+        let mut limbs = [0u64; 4];
+
+        for i in 0..4 {
+            let mut carry = 0u128;
+
+            for j in 0..4 - i {
+                let value = u128::from(self.0[i]) * u128::from(other.0[j]) + u128::from(limbs[i + j]) + carry;
+
+                limbs[i + j] = value as u64;
+                carry = value >> 64;
+            }
+        }
+
+        Wide(limbs)
+    }
+
+    fn add(self, other: Wide) -> Wide {
+        let mut limbs = [0u64; 4];
+        let mut carry = false;
+
+        for (i, limb) in limbs.iter_mut().enumerate() {
+            let (value, overflow1) = self.0[i].overflowing_add(other.0[i]);
+            let (value, overflow2) = value.overflowing_add(u64::from(carry));
+
+            *limb = value;
+            carry = overflow1 || overflow2;
+        }
+
+        Wide(limbs)
+    }
+
+    /// Difference of `self >= other`.
+    fn sub(self, other: Wide) -> Wide {
+        let mut limbs = [0u64; 4];
+        let mut borrow = false;
+
+        for (i, limb) in limbs.iter_mut().enumerate() {
+            let (value, overflow1) = self.0[i].overflowing_sub(other.0[i]);
+            let (value, overflow2) = value.overflowing_sub(u64::from(borrow));
+
+            *limb = value;
+            borrow = overflow1 || overflow2;
+        }
+
+        Wide(limbs)
+    }
+
+    fn cmp(&self, other: &Wide) -> Ordering {
+        self.0.iter().rev().cmp(other.0.iter().rev())
+    }
+
+    fn div_rem(self, divisor: u64) -> (Wide, u64) {
+        let mut limbs = [0u64; 4];
+        let mut remainder = 0u128;
+
+        for i in (0..4).rev() {
+            let value = remainder << 64 | u128::from(self.0[i]);
+
+            limbs[i] = (value / u128::from(divisor)) as u64;
+            remainder = value % u128::from(divisor);
+        }
+
+        (Wide(limbs), remainder as u64)
+    }
+
+    /// Divides by `10^exp`, rounding half away from zero.
+    fn div_pow10(self, exp: u32) -> Wide {
+        let mut quotient = self;
+        let mut exp = exp;
+        let mut last = (0, 1);
+
+        // 10^19 is the largest power of ten fitting into a limb
+        while exp > 0 {
+            let step = exp.min(19);
+            let divisor = 10u64.pow(step);
+            let (value, remainder) = quotient.div_rem(divisor);
+
+            quotient = value;
+            last = (remainder, divisor);
+            exp -= step;
+        }
+
+        // the divisors are even, so the earlier remainders can't tip the last one over half
+        if u128::from(last.0) * 2 >= u128::from(last.1) && last.1 > 1 {
+            quotient = quotient.add(Wide::from_u128(1));
+        }
+
+        quotient
+    }
+}
+
+/// The magnitude of `mantissa` at a larger `scale`, which always fits for scales up to `MAX_SCALE`.
+fn widen(value: &Decimal, scale: u32) -> Wide {
+    Wide::from_u128(value.mantissa.unsigned_abs()).mul(Wide::from_u128(pow10(scale - value.scale) as u128))
+}
+
+///
+/// Builds a decimal from an exact intermediate result.
+///
+/// Keeps as many of the decimal places as fit, up to `MAX_SCALE`, rounded half away from zero.
+/// Returns `None` if not even the integer part fits.
+///
+fn from_wide(negative: bool, magnitude: Wide, scale: u32) -> Option<Decimal> {
+    (0..scale.min(MAX_SCALE) + 1).rev().find_map(|target| {
+        let mantissa = magnitude
+            .div_pow10(scale - target)
+            .to_u128()
+            .filter(|mantissa| *mantissa <= i128::MAX as u128)? as i128;
+
+        Some(Decimal {
+            mantissa: if negative { -mantissa } else { mantissa },
+            scale: target,
+        })
+    })
+}
+
+///
+/// A fixed-point decimal number.
+///
+/// The value is `mantissa * 10^-scale`, so `"0.00100000"` keeps its eight
+/// decimal places and prints exactly as kraken sent it.
+///
+/// The mantissa holds about 38 significant digits, with up to `MAX_SCALE` decimal places.
+/// Results of arithmetic keep all decimal places which fit and are rounded half away from zero
+/// beyond that.  Only results whose integer part exceeds the mantissa overflow.
+///
+/// # Example
+///
+/// ```rust
+/// use kraken::Decimal;
+///
+/// let volume: Decimal = "0.00100000".parse().unwrap();
+/// let cap: Decimal = "0.002".parse().unwrap();
+///
+/// assert!(volume < cap);
+/// assert_eq!(volume, "0.001".parse().unwrap());
+/// assert_eq!(volume.to_string(), "0.00100000");
+/// assert_eq!("-.5".parse::<Decimal>().unwrap().to_string(), "-0.5");
+/// assert_eq!((volume + cap).to_string(), "0.00300000");
+///
+/// let price: Decimal = "283.45".parse().unwrap();
+///
+/// assert_eq!((price * volume).to_string(), "0.2834500000");
+/// assert_eq!((price / Decimal::from(3)).round_dp(5).to_string(), "94.48333");
+/// ```
+///
+#[derive(Clone, Copy, Debug)]
+pub struct Decimal {
+    mantissa: i128,
+    scale: u32,
+}
+
+impl Decimal {
+    ///
+    /// Creates a decimal of `mantissa * 10^-scale`.
+    ///
+    /// # Panics
+    ///
+    /// If `scale` is larger than `MAX_SCALE`.
+    ///
+    pub fn new(mantissa: i128, scale: u32) -> Decimal {
+        assert!(scale <= MAX_SCALE, "decimal scale {} out of range", scale);
+
+        Decimal { mantissa, scale }
+    }
+
+    pub fn zero() -> Decimal {
+        Decimal::new(0, 0)
+    }
+
+    /// The unscaled integer value.
+    pub fn mantissa(&self) -> i128 {
+        self.mantissa
+    }
+
+    /// The number of decimal places.
+    pub fn scale(&self) -> u32 {
+        self.scale
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.mantissa == 0
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.mantissa < 0
+    }
+
+    /// The same value without trailing fractional zeros.
+    pub fn normalize(&self) -> Decimal {
+        let mut mantissa = self.mantissa;
+        let mut scale = self.scale;
+
+        while scale > 0 && mantissa % 10 == 0 {
+            mantissa /= 10;
+            scale -= 1;
+        }
+
+        Decimal { mantissa, scale }
+    }
+
+    ///
+    /// Rounds to at most `dp` decimal places, half away from zero.
+    ///
+    /// Values with fewer decimal places are returned unchanged.
+    ///
+    pub fn round_dp(&self, dp: u32) -> Decimal {
+        self.round_dp_with(dp, RoundingMode::HalfUp)
+    }
+
+    /// Rounds to at most `dp` decimal places according to `mode`.
+    pub fn round_dp_with(&self, dp: u32, mode: RoundingMode) -> Decimal {
+        if dp >= self.scale {
+            return *self;
+        }
+
+        Decimal {
+            mantissa: div_rounded(self.mantissa, pow10(self.scale - dp), mode),
+            scale: dp,
+        }
+    }
+
+    ///
+    /// Brings the value to exactly `dp` decimal places, rounding according to `mode`
+    /// or padding with zeros.
+    ///
+    /// # Panics
+    ///
+    /// If `dp` is larger than `MAX_SCALE` or the padded value overflows.
+    ///
+    pub fn rescale(&self, dp: u32, mode: RoundingMode) -> Decimal {
+        assert!(dp <= MAX_SCALE, "decimal scale {} out of range", dp);
+
+        if dp < self.scale {
+            self.round_dp_with(dp, mode)
+        } else {
+            Decimal {
+                mantissa: self.mantissa_at(dp).expect("decimal overflow"),
+                scale: dp,
+            }
+        }
+    }
+
+    /// Lossy conversion, e.g. for plotting or statistics.
+    pub fn to_f64(&self) -> f64 {
+        self.mantissa as f64 / 10f64.powi(self.scale as i32)
+    }
+
+    /// The mantissa at a scale of at least `self.scale`.
+    fn mantissa_at(&self, scale: u32) -> Option<i128> {
+        self.mantissa.checked_mul(pow10(scale - self.scale))
+    }
+
+    /// Sum of `self` and `other` with the sign of `other` given separately.
+    fn sum(&self, other: &Decimal, other_negative: bool) -> Option<Decimal> {
+        let scale = self.scale.max(other.scale);
+        let (a, b) = (widen(self, scale), widen(other, scale));
+
+        if self.is_negative() == other_negative {
+            return from_wide(other_negative, a.add(b), scale);
+        }
+
+        match a.cmp(&b) {
+            Ordering::Less => from_wide(other_negative, b.sub(a), scale),
+            _ => from_wide(self.is_negative(), a.sub(b), scale),
+        }
+    }
+
+    /// Addition which returns `None` on overflow.
+    pub fn checked_add(&self, other: &Decimal) -> Option<Decimal> {
+        self.sum(other, other.is_negative())
+    }
+
+    /// Subtraction which returns `None` on overflow.
+    pub fn checked_sub(&self, other: &Decimal) -> Option<Decimal> {
+        self.sum(other, !other.is_negative() && !other.is_zero())
+    }
+
+    ///
+    /// Multiplication which returns `None` on overflow.
+    ///
+    /// The scale of the product is the sum of both scales,
+    /// products beyond `MAX_SCALE` decimal places are rounded half up.
+    ///
+    pub fn checked_mul(&self, other: &Decimal) -> Option<Decimal> {
+        let a = Wide::from_u128(self.mantissa.unsigned_abs());
+        let b = Wide::from_u128(other.mantissa.unsigned_abs());
+
+        from_wide(
+            self.is_negative() != other.is_negative(),
+            a.mul(b),
+            self.scale + other.scale,
+        )
+    }
+
+    ///
+    /// Division to exactly `dp` decimal places, rounded according to `mode`.
+    ///
+    /// Returns `None` on division by zero, overflow or if `dp` is larger than `MAX_SCALE`.
+    ///
+    pub fn checked_div(&self, other: &Decimal, dp: u32, mode: RoundingMode) -> Option<Decimal> {
+        if other.is_zero() || dp > MAX_SCALE {
+            return None;
+        }
+
+        // self / other * 10^dp = self.mantissa * 10^(dp + other.scale - self.scale) / other.mantissa
+        let exp = (dp + other.scale) as i64 - self.scale as i64;
+
+        let (mut value, mut divisor) = if exp >= 0 {
+            (self.mantissa.checked_mul(checked_pow10(exp as u32)?)?, other.mantissa)
+        } else {
+            (self.mantissa, other.mantissa.checked_mul(checked_pow10((-exp) as u32)?)?)
+        };
+
+        if divisor < 0 {
+            value = value.checked_neg()?;
+            divisor = divisor.checked_neg()?;
+        }
+
+        Some(Decimal {
+            mantissa: div_rounded(value, divisor, mode),
+            scale: dp,
+        })
+    }
+
+    /// Splits into integer part and fractional digits at `scale`, rounding towards negative infinity.
+    fn split(&self, scale: u32) -> (i128, i128) {
+        let unit = pow10(self.scale);
+
+        (
+            self.mantissa.div_euclid(unit),
+            self.mantissa.rem_euclid(unit) * pow10(scale - self.scale),
+        )
+    }
+}
+
+impl PartialEq for Decimal {
+    fn eq(&self, other: &Decimal) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Decimal {}
+
+impl PartialOrd for Decimal {
+    fn partial_cmp(&self, other: &Decimal) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Decimal {
+    fn cmp(&self, other: &Decimal) -> Ordering {
+        let scale = self.scale.max(other.scale);
+
+        self.split(scale).cmp(&other.split(scale))
+    }
+}
+
+impl Hash for Decimal {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        let normalized = self.normalize();
+
+        normalized.mantissa.hash(state);
+        normalized.scale.hash(state);
+    }
+}
+
+impl FromStr for Decimal {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Decimal, String> {
+        let invalid = || format!("invalid decimal: {:?}", value);
+
+        let (negative, digits) = if let Some(rest) = value.strip_prefix('-') {
+            (true, rest)
+        } else if let Some(rest) = value.strip_prefix('+') {
+            (false, rest)
+        } else {
+            (false, value)
+        };
+
+        let mut parts = digits.splitn(2, '.');
+        let integer = parts.next().unwrap_or("");
+        let fraction = parts.next().unwrap_or("");
+
+        if integer.is_empty() && fraction.is_empty() {
+            return Err(invalid());
+        }
+
+        if !integer.chars().chain(fraction.chars()).all(|c| c.is_ascii_digit()) {
+            return Err(invalid());
+        }
+
+        if fraction.len() > MAX_SCALE as usize {
+            return Err(format!("decimal {:?} has more than {} decimal places", value, MAX_SCALE));
+        }
+
+        let mut mantissa: i128 = 0;
+
+        for c in integer.chars().chain(fraction.chars()) {
+            mantissa = mantissa
+                .checked_mul(10)
+                .and_then(|m| m.checked_add(i128::from(c as u8 - b'0')))
+                .ok_or_else(|| format!("decimal {:?} out of range", value))?;
+        }
+
+        Ok(Decimal {
+            mantissa: if negative { -mantissa } else { mantissa },
+            scale: fraction.len() as u32,
+        })
+    }
+}
+
+impl fmt::Display for Decimal {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let digits = self.mantissa.unsigned_abs().to_string();
+        let sign = if self.mantissa < 0 { "-" } else { "" };
+        let scale = self.scale as usize;
+
+        if scale == 0 {
+            return write!(f, "{}{}", sign, digits);
+        }
+
+        let digits = format!("{:0>width$}", digits, width = scale + 1);
+        let (integer, fraction) = digits.split_at(digits.len() - scale);
+
+        write!(f, "{}{}.{}", sign, integer, fraction)
+    }
+}
+
+/// Serializes as string, to keep all decimal places.
+impl Serialize for Decimal {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+struct DecimalVisitor;
+
+impl<'de> de::Visitor<'de> for DecimalVisitor {
+    type Value = Decimal;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a decimal number or a string containing one")
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<Decimal, E> {
+        value.parse().map_err(E::custom)
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> Result<Decimal, E> {
+        Ok(Decimal::from(value))
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<Decimal, E> {
+        Ok(Decimal::new(i128::from(value), 0))
+    }
+
+    fn visit_f64<E: de::Error>(self, value: f64) -> Result<Decimal, E> {
+        value.to_string().parse().map_err(E::custom)
+    }
+}
+
+/// Deserializes from kraken's string numbers as well as from plain json numbers.
+impl<'de> Deserialize<'de> for Decimal {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Decimal, D::Error> {
+        deserializer.deserialize_any(DecimalVisitor)
+    }
+}
+
+impl Add for Decimal {
+    type Output = Decimal;
+
+    fn add(self, other: Decimal) -> Decimal {
+        self.checked_add(&other).expect("decimal overflow")
+    }
+}
+
+impl Sub for Decimal {
+    type Output = Decimal;
+
+    fn sub(self, other: Decimal) -> Decimal {
+        self.checked_sub(&other).expect("decimal overflow")
+    }
+}
+
+impl Mul for Decimal {
+    type Output = Decimal;
+
+    fn mul(self, other: Decimal) -> Decimal {
+        self.checked_mul(&other).expect("decimal overflow")
+    }
+}
+
+/// Divides to as many decimal places as fit, up to `MAX_SCALE`, without trailing zeros.
+///
+/// Use `checked_div` for a fixed number of decimal places.
+impl Div for Decimal {
+    type Output = Decimal;
+
+    fn div(self, other: Decimal) -> Decimal {
+        assert!(!other.is_zero(), "decimal division by zero");
+
+        (0..MAX_SCALE + 1)
+            .rev()
+            .filter_map(|dp| self.checked_div(&other, dp, RoundingMode::HalfUp))
+            .next()
+            .expect("decimal overflow")
+            .normalize()
+    }
+}
+
+impl Neg for Decimal {
+    type Output = Decimal;
+
+    fn neg(self) -> Decimal {
+        Decimal {
+            mantissa: -self.mantissa,
+            scale: self.scale,
+        }
+    }
+}
+
+impl From<i64> for Decimal {
+    fn from(value: i64) -> Decimal {
+        Decimal::new(i128::from(value), 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json;
+
+    fn d(value: &str) -> Decimal {
+        value.parse().unwrap()
+    }
+
+    const MODES: [RoundingMode; 6] = [
+        RoundingMode::HalfUp,
+        RoundingMode::HalfEven,
+        RoundingMode::Down,
+        RoundingMode::Up,
+        RoundingMode::Floor,
+        RoundingMode::Ceiling,
+    ];
+
+    #[test]
+    fn rounding_modes() {
+        // value / 10 for each mode, in the order of `MODES`
+        let table: [(i128, [i128; 6]); 10] = [
+            (25, [3, 2, 2, 3, 2, 3]),
+            (35, [4, 4, 3, 4, 3, 4]),
+            (24, [2, 2, 2, 3, 2, 3]),
+            (26, [3, 3, 2, 3, 2, 3]),
+            (20, [2, 2, 2, 2, 2, 2]),
+            (-25, [-3, -2, -2, -3, -3, -2]),
+            (-35, [-4, -4, -3, -4, -4, -3]),
+            (-24, [-2, -2, -2, -3, -3, -2]),
+            (-26, [-3, -3, -2, -3, -3, -2]),
+            (5, [1, 0, 0, 1, 0, 1]),
+        ];
+
+        for &(value, expected) in &table {
+            for (mode, expected) in MODES.iter().zip(expected.iter()) {
+                assert_eq!(div_rounded(value, 10, *mode), *expected, "{} / 10 {:?}", value, mode);
+            }
+        }
+
+        assert_eq!(div_rounded(-5, 10, RoundingMode::HalfEven), 0);
+        assert_eq!(div_rounded(15, 10, RoundingMode::HalfEven), 2);
+        assert_eq!(div_rounded(1, 3, RoundingMode::HalfUp), 0);
+        assert_eq!(div_rounded(2, 3, RoundingMode::HalfUp), 1);
+        assert_eq!(div_rounded(0, 7, RoundingMode::Up), 0);
+    }
+
+    #[test]
+    fn round_dp_and_rescale() {
+        assert_eq!(d("1.2345").round_dp(2).to_string(), "1.23");
+        assert_eq!(d("1.2350").round_dp(2).to_string(), "1.24");
+        assert_eq!(d("-1.235").round_dp(2).to_string(), "-1.24");
+        assert_eq!(d("1.5").round_dp(3).to_string(), "1.5");
+        assert_eq!(d("1.235").round_dp_with(2, RoundingMode::HalfEven).to_string(), "1.24");
+        assert_eq!(d("1.225").round_dp_with(2, RoundingMode::HalfEven).to_string(), "1.22");
+        assert_eq!(d("1.999").round_dp_with(0, RoundingMode::Down).to_string(), "1");
+        assert_eq!(d("-1.001").round_dp_with(0, RoundingMode::Floor).to_string(), "-2");
+
+        assert_eq!(d("1.5").rescale(4, RoundingMode::HalfUp).to_string(), "1.5000");
+        assert_eq!(d("1.23456").rescale(3, RoundingMode::Down).to_string(), "1.234");
+        assert_eq!(d("1.23456").rescale(3, RoundingMode::Ceiling).to_string(), "1.235");
+        assert_eq!(d("7").rescale(0, RoundingMode::Up).to_string(), "7");
+    }
+
+    #[test]
+    fn checked_div() {
+        let (one, three) = (Decimal::from(1), Decimal::from(3));
+
+        assert_eq!(one.checked_div(&three, 4, RoundingMode::HalfUp).unwrap().to_string(), "0.3333");
+        assert_eq!(d("2").checked_div(&three, 4, RoundingMode::HalfUp).unwrap().to_string(), "0.6667");
+        assert_eq!(d("2").checked_div(&three, 4, RoundingMode::Down).unwrap().to_string(), "0.6666");
+        assert_eq!(d("-2").checked_div(&three, 2, RoundingMode::Floor).unwrap().to_string(), "-0.67");
+        assert_eq!(d("1").checked_div(&d("-8"), 3, RoundingMode::HalfUp).unwrap().to_string(), "-0.125");
+        assert_eq!(d("104610.52842").checked_div(&d("20922.10568"), 0, RoundingMode::HalfUp).unwrap(), d("5"));
+        assert_eq!(d("0.001").checked_div(&d("0.00001"), 0, RoundingMode::HalfUp).unwrap(), d("100"));
+
+        assert!(one.checked_div(&Decimal::zero(), 2, RoundingMode::HalfUp).is_none());
+        assert!(one.checked_div(&three, MAX_SCALE + 1, RoundingMode::HalfUp).is_none());
+
+        // 10^(dp + 28) does not fit into the mantissa
+        let third = one / three;
+        let tiny = d("0.0000000000000000000000000001");
+
+        assert!(one.checked_div(&third, MAX_SCALE, RoundingMode::HalfUp).is_none());
+        assert!(one.checked_div(&tiny, MAX_SCALE, RoundingMode::HalfUp).is_none());
+        assert!(tiny.checked_div(&one, MAX_SCALE, RoundingMode::HalfUp).is_some());
+        assert_eq!(one.checked_div(&third, 10, RoundingMode::HalfUp).unwrap(), d("3"));
+    }
+
+    #[test]
+    fn division() {
+        assert_eq!((Decimal::from(1) / Decimal::from(3)).to_string(), "0.3333333333333333333333333333");
+        assert_eq!((Decimal::from(10) / Decimal::from(4)).to_string(), "2.5");
+        assert_eq!((d("283.45") / d("0.5")).to_string(), "566.9");
+
+        // divisors with many decimal places trade result places for integer digits
+        let third = Decimal::from(1) / Decimal::from(3);
+
+        assert_eq!(Decimal::from(1) / third, d("3"));
+        assert_eq!(Decimal::from(-2) / third, d("-6"));
+        assert_eq!(
+            Decimal::from(1) / d("0.0000000000000000000000000001"),
+            d("10000000000000000000000000000")
+        );
+    }
+
+    #[test]
+    fn multiplication() {
+        assert_eq!((d("283.45") * d("0.001")).to_string(), "0.28345");
+        assert_eq!((d("-1.5") * d("2")).to_string(), "-3.0");
+        assert_eq!((d("-1.5") * d("-1.5")).to_string(), "2.25");
+
+        // scale 56 is rounded back to 28 decimal places
+        let third = Decimal::from(1) / Decimal::from(3);
+
+        assert_eq!((third * third).to_string(), "0.1111111111111111111111111111");
+        assert_eq!((third * -third).to_string(), "-0.1111111111111111111111111111");
+
+        // the integer part takes precedence over decimal places
+        let big = d("12345678901234567890.123456789");
+
+        assert_eq!((big * big).round_dp(0), d("152415787532388367504953515625361987875"));
+        assert_eq!((big * third).to_string(), "4115226300411522630.0411522625884773700");
+
+        let max = Decimal::new(i128::MAX, 0);
+
+        assert!(max.checked_mul(&d("2")).is_none());
+        assert_eq!(max.checked_mul(&d("0.5")).unwrap(), Decimal::new(i128::MAX / 2 + 1, 0));
+    }
+
+    #[test]
+    fn addition_and_subtraction() {
+        assert_eq!((d("0.001") + d("0.002000")).to_string(), "0.003000");
+        assert_eq!((d("1") - d("0.25")).to_string(), "0.75");
+        assert_eq!((d("0.25") - d("1")).to_string(), "-0.75");
+        assert_eq!((d("-0.25") + d("-1")).to_string(), "-1.25");
+        assert_eq!((d("-0.25") - d("-1")).to_string(), "0.75");
+        assert_eq!((d("5") - d("5")), Decimal::zero());
+        assert_eq!((d("5") - Decimal::zero()), d("5"));
+
+        // aligning scales would overflow the mantissa, the sum is rounded instead
+        let third = Decimal::from(1) / Decimal::from(3);
+        let big = d("1000000000000");
+
+        assert_eq!((big + third).to_string(), "1000000000000.33333333333333333333333333");
+        assert_eq!((big - third).to_string(), "999999999999.66666666666666666666666667");
+
+        let max = Decimal::new(i128::MAX, 0);
+
+        assert!(max.checked_add(&d("1")).is_none());
+        assert!((-max).checked_sub(&d("1")).is_none());
+        assert_eq!(max.checked_sub(&max).unwrap(), Decimal::zero());
+        assert_eq!(max.checked_add(&d("0.4")).unwrap(), max);
+    }
+
+    #[test]
+    fn parsing() {
+        assert_eq!(d("0.00100000").to_string(), "0.00100000");
+        assert_eq!(d("0.00100000").scale(), 8);
+        assert_eq!(d("-.5").to_string(), "-0.5");
+        assert_eq!(d("+1.5").to_string(), "1.5");
+        assert_eq!(d("5.").to_string(), "5");
+        assert_eq!(d("000123.40").to_string(), "123.40");
+        assert_eq!(d("0.0000000000000000000000000001"), Decimal::new(1, MAX_SCALE));
+
+        for value in &["", "-", ".", "1.2.3", "1e5", "abc", " 1", "1,5", "--1"] {
+            assert!(value.parse::<Decimal>().is_err(), "{:?}", value);
+        }
+
+        assert!("0.00000000000000000000000000001".parse::<Decimal>().is_err());
+        assert!("1000000000000000000000000000000000000000".parse::<Decimal>().is_err());
+    }
+
+    #[test]
+    fn ordering() {
+        assert_eq!(d("1.0"), d("1"));
+        assert!(d("-0.5") < d("0.25"));
+        assert!(d("-1.5") < d("-1.25"));
+        assert!(d("0.00100000") < d("0.002"));
+        assert_eq!(d("1.10").max(d("1.1")), d("1.1"));
+    }
+
+    #[test]
+    fn serde_kraken_numbers() {
+        let values: Vec<Decimal> = serde_json::from_str(r#"["0.00100000", "-12.5", 42, -7, 0.25]"#).unwrap();
+
+        assert_eq!(values[0].to_string(), "0.00100000");
+        assert_eq!(values[1], d("-12.5"));
+        assert_eq!(values[2], d("42"));
+        assert_eq!(values[3], d("-7"));
+        assert_eq!(values[4], d("0.25"));
+
+        assert_eq!(serde_json::to_string(&values[0]).unwrap(), r#""0.00100000""#);
+        assert!(serde_json::from_str::<Decimal>(r#""1e5""#).is_err());
+        assert!(serde_json::from_str::<Decimal>("null").is_err());
+    }
+
+    #[test]
+    fn wide_arithmetic() {
+        let max = Wide::from_u128(u128::MAX);
+        let square = max.mul(max);
+
+        // (2^128 - 1)^2 = 2^256 - 2^129 + 1
+        assert_eq!(square, Wide([1, 0, u64::MAX - 1, u64::MAX]));
+        assert_eq!(square.sub(square), Wide::from_u128(0));
+        assert_eq!(Wide::from_u128(1).add(max).to_u128(), None);
+        assert_eq!(Wide::from_u128(1234).div_pow10(2).to_u128(), Some(12));
+        assert_eq!(Wide::from_u128(1250).div_pow10(2).to_u128(), Some(13));
+        assert_eq!(Wide::from_u128(1249).div_pow10(2).to_u128(), Some(12));
+        assert_eq!(Wide::from_u128(pow10(38) as u128).div_pow10(38).to_u128(), Some(1));
+        assert_eq!(Wide::from_u128(5 * pow10(37) as u128).div_pow10(38).to_u128(), Some(1));
+        assert_eq!(Wide::from_u128(5 * pow10(37) as u128 - 1).div_pow10(38).to_u128(), Some(0));
+    }
+}
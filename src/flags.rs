@@ -0,0 +1,275 @@
+//!
+//! Order flags, as sent with new orders and reported back in order info.
+//!
+
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::str::FromStr;
+
+/// A single order flag
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum OrderFlag {
+    /// volume in quote currency (not available for leveraged orders)
+    Viqc,
+    /// prefer fee in base currency (default if selling)
+    Fcib,
+    /// prefer fee in quote currency (default if buying)
+    Fciq,
+    /// no market price protection
+    Nompp,
+    /// post only order (available when ordertype = limit)
+    Post,
+    /// flag unknown to this crate, kept as sent by kraken
+    Other(String),
+}
+
+impl OrderFlag {
+    pub fn as_str(&self) -> &str {
+        match *self {
+            OrderFlag::Viqc => "viqc",
+            OrderFlag::Fcib => "fcib",
+            OrderFlag::Fciq => "fciq",
+            OrderFlag::Nompp => "nompp",
+            OrderFlag::Post => "post",
+            OrderFlag::Other(ref flag) => flag,
+        }
+    }
+}
+
+impl From<&str> for OrderFlag {
+    fn from(flag: &str) -> OrderFlag {
+        match flag {
+            "viqc" => OrderFlag::Viqc,
+            "fcib" => OrderFlag::Fcib,
+            "fciq" => OrderFlag::Fciq,
+            "nompp" => OrderFlag::Nompp,
+            "post" => OrderFlag::Post,
+            other => OrderFlag::Other(String::from(other)),
+        }
+    }
+}
+
+impl fmt::Display for OrderFlag {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+///
+/// Set of order flags, in the order they were added.
+///
+/// # Example
+///
+/// ```rust
+/// use kraken::{OrderFlag, OrderFlags};
+///
+/// let flags: OrderFlags = "fciq,post".parse().unwrap();
+///
+/// assert!(flags.contains(&OrderFlag::Post));
+/// assert_eq!(flags.with(OrderFlag::Post).with(OrderFlag::Nompp).to_string(), "fciq,post,nompp");
+/// ```
+///
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct OrderFlags {
+    flags: Vec<OrderFlag>,
+}
+
+impl OrderFlags {
+    pub fn new() -> OrderFlags {
+        OrderFlags { flags: Vec::new() }
+    }
+
+    /// Adds `flag`, returns false if it was already set.
+    pub fn insert(&mut self, flag: OrderFlag) -> bool {
+        if self.contains(&flag) {
+            false
+        } else {
+            self.flags.push(flag);
+            true
+        }
+    }
+
+    /// Removes `flag`, returns false if it was not set.
+    pub fn remove(&mut self, flag: &OrderFlag) -> bool {
+        let len = self.flags.len();
+
+        self.flags.retain(|f| f != flag);
+        self.flags.len() != len
+    }
+
+    /// The same set with `flag` added.
+    pub fn with(mut self, flag: OrderFlag) -> OrderFlags {
+        self.insert(flag);
+        self
+    }
+
+    pub fn contains(&self, flag: &OrderFlag) -> bool {
+        self.flags.contains(flag)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.flags.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.flags.len()
+    }
+
+    pub fn iter(&self) -> ::std::slice::Iter<'_, OrderFlag> {
+        self.flags.iter()
+    }
+}
+
+impl ::std::iter::FromIterator<OrderFlag> for OrderFlags {
+    fn from_iter<I: IntoIterator<Item = OrderFlag>>(iter: I) -> OrderFlags {
+        let mut flags = OrderFlags::new();
+
+        for flag in iter {
+            flags.insert(flag);
+        }
+
+        flags
+    }
+}
+
+impl<'a> IntoIterator for &'a OrderFlags {
+    type Item = &'a OrderFlag;
+    type IntoIter = ::std::slice::Iter<'a, OrderFlag>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.flags.iter()
+    }
+}
+
+/// Parses kraken's comma delimited list, unknown flags become `OrderFlag::Other`.
+impl FromStr for OrderFlags {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<OrderFlags, String> {
+        Ok(
+            value
+                .split(',')
+                .map(|flag| flag.trim())
+                .filter(|flag| !flag.is_empty())
+                .map(OrderFlag::from)
+                .collect(),
+        )
+    }
+}
+
+impl fmt::Display for OrderFlags {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, flag) in self.flags.iter().enumerate() {
+            if i > 0 {
+                f.write_str(",")?;
+            }
+
+            f.write_str(flag.as_str())?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Serializes as comma delimited list, as kraken expects it.
+impl Serialize for OrderFlags {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+struct OrderFlagsVisitor;
+
+impl<'de> de::Visitor<'de> for OrderFlagsVisitor {
+    type Value = OrderFlags;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a comma delimited list of order flags")
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<OrderFlags, E> {
+        value.parse().map_err(E::custom)
+    }
+}
+
+impl<'de> Deserialize<'de> for OrderFlags {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<OrderFlags, D::Error> {
+        deserializer.deserialize_str(OrderFlagsVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json;
+
+    #[test]
+    fn single_flags() {
+        for name in &["viqc", "fcib", "fciq", "nompp", "post"] {
+            let flag = OrderFlag::from(*name);
+
+            assert!(!matches!(flag, OrderFlag::Other(_)));
+            assert_eq!(flag.as_str(), *name);
+            assert_eq!(flag.to_string(), *name);
+        }
+
+        assert_eq!(OrderFlag::from("VIQC"), OrderFlag::Other(String::from("VIQC")));
+        assert_eq!(OrderFlag::from("new").to_string(), "new");
+    }
+
+    #[test]
+    fn parse_and_display() {
+        let flags: OrderFlags = "fciq, post,,nompp,post".parse().unwrap();
+
+        assert_eq!(flags.len(), 3);
+        assert_eq!(flags.to_string(), "fciq,post,nompp");
+        assert!(flags.contains(&OrderFlag::Nompp));
+        assert!(!flags.contains(&OrderFlag::Viqc));
+
+        let flags: OrderFlags = "".parse().unwrap();
+
+        assert!(flags.is_empty());
+        assert_eq!(flags.to_string(), "");
+
+        let flags: OrderFlags = "viqc,stp".parse().unwrap();
+        let listed: Vec<&OrderFlag> = flags.iter().collect();
+
+        assert_eq!(listed, vec![&OrderFlag::Viqc, &OrderFlag::Other(String::from("stp"))]);
+        assert_eq!(flags.to_string(), "viqc,stp");
+    }
+
+    #[test]
+    fn insert_and_remove() {
+        let mut flags = OrderFlags::new();
+
+        assert!(flags.insert(OrderFlag::Post));
+        assert!(!flags.insert(OrderFlag::Post));
+        assert!(flags.insert(OrderFlag::Fcib));
+        assert_eq!(flags.to_string(), "post,fcib");
+
+        assert!(flags.remove(&OrderFlag::Post));
+        assert!(!flags.remove(&OrderFlag::Post));
+        assert_eq!(flags, OrderFlags::new().with(OrderFlag::Fcib));
+
+        let collected: OrderFlags = vec![OrderFlag::Viqc, OrderFlag::Nompp, OrderFlag::Viqc]
+            .into_iter()
+            .collect();
+
+        assert_eq!(collected.to_string(), "viqc,nompp");
+    }
+
+    #[test]
+    fn serde_comma_delimited() {
+        let flags: OrderFlags = serde_json::from_str(r#""fciq,post""#).unwrap();
+
+        assert_eq!(flags, OrderFlags::new().with(OrderFlag::Fciq).with(OrderFlag::Post));
+        assert_eq!(serde_json::to_string(&flags).unwrap(), r#""fciq,post""#);
+
+        let empty: OrderFlags = serde_json::from_str(r#""""#).unwrap();
+
+        assert!(empty.is_empty());
+
+        assert!(serde_json::from_str::<OrderFlags>(r#"["fciq"]"#).is_err());
+        assert!(serde_json::from_str::<OrderFlags>("null").is_err());
+    }
+}
@@ -13,7 +13,7 @@
 //!
 //! ## Example
 //!
-//! ```rust
+//! ```rust,no_run
 //! extern crate kraken;
 //!
 //! fn main() {
@@ -28,13 +28,12 @@
 //!
 //!   let tick = kraken::ticker("XETHZUSD").expect("could not get tick");
 //!
-//!   println!("{:?}", tick.get("XETHZUSD").a[1].parse::<f64>());
+//!   println!("{:?}", tick["XETHZUSD"].ask_price());
 //!
 //!   // ticker all pairs at once :D
 //!
-//!   let pairs = kraken.asset_pairs().expect("could not optain kraken pairs");
+//!   let pair_data = kraken::asset_pairs().expect("could not optain kraken pairs");
 //!
-//!   let pair_data = pairs.result.unwrap();
 //!   let pairs: Vec<&String> = pair_data.keys().collect();
 //!   let mut pairchain = pairs.iter().fold(
 //!     String::new(),
@@ -42,19 +41,24 @@
 //!   );
 //!   pairchain.pop();
 //!
-//!   kraken::ticker(&pairchain).and_then(|tick| {
+//!   if let Ok(tick) = kraken::ticker(&pairchain) {
 //!     // do funky stuff with a tick
-//!   });
+//!     println!("{:?}", tick);
+//!   }
 //! }
 //! ```
 //!
 extern crate base64;
+extern crate crc32fast;
 extern crate crypto;
+extern crate csv;
 extern crate curl;
 extern crate serde;
 #[macro_use]
 extern crate serde_derive;
 extern crate serde_json;
+extern crate tungstenite;
+extern crate zip;
 
 
 use crypto::digest::Digest;
@@ -62,8 +66,26 @@ use crypto::hmac::Hmac;
 use crypto::mac::Mac;
 use crypto::sha2::{Sha256, Sha512};
 use curl::easy::{Easy, List};
-use std::collections::HashMap;
-use std::io::Read;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
+use std::io::{Cursor, Read};
+use std::sync::{mpsc, Arc, Mutex, OnceLock};
+use std::thread;
+use std::str::FromStr;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+mod book;
+mod decimal;
+mod flags;
+mod manager;
+mod ws;
+
+pub use book::{BookFeed, OrderBook, PriceLevel};
+pub use decimal::{Decimal, RoundingMode};
+pub use flags::{OrderFlag, OrderFlags};
+pub use manager::{AccountManager, Tier};
+pub use ws::{Channel, OrderChange, OwnTrade, PrivateChannel, PrivateClient, PrivateEvent, PublicClient,
+             PublicEvent, Subscription};
 
 
 ///
@@ -84,6 +106,69 @@ pub struct Time {
     pub rfc1123: String,
 }
 
+impl Time {
+    pub fn unixtime(&self) -> SystemTime {
+        timestamp(self.unixtime as f64)
+    }
+
+    /// Parses `rfc1123`, e.g. "Sun,  8 Oct 17 19:09:38 +0000".  Two digit years are taken as 20xx.
+    pub fn parse_rfc1123(&self) -> Result<SystemTime, String> {
+        let invalid = || format!("invalid RFC 1123 time: {}", self.rfc1123);
+
+        let parts = self.rfc1123.split_whitespace().collect::<Vec<&str>>();
+
+        if parts.len() != 6 {
+            return Err(invalid());
+        }
+
+        let day = parts[1].parse::<i64>().map_err(|_| invalid())?;
+        let month = [
+            "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+        ].iter()
+            .position(|m| *m == parts[2])
+            .ok_or_else(invalid)? as i64 + 1;
+        let year = match parts[3].parse::<i64>().map_err(|_| invalid())? {
+            year if year < 100 => year + 2000,
+            year => year,
+        };
+
+        let time = parts[4]
+            .split(':')
+            .map(|x| x.parse::<i64>())
+            .collect::<Result<Vec<i64>, _>>()
+            .map_err(|_| invalid())?;
+
+        if time.len() != 3 {
+            return Err(invalid());
+        }
+
+        let zone = parts[5];
+        let offset = match (zone.get(..1), zone.get(1..3), zone.get(3..5)) {
+            _ if zone == "GMT" || zone == "UTC" => 0,
+            (Some(sign), Some(hours), Some(minutes)) if zone.len() == 5 => {
+                let hours = hours.parse::<i64>().map_err(|_| invalid())?;
+                let minutes = minutes.parse::<i64>().map_err(|_| invalid())?;
+
+                match sign {
+                    "+" => hours * 3600 + minutes * 60,
+                    "-" => -(hours * 3600 + minutes * 60),
+                    _ => return Err(invalid()),
+                }
+            }
+            _ => return Err(invalid()),
+        };
+
+        let secs = days_from_civil(year, month, day) * 86_400 + time[0] * 3600 + time[1] * 60 +
+            time[2] - offset;
+
+        if secs < 0 {
+            return Err(invalid());
+        }
+
+        Ok(timestamp(secs as f64))
+    }
+}
+
 /// A currency asset
 #[derive(Deserialize, Serialize, Debug)]
 pub struct Asset {
@@ -97,6 +182,18 @@ pub struct Asset {
     pub display_decimals: u32,
 }
 
+impl Asset {
+    /// Brings an amount to the asset's decimal places for record keeping.
+    pub fn round_amount(&self, amount: Decimal, mode: RoundingMode) -> Decimal {
+        amount.rescale(self.decimals, mode)
+    }
+
+    /// Brings an amount to the asset's decimal places for display.
+    pub fn display_amount(&self, amount: Decimal) -> Decimal {
+        amount.rescale(self.display_decimals, RoundingMode::HalfUp)
+    }
+}
+
 /// Ticker info
 #[derive(Deserialize, Serialize, Debug)]
 pub struct Tick {
@@ -120,6 +217,68 @@ pub struct Tick {
     pub o: String,
 }
 
+impl Tick {
+    pub fn ask_price(&self) -> Result<Decimal, String> {
+        decimal_at(&self.a, 0)
+    }
+
+    pub fn ask_volume(&self) -> Result<Decimal, String> {
+        decimal_at(&self.a, 2)
+    }
+
+    pub fn bid_price(&self) -> Result<Decimal, String> {
+        decimal_at(&self.b, 0)
+    }
+
+    pub fn bid_volume(&self) -> Result<Decimal, String> {
+        decimal_at(&self.b, 2)
+    }
+
+    pub fn last_price(&self) -> Result<Decimal, String> {
+        decimal_at(&self.c, 0)
+    }
+
+    pub fn last_volume(&self) -> Result<Decimal, String> {
+        decimal_at(&self.c, 1)
+    }
+
+    pub fn volume_today(&self) -> Result<Decimal, String> {
+        decimal_at(&self.v, 0)
+    }
+
+    pub fn volume_24h(&self) -> Result<Decimal, String> {
+        decimal_at(&self.v, 1)
+    }
+
+    pub fn vwap_today(&self) -> Result<Decimal, String> {
+        decimal_at(&self.p, 0)
+    }
+
+    pub fn vwap_24h(&self) -> Result<Decimal, String> {
+        decimal_at(&self.p, 1)
+    }
+
+    pub fn low_today(&self) -> Result<Decimal, String> {
+        decimal_at(&self.l, 0)
+    }
+
+    pub fn low_24h(&self) -> Result<Decimal, String> {
+        decimal_at(&self.l, 1)
+    }
+
+    pub fn high_today(&self) -> Result<Decimal, String> {
+        decimal_at(&self.h, 0)
+    }
+
+    pub fn high_24h(&self) -> Result<Decimal, String> {
+        decimal_at(&self.h, 1)
+    }
+
+    pub fn open(&self) -> Result<Decimal, String> {
+        self.o.parse()
+    }
+}
+
 /// Tradable asset pairs
 #[derive(Deserialize, Serialize, Debug)]
 pub struct AssetPair {
@@ -139,6 +298,9 @@ pub struct AssetPair {
     pub pair_decimals: u32,
     /// scaling decimal places for volume
     pub lot_decimals: u32,
+    /// scaling decimal places for cost
+    #[serde(default)]
+    pub cost_decimals: Option<u32>,
     /// amount to multiply lot volume by to get currency volume
     pub lot_multiplier: u32,
     /// array of leverage amounts available when buying
@@ -155,13 +317,127 @@ pub struct AssetPair {
     pub margin_call: u32,
     /// stop-out/liquidation margin level
     pub margin_stop: u32,
+    /// minimum order size (in terms of base currency)
+    pub ordermin: Option<String>,
+    /// minimum order cost (in terms of quote currency)
+    pub costmin: Option<String>,
+}
+
+impl AssetPair {
+    /// Brings a price to the pair's decimal places.
+    pub fn round_price(&self, price: Decimal, mode: RoundingMode) -> Decimal {
+        price.rescale(self.pair_decimals, mode)
+    }
+
+    /// Brings a volume to the pair's lot decimal places.
+    pub fn round_volume(&self, volume: Decimal, mode: RoundingMode) -> Decimal {
+        volume.rescale(self.lot_decimals, mode)
+    }
+
+    pub fn ordermin(&self) -> Result<Option<Decimal>, String> {
+        optional_decimal(&self.ordermin)
+    }
+
+    pub fn costmin(&self) -> Result<Option<Decimal>, String> {
+        optional_decimal(&self.costmin)
+    }
 }
 
 /// Open High Low Close data
 pub type OHLC = HashMap<String, serde_json::Value>;
 
+/// A single OHLC frame, deserialized from kraken's array form
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct OhlcFrame {
+    /// unix timestamp of the frame's start
+    pub time: i64,
+    pub open: Decimal,
+    pub high: Decimal,
+    pub low: Decimal,
+    pub close: Decimal,
+    /// volume weighted average price
+    pub vwap: Decimal,
+    pub volume: Decimal,
+    /// number of trades
+    pub count: u64,
+}
+
+impl OhlcFrame {
+    pub fn time(&self) -> SystemTime {
+        timestamp(self.time as f64)
+    }
+}
+
+/// OHLC frames of a pair
+#[derive(Deserialize, Serialize, Debug)]
+pub struct OhlcFrames {
+    /// frames, oldest first.  the last one is the current, not-yet-committed frame
+    pub frames: Vec<OhlcFrame>,
+    /// id to be used as `since` when polling for new, committed OHLC data
+    pub last: i64,
+}
+
+/// A public trade
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct Trade {
+    pub price: Decimal,
+    pub volume: Decimal,
+    /// unix timestamp of the trade
+    pub time: f64,
+    /// side of the taker (buy/sell)
+    pub kind: TradeType,
+    /// order type of the taker (market/limit)
+    pub ordertype: OrderType,
+    /// miscellaneous info
+    pub misc: String,
+}
+
+impl Trade {
+    pub fn time(&self) -> SystemTime {
+        timestamp(self.time)
+    }
+}
+
+/// Best bid and ask at a point in time
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct Spread {
+    /// unix timestamp of the spread
+    pub time: f64,
+    pub bid: Decimal,
+    pub ask: Decimal,
+    /// volume at the best bid (websocket feed only)
+    pub bid_volume: Option<Decimal>,
+    /// volume at the best ask (websocket feed only)
+    pub ask_volume: Option<Decimal>,
+}
+
+impl Spread {
+    pub fn time(&self) -> SystemTime {
+        timestamp(self.time)
+    }
+}
+
+/// Recent trades of a pair
+#[derive(Deserialize, Serialize, Debug)]
+pub struct RecentTrades {
+    /// trades, oldest first
+    pub trades: Vec<Trade>,
+    /// id to be used as `since` when polling for new trade data
+    pub last: String,
+}
+
+/// Recent spreads of a pair
+#[derive(Deserialize, Serialize, Debug)]
+pub struct RecentSpreads {
+    /// spreads, oldest first
+    pub spreads: Vec<Spread>,
+    /// id to be used as `since` when polling for new spread data
+    pub last: i64,
+}
+
+/// A price level: price, volume, unix timestamp
 #[derive(Deserialize, Serialize, Debug)]
-pub struct DepthPairTuple(String, String, i64);
+pub struct DepthPairTuple(pub String, pub String, pub i64);
 
 #[derive(Deserialize, Serialize, Debug)]
 pub struct DepthPair {
@@ -193,19 +469,289 @@ pub struct TradeBalance {
     pub ml: Option<String>,
 }
 
+impl TradeBalance {
+    pub fn equivalent_balance(&self) -> Result<Decimal, String> {
+        self.eb.parse()
+    }
+
+    pub fn trade_balance(&self) -> Result<Decimal, String> {
+        self.tb.parse()
+    }
+
+    pub fn margin(&self) -> Result<Decimal, String> {
+        self.m.parse()
+    }
+
+    pub fn unrealized_net(&self) -> Result<Decimal, String> {
+        self.n.parse()
+    }
+
+    pub fn cost_basis(&self) -> Result<Decimal, String> {
+        self.c.parse()
+    }
+
+    pub fn valuation(&self) -> Result<Decimal, String> {
+        self.v.parse()
+    }
+
+    pub fn equity(&self) -> Result<Decimal, String> {
+        self.e.parse()
+    }
+
+    pub fn free_margin(&self) -> Result<Decimal, String> {
+        self.mf.parse()
+    }
+
+    pub fn margin_level(&self) -> Result<Option<Decimal>, String> {
+        optional_decimal(&self.ml)
+    }
+}
+
+/// Extended balance of an asset
+#[derive(Deserialize, Serialize, Debug)]
+pub struct ExtendedBalance {
+    /// total balance
+    pub balance: Decimal,
+    /// balance on hold for open orders
+    pub hold_trade: Option<Decimal>,
+    /// credit available
+    pub credit: Option<Decimal>,
+    /// credit used
+    pub credit_used: Option<Decimal>,
+}
+
+impl ExtendedBalance {
+    /// Amount available to trade: balance plus unused credit minus the amount held by open orders.
+    pub fn available(&self) -> Decimal {
+        let zero = Decimal::zero();
+
+        self.balance + self.credit.unwrap_or(zero) - self.credit_used.unwrap_or(zero) -
+            self.hold_trade.unwrap_or(zero)
+    }
+}
+
+/// Fee tier info of an asset pair
+#[derive(Deserialize, Serialize, Debug)]
+pub struct FeeInfo {
+    /// current fee in percent
+    pub fee: String,
+    /// minimum fee for pair (if not fixed fee)
+    pub minfee: Option<String>,
+    /// maximum fee for pair (if not fixed fee)
+    pub maxfee: Option<String>,
+    /// next tier's fee for pair (if not fixed fee.  null if at lowest fee tier)
+    pub nextfee: Option<String>,
+    /// volume level of next tier (if not fixed fee.  null if at lowest fee tier)
+    pub nextvolume: Option<String>,
+    /// volume level of current tier (if not fixed fee.  null if at lowest fee tier)
+    pub tiervolume: Option<String>,
+}
+
+impl FeeInfo {
+    pub fn fee(&self) -> Result<Decimal, String> {
+        self.fee.parse()
+    }
+
+    pub fn minfee(&self) -> Result<Option<Decimal>, String> {
+        optional_decimal(&self.minfee)
+    }
+
+    pub fn maxfee(&self) -> Result<Option<Decimal>, String> {
+        optional_decimal(&self.maxfee)
+    }
+
+    pub fn nextfee(&self) -> Result<Option<Decimal>, String> {
+        optional_decimal(&self.nextfee)
+    }
+
+    pub fn nextvolume(&self) -> Result<Option<Decimal>, String> {
+        optional_decimal(&self.nextvolume)
+    }
+
+    pub fn tiervolume(&self) -> Result<Option<Decimal>, String> {
+        optional_decimal(&self.tiervolume)
+    }
+}
+
+/// Trade volume result
+#[derive(Deserialize, Serialize, Debug)]
+pub struct TradeVolume {
+    /// volume currency
+    pub currency: String,
+    /// current discount volume
+    pub volume: String,
+    /// fee tier info per asset pair (if requested)
+    pub fees: Option<HashMap<String, FeeInfo>>,
+    /// maker fee tier info per asset pair (if requested and on maker/taker)
+    pub fees_maker: Option<HashMap<String, FeeInfo>>,
+}
+
+impl TradeVolume {
+    pub fn volume(&self) -> Result<Decimal, String> {
+        self.volume.parse()
+    }
+}
+
 #[derive(Deserialize, Serialize, Debug)]
 pub struct OrderDescription {
     pub leverage: String,
+    /// human-readable order description, see `parsed`
     pub order: String,
-    pub ordertype: String,
+    pub ordertype: OrderType,
     pub pair: String,
     pub price: String,
     pub price2: String,
     #[serde(rename = "type")]
-    pub kind: String,
+    pub kind: TradeType,
 }
 
-#[derive(Deserialize, Serialize, Debug)]
+impl OrderDescription {
+    /// Parses the human-readable `order` description.
+    pub fn parsed(&self) -> Result<ParsedOrder, String> {
+        self.order.parse()
+    }
+}
+
+///
+/// An order parsed from kraken's human-readable order description.
+///
+/// # Example
+///
+/// ```rust
+/// use kraken::{OrderType, ParsedOrder, TradeType};
+///
+/// let order: ParsedOrder = "sell 1.5 XBTUSD @ stop loss 100.0 -> limit 99.5 with 2:1 leverage"
+///     .parse()
+///     .unwrap();
+///
+/// assert_eq!(order.kind, TradeType::Sell);
+/// assert_eq!(order.volume.to_string(), "1.5");
+/// assert_eq!(order.pair, "XBTUSD");
+/// assert_eq!(order.order_type, OrderType::StopLossLimit);
+/// assert_eq!(order.price.as_ref().unwrap(), "100.0");
+/// assert_eq!(order.price2.as_ref().unwrap(), "99.5");
+/// assert_eq!(order.leverage, Some(2));
+/// ```
+///
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct ParsedOrder {
+    pub kind: TradeType,
+    pub volume: Decimal,
+    pub pair: String,
+    pub order_type: OrderType,
+    /// price, as given by kraken (may be relative for trailing stops)
+    pub price: Option<String>,
+    /// secondary price, as given by kraken
+    pub price2: Option<String>,
+    pub leverage: Option<u32>,
+}
+
+impl FromStr for ParsedOrder {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<ParsedOrder, String> {
+        let invalid = || format!("invalid order description: {}", value);
+
+        let (order, leverage) = match value.find(" with ") {
+            Some(i) => {
+                let leverage = value[i + 6..]
+                    .strip_suffix(" leverage")
+                    .and_then(|leverage| leverage.split(':').next())
+                    .and_then(|leverage| leverage.parse::<u32>().ok())
+                    .ok_or_else(invalid)?;
+
+                (&value[..i], Some(leverage))
+            }
+            None => (value, None),
+        };
+
+        let mut parts = order.splitn(2, " @ ");
+        let side = parts.next().ok_or_else(invalid)?;
+        let prices = parts.next().ok_or_else(invalid)?;
+
+        let side = side.split_whitespace().collect::<Vec<&str>>();
+
+        if side.len() != 3 {
+            return Err(invalid());
+        }
+
+        let (order_type, price, price2) = parse_order_prices(prices).ok_or_else(invalid)?;
+
+        Ok(ParsedOrder {
+            kind: side[0].parse()?,
+            volume: side[1].parse()?,
+            pair: String::from(side[2]),
+            order_type,
+            price,
+            price2,
+            leverage,
+        })
+    }
+}
+
+///
+/// Parses the order type and prices of an order description, e.g. "stop loss 100.0 -> limit 99.5".
+///
+/// Unknown order types become `OrderType::Other`, named after their labels, e.g. "iceberg".
+/// Returns `None` for known order types with missing or extra prices.
+///
+fn parse_order_prices(value: &str) -> Option<(OrderType, Option<String>, Option<String>)> {
+    let mut segments = Vec::new();
+
+    for segment in value.trim().split(" -> ") {
+        let segment = segment.trim();
+        let (label, price) = match segment.rfind(' ') {
+            Some(i) if is_price(&segment[i + 1..]) => (segment[..i].trim(), Some(&segment[i + 1..])),
+            _ if is_price(segment) => return None,
+            _ => (segment, None),
+        };
+
+        if label.is_empty() {
+            return None;
+        }
+
+        segments.push((label, price));
+    }
+
+    let labels = segments.iter().map(|&(label, _)| label).collect::<Vec<&str>>();
+    let mut prices = segments.iter().map(|&(_, price)| price.map(String::from));
+    let price = prices.next().and_then(|price| price);
+    let price2 = prices.next().and_then(|price| price);
+
+    let order_type = match labels.as_slice() {
+        ["market"] => OrderType::Market,
+        ["settle position"] => OrderType::SettlePosition,
+        ["limit"] => OrderType::Limit,
+        ["stop loss"] => OrderType::StopLoss,
+        ["take profit"] => OrderType::TakeProfit,
+        ["trailing stop"] => OrderType::TrailingStop,
+        ["stop loss", "limit"] => OrderType::StopLossLimit,
+        ["take profit", "limit"] => OrderType::TakeProfitLimit,
+        ["trailing stop", "limit"] => OrderType::TrailingStopLimit,
+        ["stop loss", "take profit"] => OrderType::StopLossProfit,
+        _ => {
+            let order_type = labels.join("-").replace(' ', "-");
+
+            return Some((OrderType::Other(order_type), price, price2));
+        }
+    };
+
+    if order_type.needs_price() != price.is_some() || order_type.needs_price2() != price2.is_some() {
+        return None;
+    }
+
+    Some((order_type, price, price2))
+}
+
+/// Whether `value` looks like an absolute or relative price of an order description, e.g. "+50.0" or "#2%".
+fn is_price(value: &str) -> bool {
+    let value = value.trim_start_matches(['+', '-', '#']);
+    let value = value.strip_suffix('%').unwrap_or(value);
+
+    !value.is_empty() && value.parse::<Decimal>().is_ok()
+}
+
+#[derive(Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Debug)]
 #[serde(rename_all = "lowercase")]
 pub enum OrderStatus {
     /// order pending book entry
@@ -238,12 +784,8 @@ pub struct OrderInfo {
     /// + liquidated = liquidation
     /// + partial = partial fill
     pub misc: String,
-    /// comma delimited list of order flags:
-    /// + viqc = volume in quote currency
-    /// + fcib = prefer fee in base currency (default if selling)
-    /// + fciq = prefer fee in quote currency (default if buying)
-    /// + nompp = no market price protection
-    pub oflags: String,
+    /// order flags
+    pub oflags: OrderFlags,
     /// unix timestamp of when order was placed
     pub opentm: f64,
     /// average price (quote currency unless viqc set in oflags)
@@ -261,13 +803,63 @@ pub struct OrderInfo {
     /// status of order:
     pub status: OrderStatus,
     /// user reference id
+    #[serde(default, deserialize_with = "string_or_number")]
     pub userref: Option<String>,
+    /// client order id (if set)
+    #[serde(default)]
+    pub cl_ord_id: Option<String>,
     /// volume of order (base currency unless viqc set in oflags)
     pub vol: String,
     /// volume executed (base currency unless viqc set in oflags)
     pub vol_exec: String,
 }
 
+impl OrderInfo {
+    pub fn opentm(&self) -> SystemTime {
+        timestamp(self.opentm)
+    }
+
+    pub fn closetm(&self) -> Option<SystemTime> {
+        self.closetm.and_then(optional_timestamp)
+    }
+
+    pub fn starttm(&self) -> Option<SystemTime> {
+        optional_timestamp(self.starttm)
+    }
+
+    pub fn expiretm(&self) -> Option<SystemTime> {
+        optional_timestamp(self.expiretm)
+    }
+
+    pub fn cost(&self) -> Result<Decimal, String> {
+        self.cost.parse()
+    }
+
+    pub fn fee(&self) -> Result<Decimal, String> {
+        self.fee.parse()
+    }
+
+    pub fn price(&self) -> Result<Decimal, String> {
+        self.price.parse()
+    }
+
+    pub fn stopprice(&self) -> Result<Option<Decimal>, String> {
+        optional_decimal(&self.stopprice)
+    }
+
+    pub fn limitprice(&self) -> Result<Option<Decimal>, String> {
+        optional_decimal(&self.limitprice)
+    }
+
+    pub fn vol(&self) -> Result<Decimal, String> {
+        self.vol.parse()
+    }
+
+    pub fn vol_exec(&self) -> Result<Decimal, String> {
+        self.vol_exec.parse()
+    }
+}
+
 /// Open orders
 #[derive(Deserialize, Serialize, Debug)]
 pub struct OpenOrders {
@@ -281,23 +873,59 @@ pub struct ClosedOrders {
     pub count: u32,
 }
 
-#[derive(Deserialize, Serialize, Debug)]
+#[derive(Deserialize, Serialize, Debug, Clone, Copy)]
 pub enum ClosedOrdersConfigCloseTime {
     Open,
     Close,
     Both,
 }
 
-#[derive(Deserialize, Serialize, Debug)]
+/// Start or end of a closed orders query
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub enum ClosedOrdersCursor {
+    /// unix timestamp
+    Timestamp(i64),
+    /// order tx id, the order's open time is used
+    TxId(String),
+}
+
+impl fmt::Display for ClosedOrdersCursor {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ClosedOrdersCursor::Timestamp(timestamp) => write!(f, "{}", timestamp),
+            ClosedOrdersCursor::TxId(ref txid) => write!(f, "{}", txid),
+        }
+    }
+}
+
+impl From<i64> for ClosedOrdersCursor {
+    fn from(timestamp: i64) -> ClosedOrdersCursor {
+        ClosedOrdersCursor::Timestamp(timestamp)
+    }
+}
+
+impl<'a> From<&'a str> for ClosedOrdersCursor {
+    fn from(txid: &'a str) -> ClosedOrdersCursor {
+        ClosedOrdersCursor::TxId(String::from(txid))
+    }
+}
+
+impl From<String> for ClosedOrdersCursor {
+    fn from(txid: String) -> ClosedOrdersCursor {
+        ClosedOrdersCursor::TxId(txid)
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
 pub struct ClosedOrdersConfig {
     /// whether or not to include trades in output (optional.  default = false).
     pub trades: Option<bool>,
     /// restrict results to given user reference id (optional).
     pub userref: Option<String>,
     /// starting unix timestamp or order tx id of results (optional.  exclusive).
-    pub start: Option<i64>,
+    pub start: Option<ClosedOrdersCursor>,
     /// ending unix timestamp or order tx id of results (optional.  inclusive).
-    pub end: Option<i64>,
+    pub end: Option<ClosedOrdersCursor>,
     /// result offset.
     pub ofs: Option<u64>,
     /// which time to use (optional).
@@ -308,33 +936,203 @@ pub struct ClosedOrdersConfig {
 #[derive(Deserialize, Serialize, Debug)]
 pub struct CanceldOrders {
     /// number of orders canceled
-    count: u32,
+    pub count: u32,
     /// if set, order(s) is/are pending cancellation
-    pending: u32,
+    pub pending: Option<bool>,
 }
 
+/// Cancel all orders after result
 #[derive(Deserialize, Serialize, Debug)]
-pub enum TradeType {
-    Buy,
-    Sell,
+pub struct CancelAllOrdersAfter {
+    /// server time when the request was received, in RFC 3339 format
+    #[serde(rename = "currentTime")]
+    pub current_time: String,
+    /// time when all open orders will be canceled, in RFC 3339 format, or "0" if disabled
+    #[serde(rename = "triggerTime")]
+    pub trigger_time: String,
 }
 
+/// Open margin position
 #[derive(Deserialize, Serialize, Debug)]
-pub enum OrderType {
-    Market,
-    /// (price = limit price)
-    Limit,
-    /// (price = stop loss price)
-    StopLoss,
-    /// (price = take profit price)
-    TakeProfit,
-    /// (price = stop loss price, price2 = take profit price)
-    StopLossProfit,
-    /// (price = stop loss price, price2 = take profit price)
-    StopLossProfitLimit,
-    /// (price = stop loss trigger price, price2 = triggered limit price)
-    StopLossLimit,
-    /// (price = take profit trigger price, price2 = triggered limit price)
+pub struct Position {
+    /// order responsible for execution of trade
+    pub ordertxid: String,
+    /// status of position
+    pub posstatus: Option<String>,
+    /// asset pair
+    pub pair: String,
+    /// unix timestamp of trade
+    pub time: f64,
+    /// type of order used to open position (buy/sell)
+    #[serde(rename = "type")]
+    pub kind: TradeType,
+    /// order type used to open position
+    pub ordertype: OrderType,
+    /// opening cost of position (quote currency unless viqc set in oflags)
+    pub cost: String,
+    /// opening fee of position (quote currency)
+    pub fee: String,
+    /// position volume (base currency unless viqc set in oflags)
+    pub vol: String,
+    /// position volume closed (base currency unless viqc set in oflags)
+    pub vol_closed: String,
+    /// initial margin (quote currency)
+    pub margin: String,
+    /// current value of remaining position (if docalcs requested.  quote currency)
+    pub value: Option<String>,
+    /// unrealized profit/loss of remaining position (if docalcs requested.  quote currency, quote currency scale)
+    pub net: Option<String>,
+    /// funding cost and term of position
+    pub terms: Option<String>,
+    /// unix timestamp of next margin rollover
+    pub rollovertm: Option<String>,
+    /// comma delimited list of miscellaneous info
+    pub misc: String,
+    /// order flags
+    pub oflags: OrderFlags,
+}
+
+impl Position {
+    pub fn time(&self) -> SystemTime {
+        timestamp(self.time)
+    }
+
+    pub fn rollovertm(&self) -> Result<Option<SystemTime>, String> {
+        match self.rollovertm {
+            Some(ref rollovertm) => rollovertm
+                .parse::<f64>()
+                .map(optional_timestamp)
+                .map_err(|e| format!("{:?}", e)),
+            None => Ok(None),
+        }
+    }
+
+    pub fn cost(&self) -> Result<Decimal, String> {
+        self.cost.parse()
+    }
+
+    pub fn fee(&self) -> Result<Decimal, String> {
+        self.fee.parse()
+    }
+
+    pub fn vol(&self) -> Result<Decimal, String> {
+        self.vol.parse()
+    }
+
+    pub fn vol_closed(&self) -> Result<Decimal, String> {
+        self.vol_closed.parse()
+    }
+
+    pub fn margin(&self) -> Result<Decimal, String> {
+        self.margin.parse()
+    }
+
+    pub fn value(&self) -> Result<Option<Decimal>, String> {
+        optional_decimal(&self.value)
+    }
+
+    pub fn net(&self) -> Result<Option<Decimal>, String> {
+        optional_decimal(&self.net)
+    }
+
+    ///
+    /// Builds a market order which settles this position.
+    ///
+    /// The leverage is derived from the opening cost and the initial margin.
+    /// The volume is set to `0`, which lets kraken auto-fill the volume needed
+    /// to close out the position.
+    ///
+    pub fn closing_order(&self) -> Result<NewOrder, String> {
+        let cost = self.cost()?;
+        let margin = self.margin()?;
+
+        if margin <= Decimal::zero() {
+            return Err(format!("position has no margin: {}", self.margin));
+        }
+
+        let leverage = cost
+            .checked_div(&margin, 0, RoundingMode::HalfUp)
+            .ok_or_else(|| format!("invalid leverage: {} / {}", cost, margin))?;
+
+        let kind = match self.kind {
+            TradeType::Buy => TradeType::Sell,
+            TradeType::Sell => TradeType::Buy,
+        };
+
+        Ok(NewOrder {
+            pair: self.pair.clone(),
+            kind,
+            order_type: OrderType::Market,
+            price: None,
+            price2: None,
+            volume: String::from("0"),
+            leverage: Some(leverage.to_string()),
+            oflags: None,
+            timeinforce: None,
+            starttm: None,
+            expiretm: None,
+            userref: None,
+            validate: None,
+            close: None,
+            reduce_only: None,
+            cl_ord_id: None,
+            trigger: None,
+            stptype: None,
+        })
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum TradeType {
+    Buy,
+    Sell,
+}
+
+impl TradeType {
+    fn as_str(&self) -> &'static str {
+        match *self {
+            TradeType::Buy => "buy",
+            TradeType::Sell => "sell",
+        }
+    }
+}
+
+impl fmt::Display for TradeType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for TradeType {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<TradeType, String> {
+        match value {
+            "buy" => Ok(TradeType::Buy),
+            "sell" => Ok(TradeType::Sell),
+            other => Err(format!("unknown trade type: {}", other)),
+        }
+    }
+}
+
+/// Order type, (de)serialized by its wire name, e.g. "stop-loss-limit"
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OrderType {
+    Market,
+    /// (price = limit price)
+    Limit,
+    /// (price = stop loss price)
+    StopLoss,
+    /// (price = take profit price)
+    TakeProfit,
+    /// (price = stop loss price, price2 = take profit price)
+    StopLossProfit,
+    /// (price = stop loss price, price2 = take profit price)
+    StopLossProfitLimit,
+    /// (price = stop loss trigger price, price2 = triggered limit price)
+    StopLossLimit,
+    /// (price = take profit trigger price, price2 = triggered limit price)
     TakeProfitLimit,
     /// (price = trailing stop offset)
     TrailingStop,
@@ -343,6 +1141,321 @@ pub enum OrderType {
     /// (price = stop loss price, price2 = limit price)
     StopLossAndLimit,
     SettlePosition,
+    /// order type unknown to this crate, kept as sent by kraken
+    Other(String),
+}
+
+impl OrderType {
+    fn as_str(&self) -> &str {
+        match *self {
+            OrderType::Market => "market",
+            OrderType::Limit => "limit",
+            OrderType::StopLoss => "stop-loss",
+            OrderType::TakeProfit => "take-profit",
+            OrderType::StopLossProfit => "stop-loss-profit",
+            OrderType::StopLossProfitLimit => "stop-loss-profit-limit",
+            OrderType::StopLossLimit => "stop-loss-limit",
+            OrderType::TakeProfitLimit => "take-profit-limit",
+            OrderType::TrailingStop => "trailing-stop",
+            OrderType::TrailingStopLimit => "trailing-stop-limit",
+            OrderType::StopLossAndLimit => "stop-loss-and-limit",
+            OrderType::SettlePosition => "settle-position",
+            OrderType::Other(ref order_type) => order_type,
+        }
+    }
+
+    const ALL: [OrderType; 12] = [
+        OrderType::Market,
+        OrderType::Limit,
+        OrderType::StopLoss,
+        OrderType::TakeProfit,
+        OrderType::StopLossProfit,
+        OrderType::StopLossProfitLimit,
+        OrderType::StopLossLimit,
+        OrderType::TakeProfitLimit,
+        OrderType::TrailingStop,
+        OrderType::TrailingStopLimit,
+        OrderType::StopLossAndLimit,
+        OrderType::SettlePosition,
+    ];
+
+    /// Whether orders of this type need a `price`.  Unknown order types are taken to have one.
+    pub fn needs_price(&self) -> bool {
+        !matches!(*self, OrderType::Market | OrderType::SettlePosition)
+    }
+
+    /// Whether orders of this type need a secondary price `price2`.
+    pub fn needs_price2(&self) -> bool {
+        matches!(
+            *self,
+            OrderType::StopLossProfit |
+            OrderType::StopLossProfitLimit |
+            OrderType::StopLossLimit |
+            OrderType::TakeProfitLimit |
+            OrderType::TrailingStopLimit |
+            OrderType::StopLossAndLimit
+        )
+    }
+}
+
+impl fmt::Display for OrderType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl From<&str> for OrderType {
+    fn from(order_type: &str) -> OrderType {
+        OrderType::ALL
+            .iter()
+            .find(|known| known.as_str() == order_type)
+            .cloned()
+            .unwrap_or_else(|| OrderType::Other(String::from(order_type)))
+    }
+}
+
+/// Parses the wire names, e.g. "stop-loss-limit".  Unknown names become `OrderType::Other`.
+impl FromStr for OrderType {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<OrderType, String> {
+        Ok(OrderType::from(value))
+    }
+}
+
+impl serde::Serialize for OrderType {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for OrderType {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<OrderType, D::Error> {
+        let order_type: String = serde::Deserialize::deserialize(deserializer)?;
+
+        Ok(OrderType::from(order_type.as_str()))
+    }
+}
+
+/// Time in force of an order
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq)]
+pub enum TimeInForce {
+    /// good till canceled (default)
+    #[serde(rename = "GTC")]
+    Gtc,
+    /// immediate or cancel
+    #[serde(rename = "IOC")]
+    Ioc,
+    /// good till date, the date is given by `expiretm`
+    #[serde(rename = "GTD")]
+    Gtd,
+}
+
+impl TimeInForce {
+    fn as_str(&self) -> &'static str {
+        match *self {
+            TimeInForce::Gtc => "GTC",
+            TimeInForce::Ioc => "IOC",
+            TimeInForce::Gtd => "GTD",
+        }
+    }
+}
+
+/// Price signal which triggers stop loss and take profit orders
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Trigger {
+    /// last traded price (default)
+    Last,
+    /// index price of the pair
+    Index,
+}
+
+impl Trigger {
+    fn as_str(&self) -> &'static str {
+        match *self {
+            Trigger::Last => "last",
+            Trigger::Index => "index",
+        }
+    }
+}
+
+/// Self trade prevention, which order is canceled when an order would match another order of the same user
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq)]
+pub enum SelfTradePrevention {
+    /// cancel the arriving order (default)
+    #[serde(rename = "cancel-newest")]
+    CancelNewest,
+    /// cancel the resting order
+    #[serde(rename = "cancel-oldest")]
+    CancelOldest,
+    /// cancel both orders
+    #[serde(rename = "cancel-both")]
+    CancelBoth,
+}
+
+impl SelfTradePrevention {
+    fn as_str(&self) -> &'static str {
+        match *self {
+            SelfTradePrevention::CancelNewest => "cancel-newest",
+            SelfTradePrevention::CancelOldest => "cancel-oldest",
+            SelfTradePrevention::CancelBoth => "cancel-both",
+        }
+    }
+}
+
+///
+/// Start or expiration time of an order.
+///
+/// Serializes to kraken's wire form: `0` for now (no expiration when used as `expiretm`),
+/// `+<n>` for `n` seconds from now and `<n>` for a unix timestamp.
+/// Absolute times are truncated to whole seconds.
+///
+/// Relative times with fractions of a second and absolute times not after the unix epoch
+/// have no wire form, see `check`.  Serializing them fails, orders containing them are rejected.
+///
+/// # Example
+///
+/// ```rust
+/// use kraken::OrderTime;
+/// use std::time::{Duration, UNIX_EPOCH};
+///
+/// assert_eq!(OrderTime::In(Duration::from_secs(90)).to_string(), "+90");
+/// assert_eq!(OrderTime::At(UNIX_EPOCH + Duration::from_secs(1500000000)).to_string(), "1500000000");
+/// assert_eq!("+90".parse::<OrderTime>().unwrap(), OrderTime::In(Duration::from_secs(90)));
+/// assert!(OrderTime::In(Duration::from_millis(500)).check().is_err());
+/// ```
+///
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OrderTime {
+    Now,
+    /// relative to the time kraken receives the order, in whole seconds
+    In(Duration),
+    At(SystemTime),
+}
+
+impl OrderTime {
+    /// Checks that the time can be sent without changing its meaning.
+    pub fn check(&self) -> Result<(), String> {
+        match *self {
+            OrderTime::Now => Ok(()),
+            OrderTime::In(duration) if duration.subsec_nanos() != 0 => {
+                Err(format!("relative order time {:?} is not in whole seconds", duration))
+            }
+            OrderTime::In(_) => Ok(()),
+            OrderTime::At(time) => match time.duration_since(UNIX_EPOCH) {
+                Ok(since) if since.as_secs() > 0 => Ok(()),
+                _ => Err(format!("order time {:?} is not after the unix epoch", time)),
+            },
+        }
+    }
+}
+
+/// Writes the wire form, times rejected by `check` are truncated.
+impl fmt::Display for OrderTime {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            OrderTime::Now => write!(f, "0"),
+            OrderTime::In(duration) => write!(f, "+{}", duration.as_secs()),
+            OrderTime::At(time) => {
+                let secs = time.duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+
+                write!(f, "{}", secs)
+            }
+        }
+    }
+}
+
+impl FromStr for OrderTime {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<OrderTime, String> {
+        let invalid = |_| format!("invalid order time: {:?}", value);
+
+        if let Some(secs) = value.strip_prefix('+') {
+            secs.parse().map(|s| OrderTime::In(Duration::from_secs(s))).map_err(invalid)
+        } else {
+            match value.parse().map_err(invalid)? {
+                0 => Ok(OrderTime::Now),
+                secs => Ok(OrderTime::At(UNIX_EPOCH + Duration::from_secs(secs))),
+            }
+        }
+    }
+}
+
+impl serde::Serialize for OrderTime {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.check().map_err(serde::ser::Error::custom)?;
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for OrderTime {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<OrderTime, D::Error> {
+        match serde::Deserialize::deserialize(deserializer)? {
+            serde_json::Value::String(value) => value.parse().map_err(serde::de::Error::custom),
+            serde_json::Value::Number(value) => value.to_string().parse().map_err(serde::de::Error::custom),
+            other => Err(serde::de::Error::custom(
+                format!("expected order time, got {}", other),
+            )),
+        }
+    }
+}
+
+/// Conditional close order, which is placed once the order it is attached to is filled
+#[derive(Deserialize, Serialize, Debug)]
+pub struct CloseOrder {
+    /// order type of the close order
+    pub order_type: OrderType,
+    /// price (dependent upon ordertype)
+    pub price: String,
+    /// secondary price (optional.  dependent upon ordertype)
+    pub price2: Option<String>,
+}
+
+/// Formats kraken's human-readable close order description, e.g. "close position @ limit 100.0".
+impl fmt::Display for CloseOrder {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (label, label2) = match self.order_type {
+            OrderType::Limit => ("limit", None),
+            OrderType::StopLoss => ("stop loss", None),
+            OrderType::TakeProfit => ("take profit", None),
+            OrderType::TrailingStop => ("trailing stop", None),
+            OrderType::StopLossLimit => ("stop loss", Some("limit")),
+            OrderType::TakeProfitLimit => ("take profit", Some("limit")),
+            OrderType::TrailingStopLimit => ("trailing stop", Some("limit")),
+            OrderType::StopLossProfit => ("stop loss", Some("take profit")),
+            ref other => (other.as_str(), None),
+        };
+
+        write!(f, "close position @ {} {}", label, self.price)?;
+
+        match (label2, &self.price2) {
+            (Some(label2), Some(price2)) => write!(f, " -> {} {}", label2, price2),
+            (None, Some(price2)) => write!(f, " -> {}", price2),
+            (_, None) => Ok(()),
+        }
+    }
+}
+
+/// Parses kraken's human-readable close order description, e.g. "close position @ limit 100.0".
+impl FromStr for CloseOrder {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<CloseOrder, String> {
+        let invalid = || format!("invalid close order description: {}", value);
+
+        let (order_type, price, price2) = value
+            .strip_prefix("close position @ ")
+            .and_then(parse_order_prices)
+            .ok_or_else(invalid)?;
+
+        Ok(CloseOrder {
+            order_type,
+            price: price.ok_or_else(invalid)?,
+            price2,
+        })
+    }
 }
 
 #[derive(Deserialize, Serialize, Debug)]
@@ -360,116 +1473,3711 @@ pub struct NewOrder {
     pub volume: String,
     /// amount of leverage desired (optional.  default = none)
     pub leverage: Option<String>,
-    /// comma delimited list of order flags (optional):
-    ///   + viqc = volume in quote currency (not available for leveraged orders)
-    ///   + fcib = prefer fee in base currency
-    ///   + fciq = prefer fee in quote currency
-    ///   + nompp = no market price protection
-    ///   + post = post only order (available when ordertype = limit)
-    pub oflags: Option<String>,
-    /// scheduled start time (optional):
-    ///   + 0 = now (default)
-    ///   + +<n> = schedule start time <n> seconds from now
-    ///   + <n> = unix timestamp of start time
-    pub starttm: Option<i64>,
-    /// expiration time (optional):
-    ///   + 0 = no expiration (default)
-    ///   + +<n> = expire <n> seconds from now
-    ///   + <n> = unix timestamp of expiration time
-    pub expiretm: Option<i64>,
+    /// order flags (optional)
+    pub oflags: Option<OrderFlags>,
+    /// time in force (optional.  default = GTC)
+    pub timeinforce: Option<TimeInForce>,
+    /// scheduled start time (optional.  default = now)
+    pub starttm: Option<OrderTime>,
+    /// expiration time (optional.  default = no expiration)
+    pub expiretm: Option<OrderTime>,
     /// user reference id.  32-bit signed number.  (optional)
     pub userref: Option<String>,
     /// validate inputs only.  do not submit order (optional)
     pub validate: Option<bool>,
+    /// conditional close order (optional)
+    pub close: Option<CloseOrder>,
+    /// only reduce an existing margin position (optional)
+    pub reduce_only: Option<bool>,
+    /// client order id, alternative to userref (optional)
+    pub cl_ord_id: Option<String>,
+    /// price signal for triggered order types (optional.  default = last)
+    pub trigger: Option<Trigger>,
+    /// self trade prevention (optional.  default = cancel-newest)
+    pub stptype: Option<SelfTradePrevention>,
 }
 
-#[derive(Deserialize, Serialize, Debug)]
-pub struct KrakenResult<T> {
-    pub error: Vec<String>,
-    pub result: Option<T>,
+impl NewOrder {
+    ///
+    /// Starts building an order which is checked against the pair's metadata.
+    ///
+    /// # Arguments
+    ///
+    /// + `pair_name` - asset pair name, as used as key by `asset_pairs`.
+    /// + `pair` - asset pair metadata.
+    /// + `kind` - type of order (buy/sell).
+    /// + `order_type` - order type.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// extern crate kraken;
+    ///
+    /// use kraken::{NewOrder, OrderType, TradeType};
+    ///
+    /// fn main() {
+    ///   let pairs = kraken::asset_pairs().expect("could not get asset pairs");
+    ///
+    ///   let order = NewOrder::builder("XETHZEUR", &pairs["XETHZEUR"], TradeType::Buy, OrderType::Limit)
+    ///     .price("283.123456")
+    ///     .volume("1.5")
+    ///     .leverage(2)
+    ///     .build()
+    ///     .expect("invalid order");
+    ///
+    ///   println!("{:?}", order);
+    /// }
+    /// ```
+    ///
+    pub fn builder<'a>(
+        pair_name: &str,
+        pair: &'a AssetPair,
+        kind: TradeType,
+        order_type: OrderType,
+    ) -> NewOrderBuilder<'a> {
+        NewOrderBuilder {
+            pair_name: String::from(pair_name),
+            pair,
+            kind,
+            order_type,
+            price: None,
+            price2: None,
+            volume: None,
+            leverage: None,
+            oflags: None,
+            timeinforce: None,
+            starttm: None,
+            expiretm: None,
+            userref: None,
+            validate: None,
+            close: None,
+            reduce_only: None,
+            cl_ord_id: None,
+            trigger: None,
+            stptype: None,
+        }
+    }
 }
 
-fn public(url: &str) -> Result<Vec<u8>, String> {
-    let mut easy = Easy::new();
-    let mut dst = Vec::new();
+///
+/// Builder for a `NewOrder`, see `NewOrder::builder`.
+///
+/// `build` checks the order before any request is made:
+///
+/// + prices are given exactly when the order type needs them
+/// + prices and price offsets are rounded to `pair_decimals`, percentages are left as they are
+/// + the volume is rounded towards zero to `lot_decimals` and must reach `ordermin`,
+///   a volume in quote currency (`viqc`) is rounded to `cost_decimals` instead
+/// + the cost must reach `costmin`, if known from the volume in quote currency or an absolute price
+/// + the leverage must be offered for the order's side
+/// + start and expiration times must pass `OrderTime::check`
+/// + GTD orders need an `expiretm`, and `userref` and `cl_ord_id` exclude each other
+/// + the same price checks apply to the conditional close order
+///
+#[derive(Debug)]
+pub struct NewOrderBuilder<'a> {
+    pair_name: String,
+    pair: &'a AssetPair,
+    kind: TradeType,
+    order_type: OrderType,
+    price: Option<String>,
+    price2: Option<String>,
+    volume: Option<String>,
+    leverage: Option<u32>,
+    oflags: Option<OrderFlags>,
+    timeinforce: Option<TimeInForce>,
+    starttm: Option<OrderTime>,
+    expiretm: Option<OrderTime>,
+    userref: Option<String>,
+    validate: Option<bool>,
+    close: Option<CloseOrder>,
+    reduce_only: Option<bool>,
+    cl_ord_id: Option<String>,
+    trigger: Option<Trigger>,
+    stptype: Option<SelfTradePrevention>,
+}
 
-    easy.url(&format!("https://api.kraken.com/0/public/{}", url))
-        .unwrap();
+impl<'a> NewOrderBuilder<'a> {
+    pub fn price(mut self, price: &str) -> Self {
+        self.price = Some(String::from(price));
+        self
+    }
 
-    let result = {
-        let mut transfer = easy.transfer();
-        transfer
-            .write_function(|data| {
-                dst.extend_from_slice(data);
-                Ok(data.len())
-            })
-            .unwrap();
+    pub fn price2(mut self, price: &str) -> Self {
+        self.price2 = Some(String::from(price));
+        self
+    }
+
+    pub fn volume(mut self, volume: &str) -> Self {
+        self.volume = Some(String::from(volume));
+        self
+    }
+
+    pub fn leverage(mut self, leverage: u32) -> Self {
+        self.leverage = Some(leverage);
+        self
+    }
+
+    pub fn oflags(mut self, oflags: OrderFlags) -> Self {
+        self.oflags = Some(oflags);
+        self
+    }
+
+    /// Adds a single order flag.
+    pub fn flag(mut self, flag: OrderFlag) -> Self {
+        self.oflags = Some(self.oflags.unwrap_or_default().with(flag));
+        self
+    }
+
+    pub fn timeinforce(mut self, timeinforce: TimeInForce) -> Self {
+        self.timeinforce = Some(timeinforce);
+        self
+    }
+
+    pub fn starttm(mut self, starttm: OrderTime) -> Self {
+        self.starttm = Some(starttm);
+        self
+    }
+
+    pub fn expiretm(mut self, expiretm: OrderTime) -> Self {
+        self.expiretm = Some(expiretm);
+        self
+    }
+
+    pub fn userref(mut self, userref: &str) -> Self {
+        self.userref = Some(String::from(userref));
+        self
+    }
+
+    pub fn validate(mut self, validate: bool) -> Self {
+        self.validate = Some(validate);
+        self
+    }
+
+    pub fn close(mut self, close: CloseOrder) -> Self {
+        self.close = Some(close);
+        self
+    }
+
+    pub fn reduce_only(mut self, reduce_only: bool) -> Self {
+        self.reduce_only = Some(reduce_only);
+        self
+    }
+
+    pub fn cl_ord_id(mut self, cl_ord_id: &str) -> Self {
+        self.cl_ord_id = Some(String::from(cl_ord_id));
+        self
+    }
+
+    pub fn trigger(mut self, trigger: Trigger) -> Self {
+        self.trigger = Some(trigger);
+        self
+    }
+
+    pub fn stptype(mut self, stptype: SelfTradePrevention) -> Self {
+        self.stptype = Some(stptype);
+        self
+    }
+
+    ///
+    /// Rounds prices and price offsets to the pair's decimals.
+    ///
+    /// Percentages are only checked, kraken derives the price from them and rounds it itself.
+    ///
+    fn round_price(&self, price: &str) -> Result<String, String> {
+        let (prefix, amount) = match price.chars().next() {
+            Some('+') | Some('-') | Some('#') => price.split_at(1),
+            _ => ("", price),
+        };
+
+        if let Some(percent) = amount.strip_suffix('%') {
+            if percent.starts_with('+') || percent.starts_with('-') {
+                return Err(format!("invalid price {}", price));
+            }
+
+            percent.parse::<Decimal>()?;
+
+            return Ok(String::from(price));
+        }
+
+        if amount.starts_with('+') || amount.starts_with('-') {
+            return Err(format!("invalid price {}", price));
+        }
+
+        let amount = amount.parse::<Decimal>()?.round_dp(self.pair.pair_decimals);
+
+        Ok(format!("{}{}", prefix, amount))
+    }
+
+    /// Checks the order and builds it.
+    pub fn build(self) -> Result<NewOrder, String> {
+        if let OrderType::Other(ref order_type) = self.order_type {
+            return Err(format!("unsupported order type: {}", order_type));
+        }
+
+        let price = match (self.order_type.needs_price(), &self.price) {
+            (true, Some(price)) => Some(self.round_price(price)?),
+            (false, None) => None,
+            (true, None) => return Err(format!("{:?} order needs a price", self.order_type)),
+            (false, Some(_)) => return Err(format!("{:?} order takes no price", self.order_type)),
+        };
+
+        let price2 = match (self.order_type.needs_price2(), &self.price2) {
+            (true, Some(price)) => Some(self.round_price(price)?),
+            (false, None) => None,
+            (true, None) => return Err(format!("{:?} order needs a price2", self.order_type)),
+            (false, Some(_)) => return Err(format!("{:?} order takes no price2", self.order_type)),
+        };
+
+        let close = match self.close {
+            Some(ref close) => {
+                if !close.order_type.needs_price() || matches!(close.order_type, OrderType::Other(_)) {
+                    return Err(format!("{:?} close order is not supported", close.order_type));
+                }
+
+                let price2 = match (close.order_type.needs_price2(), &close.price2) {
+                    (true, Some(price)) => Some(self.round_price(price)?),
+                    (false, None) => None,
+                    (true, None) => {
+                        return Err(format!("{:?} close order needs a price2", close.order_type))
+                    }
+                    (false, Some(_)) => {
+                        return Err(format!("{:?} close order takes no price2", close.order_type))
+                    }
+                };
+
+                Some((self.round_price(&close.price)?, price2))
+            }
+            None => None,
+        };
+
+        let in_quote = self.oflags.as_ref().is_some_and(
+            |flags| flags.contains(&OrderFlag::Viqc),
+        );
+
+        let volume = self.volume
+            .as_ref()
+            .ok_or_else(|| String::from("order needs a volume"))?
+            .parse::<Decimal>()?;
+
+        let volume = match (in_quote, self.pair.cost_decimals) {
+            (true, Some(decimals)) => volume.round_dp_with(decimals, RoundingMode::Down),
+            (true, None) => volume,
+            (false, _) => volume.round_dp_with(self.pair.lot_decimals, RoundingMode::Down),
+        };
+
+        if volume.is_negative() || (volume.is_zero() && self.leverage.is_none()) {
+            return Err(format!("invalid order volume {}", volume));
+        }
+
+        for time in self.starttm.iter().chain(self.expiretm.iter()) {
+            time.check()?;
+        }
+
+        if self.timeinforce == Some(TimeInForce::Gtd) &&
+            self.expiretm.unwrap_or(OrderTime::Now) == OrderTime::Now
+        {
+            return Err(String::from("GTD order needs an expiretm"));
+        }
+
+        if self.userref.is_some() && self.cl_ord_id.is_some() {
+            return Err(String::from("order takes either a userref or a cl_ord_id"));
+        }
+
+        if let Some(ordermin) = self.pair.ordermin()? {
+            if !volume.is_zero() && !in_quote && volume < ordermin {
+                return Err(format!("order volume {} below minimum of {}", volume, ordermin));
+            }
+        }
+
+        if let Some(costmin) = self.pair.costmin()? {
+            // the cost is only known for volumes in quote currency or orders at an absolute price
+            let cost = match price {
+                _ if in_quote => Some(volume),
+                Some(ref price) if !is_relative_price(price) => Some(
+                    volume
+                        .checked_mul(&price.parse()?)
+                        .ok_or_else(|| format!("order cost of {} @ {} out of range", volume, price))?,
+                ),
+                _ => None,
+            };
+
+            if let Some(cost) = cost {
+                if !volume.is_zero() && cost < costmin {
+                    return Err(format!("order cost {} below minimum of {}", cost, costmin));
+                }
+            }
+        }
+
+        if let Some(leverage) = self.leverage {
+            let offered = match self.kind {
+                TradeType::Buy => &self.pair.leverage_buy,
+                TradeType::Sell => &self.pair.leverage_sell,
+            };
+
+            if !offered.contains(&leverage) {
+                return Err(format!(
+                    "leverage {} not available for {:?} {}, only {:?}",
+                    leverage,
+                    self.kind,
+                    self.pair_name,
+                    offered
+                ));
+            }
+        }
+
+        let close = match (self.close, close) {
+            (Some(order), Some((price, price2))) => Some(CloseOrder {
+                order_type: order.order_type,
+                price,
+                price2,
+            }),
+            _ => None,
+        };
+
+        Ok(NewOrder {
+            pair: self.pair_name,
+            kind: self.kind,
+            order_type: self.order_type,
+            price,
+            price2,
+            volume: volume.to_string(),
+            leverage: self.leverage.map(|l| format!("{}", l)),
+            oflags: self.oflags,
+            timeinforce: self.timeinforce,
+            starttm: self.starttm,
+            expiretm: self.expiretm,
+            userref: self.userref,
+            validate: self.validate,
+            close,
+            reduce_only: self.reduce_only,
+            cl_ord_id: self.cl_ord_id,
+            trigger: self.trigger,
+            stptype: self.stptype,
+        })
+    }
+}
+
+/// Whether a price is given relative to the current price, e.g. "+50.0", "#2" or "1.5%".
+fn is_relative_price(price: &str) -> bool {
+    price.starts_with('+') || price.starts_with('-') || price.starts_with('#') || price.ends_with('%')
+}
+
+/// Trade info
+#[derive(Deserialize, Serialize, Debug)]
+pub struct TradeInfo {
+    /// order responsible for execution of trade
+    pub ordertxid: String,
+    /// position responsible for execution of trade (if any)
+    pub postxid: Option<String>,
+    /// asset pair
+    pub pair: String,
+    /// unix timestamp of trade
+    pub time: f64,
+    /// type of order (buy/sell)
+    #[serde(rename = "type")]
+    pub kind: TradeType,
+    /// order type
+    pub ordertype: OrderType,
+    /// average price order was executed at (quote currency)
+    pub price: String,
+    /// total cost of order (quote currency)
+    pub cost: String,
+    /// total fee (quote currency)
+    pub fee: String,
+    /// volume (base currency)
+    pub vol: String,
+    /// initial margin (quote currency)
+    pub margin: String,
+    /// comma delimited list of miscellaneous info
+    pub misc: String,
+}
+
+impl TradeInfo {
+    pub fn time(&self) -> SystemTime {
+        timestamp(self.time)
+    }
+
+    pub fn price(&self) -> Result<Decimal, String> {
+        self.price.parse()
+    }
+
+    pub fn cost(&self) -> Result<Decimal, String> {
+        self.cost.parse()
+    }
+
+    pub fn fee(&self) -> Result<Decimal, String> {
+        self.fee.parse()
+    }
+
+    pub fn vol(&self) -> Result<Decimal, String> {
+        self.vol.parse()
+    }
+
+    pub fn margin(&self) -> Result<Decimal, String> {
+        self.margin.parse()
+    }
+}
+
+/// Ledger info
+#[derive(Deserialize, Serialize, Debug)]
+pub struct LedgerInfo {
+    /// reference id
+    pub refid: String,
+    /// unix timestamp of ledger
+    pub time: f64,
+    /// type of ledger entry
+    #[serde(rename = "type")]
+    pub kind: String,
+    /// additional info relating to the ledger entry type, where applicable
+    pub subtype: Option<String>,
+    /// asset class
+    pub aclass: String,
+    /// asset
+    pub asset: String,
+    /// transaction amount
+    pub amount: String,
+    /// transaction fee
+    pub fee: String,
+    /// resulting balance
+    pub balance: String,
+}
+
+impl LedgerInfo {
+    pub fn time(&self) -> SystemTime {
+        timestamp(self.time)
+    }
+
+    pub fn amount(&self) -> Result<Decimal, String> {
+        self.amount.parse()
+    }
+
+    pub fn fee(&self) -> Result<Decimal, String> {
+        self.fee.parse()
+    }
+
+    pub fn balance(&self) -> Result<Decimal, String> {
+        self.balance.parse()
+    }
+}
+
+/// Report type of an export
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportReport {
+    Trades,
+    Ledgers,
+}
+
+/// File format of an export
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum ExportFormat {
+    /// comma separated values (default)
+    Csv,
+    /// tab separated values
+    Tsv,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct NewExport {
+    /// type of data to export
+    pub report: ExportReport,
+    /// file format to export (optional.  default = CSV)
+    pub format: Option<ExportFormat>,
+    /// report description info
+    pub description: String,
+    /// comma delimited list of fields to include in report (optional.  default = all)
+    pub fields: Option<String>,
+    /// unix timestamp of report start time (optional.  default = one year before now)
+    pub starttm: Option<i64>,
+    /// unix timestamp of report end time (optional.  default = now)
+    pub endtm: Option<i64>,
+}
+
+/// Processing state of an export
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq)]
+pub enum ExportState {
+    Queued,
+    Processing,
+    Processed,
+    Failed,
+}
+
+/// Export status info
+#[derive(Deserialize, Serialize, Debug)]
+pub struct ExportStatus {
+    /// report id
+    pub id: String,
+    /// report description
+    pub descr: String,
+    /// file format of the report
+    pub format: String,
+    /// type of data in the report
+    pub report: String,
+    /// status of the report
+    pub status: ExportState,
+    /// comma delimited list of fields in the report
+    pub fields: Option<String>,
+    /// unix timestamp of report request
+    pub createdtm: Option<String>,
+    /// unix timestamp of report start time
+    pub starttm: Option<String>,
+    /// unix timestamp of report processing completion
+    pub completedtm: Option<String>,
+    /// unix timestamp of the report data start time
+    pub datastarttm: Option<String>,
+    /// unix timestamp of the report data end time
+    pub dataendtm: Option<String>,
+    /// asset class
+    pub aclass: Option<String>,
+    /// asset
+    pub asset: Option<String>,
+}
+
+/// How to remove an export
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum RemoveExportType {
+    /// cancel a queued or processing report
+    Cancel,
+    /// delete a processed report
+    Delete,
+}
+
+/// Remove export result
+#[derive(Deserialize, Serialize, Debug)]
+pub struct RemovedExport {
+    /// whether deletion was successful
+    pub delete: Option<bool>,
+    /// whether cancellation was successful
+    pub cancel: Option<bool>,
+}
+
+/// Deposit method info
+#[derive(Deserialize, Serialize, Debug)]
+pub struct DepositMethod {
+    /// name of deposit method
+    pub method: String,
+    /// maximum net amount that can be deposited right now, or none if no limit
+    #[serde(default, deserialize_with = "string_or_false")]
+    pub limit: Option<String>,
+    /// amount of fees that will be paid
+    pub fee: Option<String>,
+    /// whether or not method has an address setup fee
+    #[serde(rename = "address-setup-fee")]
+    pub address_setup_fee: Option<String>,
+    /// whether new addresses can be generated for this method
+    #[serde(rename = "gen-address")]
+    pub gen_address: Option<bool>,
+    /// minimum net amount that can be deposited
+    pub minimum: Option<String>,
+}
+
+/// Deposit address info
+#[derive(Deserialize, Serialize, Debug)]
+pub struct DepositAddress {
+    /// deposit address
+    pub address: String,
+    /// expiration time in unix timestamp, or 0 if not expiring
+    pub expiretm: String,
+    /// whether or not address has ever been used
+    pub new: Option<bool>,
+    /// tag for the address, required by some assets
+    pub tag: Option<String>,
+    /// memo for the address, required by some assets
+    pub memo: Option<String>,
+}
+
+/// State of a deposit or withdrawal
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq)]
+pub enum FundingState {
+    Initial,
+    Pending,
+    Settled,
+    Success,
+    Failure,
+}
+
+/// Deposit or withdrawal status info
+#[derive(Deserialize, Serialize, Debug)]
+pub struct FundingStatus {
+    /// name of the funding method used
+    pub method: String,
+    /// asset class
+    pub aclass: String,
+    /// asset
+    pub asset: String,
+    /// reference id
+    pub refid: String,
+    /// method transaction id
+    pub txid: String,
+    /// method transaction information
+    pub info: String,
+    /// amount funded
+    pub amount: String,
+    /// fees paid
+    pub fee: Option<String>,
+    /// unix timestamp when request was made
+    pub time: i64,
+    /// status of the transaction
+    pub status: FundingState,
+    /// additional status properties:
+    /// + return = a return transaction initiated by kraken
+    /// + onhold = funding is on hold pending review
+    #[serde(rename = "status-prop")]
+    pub status_prop: Option<String>,
+}
+
+impl FundingStatus {
+    pub fn time(&self) -> SystemTime {
+        timestamp(self.time as f64)
+    }
+
+    pub fn amount(&self) -> Result<Decimal, String> {
+        self.amount.parse()
+    }
+
+    pub fn fee(&self) -> Result<Option<Decimal>, String> {
+        optional_decimal(&self.fee)
+    }
+}
+
+/// Withdrawal info
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct WithdrawInfo {
+    /// name of the withdrawal method that will be used
+    pub method: String,
+    /// maximum net amount that can be withdrawn right now
+    pub limit: String,
+    /// net amount that will be sent, after fees
+    pub amount: String,
+    /// amount of fees that will be paid
+    pub fee: String,
+}
+
+/// Restrictions for withdrawals of a single asset
+#[derive(Debug, Clone)]
+pub struct WithdrawRule {
+    /// names of the withdrawal keys which may be used
+    pub keys: Vec<String>,
+    /// maximum amount per withdrawal (optional.  default = no cap)
+    pub max_amount: Option<Decimal>,
+}
+
+///
+/// Local allowlist of permitted withdrawals.
+///
+/// Withdrawals of assets not listed here are rejected before any request is made.
+///
+#[derive(Debug, Clone, Default)]
+pub struct WithdrawPolicy {
+    /// rules keyed by asset name
+    pub assets: HashMap<String, WithdrawRule>,
+}
+
+impl WithdrawPolicy {
+    /// Checks a withdrawal against the allowlist.
+    pub fn check(&self, asset: &str, key: &str, amount: &Decimal) -> Result<(), String> {
+        if key.is_empty() {
+            return Err(String::from("withdrawal key name required"));
+        }
+
+        let rule = self.assets.get(asset).ok_or_else(|| {
+            format!("withdrawals of {} are not permitted", asset)
+        })?;
+
+        if !rule.keys.iter().any(|k| k == key) {
+            return Err(format!("withdrawal key {} is not permitted for {}", key, asset));
+        }
+
+        if *amount <= Decimal::zero() {
+            return Err(format!("withdrawal amount must be positive, got {}", amount));
+        }
+
+        if let Some(ref max_amount) = rule.max_amount {
+            if amount > max_amount {
+                return Err(format!(
+                    "withdrawal of {} {} exceeds cap of {}",
+                    amount,
+                    asset,
+                    max_amount
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// How long a `WithdrawPreview` can be used, fees and limits may have changed afterwards.
+pub const WITHDRAW_PREVIEW_MAX_AGE: Duration = Duration::from_secs(60);
+
+///
+/// A withdrawal which passed the local policy, together with kraken's fee and limit preview.
+///
+/// Can only be created by `withdraw_preview` and is consumed by `withdraw`, so every check
+/// covers a single withdrawal.  It expires after `WITHDRAW_PREVIEW_MAX_AGE`.
+///
+#[derive(Debug)]
+pub struct WithdrawPreview {
+    asset: String,
+    key: String,
+    amount: Decimal,
+    info: WithdrawInfo,
+    created: Instant,
+}
+
+impl WithdrawPreview {
+    pub fn asset(&self) -> &str {
+        &self.asset
+    }
+
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    pub fn amount(&self) -> &Decimal {
+        &self.amount
+    }
+
+    /// Fee, limit and net amount as reported by kraken.
+    pub fn info(&self) -> &WithdrawInfo {
+        &self.info
+    }
+
+    /// Whether the preview is too old to be used by `withdraw`.
+    pub fn is_expired(&self) -> bool {
+        self.created.elapsed() > WITHDRAW_PREVIEW_MAX_AGE
+    }
+}
+
+/// Transfer between master account and subaccount result
+#[derive(Deserialize, Serialize, Debug)]
+pub struct AccountTransfer {
+    /// transfer id
+    pub transfer_id: String,
+    /// status of the transfer
+    pub status: String,
+}
+
+/// Lock type of an earn strategy
+#[derive(Deserialize, Serialize, Debug)]
+pub struct EarnLockType {
+    /// instant, bonded, timed or flex
+    #[serde(rename = "type")]
+    pub kind: String,
+    /// duration in seconds between reward payouts
+    pub payout_frequency: Option<u64>,
+    /// duration in seconds until allocated funds start earning
+    pub bonding_period: Option<u64>,
+    /// duration in seconds until deallocated funds are available again
+    pub unbonding_period: Option<u64>,
+}
+
+/// Estimated annual percentage rate range
+#[derive(Deserialize, Serialize, Debug)]
+pub struct EarnAprEstimate {
+    pub low: String,
+    pub high: String,
+}
+
+/// Auto compounding of an earn strategy
+#[derive(Deserialize, Serialize, Debug)]
+pub struct EarnAutoCompound {
+    /// enabled, disabled or optional
+    #[serde(rename = "type")]
+    pub kind: String,
+    /// whether auto compounding is enabled by default (if optional)
+    pub default: Option<bool>,
+}
+
+/// Source of the yield of an earn strategy
+#[derive(Deserialize, Serialize, Debug)]
+pub struct EarnYieldSource {
+    /// staking, off_chain or opt_in_rewards
+    #[serde(rename = "type")]
+    pub kind: String,
+}
+
+/// Earn strategy info
+#[derive(Deserialize, Serialize, Debug)]
+pub struct EarnStrategy {
+    /// strategy id
+    pub id: String,
+    /// asset to allocate
+    pub asset: String,
+    pub lock_type: EarnLockType,
+    /// estimated annual percentage rate
+    pub apr_estimate: Option<EarnAprEstimate>,
+    /// minimum amount for an allocation
+    pub user_min_allocation: Option<String>,
+    /// fee applied when allocating
+    pub allocation_fee: Option<String>,
+    /// fee applied when deallocating
+    pub deallocation_fee: Option<String>,
+    pub auto_compound: Option<EarnAutoCompound>,
+    pub yield_source: Option<EarnYieldSource>,
+    /// whether the user can allocate to this strategy
+    pub can_allocate: bool,
+    /// whether the user can deallocate from this strategy
+    pub can_deallocate: bool,
+    /// reasons why allocating is not possible
+    #[serde(default)]
+    pub allocation_restriction_info: Vec<String>,
+}
+
+/// Page of earn strategies
+#[derive(Deserialize, Serialize, Debug)]
+pub struct EarnStrategies {
+    pub items: Vec<EarnStrategy>,
+    /// cursor of the next page, if any
+    pub next_cursor: Option<String>,
+}
+
+/// Amount in native and converted asset
+#[derive(Deserialize, Serialize, Debug)]
+pub struct EarnAmount {
+    /// amount in the allocated asset
+    pub native: String,
+    /// amount in the converted asset
+    pub converted: String,
+}
+
+/// Allocated amount in a single state, e.g. bonding
+#[derive(Deserialize, Serialize, Debug)]
+pub struct EarnAllocationState {
+    /// amount in the allocated asset
+    pub native: String,
+    /// amount in the converted asset
+    pub converted: String,
+    /// number of allocations in this state
+    pub allocation_count: u32,
+}
+
+/// Allocated amounts of a strategy
+#[derive(Deserialize, Serialize, Debug)]
+pub struct EarnAllocatedAmount {
+    /// funds which are not yet earning
+    pub bonding: Option<EarnAllocationState>,
+    /// funds waiting to be unbonded
+    pub exit_queue: Option<EarnAllocationState>,
+    /// funds being allocated
+    pub pending: Option<EarnAmount>,
+    /// funds being deallocated
+    pub unbonding: Option<EarnAllocationState>,
+    /// total funds allocated
+    pub total: EarnAmount,
+}
+
+/// Allocation to an earn strategy
+#[derive(Deserialize, Serialize, Debug)]
+pub struct EarnAllocation {
+    /// strategy id
+    pub strategy_id: String,
+    /// asset of the allocation
+    pub native_asset: String,
+    pub amount_allocated: EarnAllocatedAmount,
+    /// total rewards earned
+    pub total_rewarded: EarnAmount,
+}
+
+/// Allocations to earn strategies
+#[derive(Deserialize, Serialize, Debug)]
+pub struct EarnAllocations {
+    /// asset the converted amounts are given in
+    pub converted_asset: String,
+    /// total allocated amount in the converted asset
+    pub total_allocated: String,
+    /// total rewarded amount in the converted asset
+    pub total_rewarded: String,
+    pub items: Vec<EarnAllocation>,
+    /// cursor of the next page, if any
+    pub next_cursor: Option<String>,
+}
+
+impl EarnAllocations {
+    ///
+    /// Sums the allocated amounts per asset over all strategies.
+    ///
+    /// # Note
+    ///
+    /// `balance` lists allocated funds as separate assets, e.g. "DOT.S" or "USDC.F",
+    /// next to the unallocated balance.
+    ///
+    pub fn totals(&self) -> Result<HashMap<String, Decimal>, String> {
+        let mut totals = HashMap::new();
+
+        for item in &self.items {
+            let amount = item.amount_allocated.total.native.parse::<Decimal>()?;
+            let total = totals.entry(item.native_asset.clone()).or_insert_with(
+                Decimal::zero,
+            );
+
+            *total = *total + amount;
+        }
+
+        Ok(totals)
+    }
+}
+
+/// Description of a placed order
+#[derive(Deserialize, Serialize, Debug)]
+pub struct AddOrderDescription {
+    /// order description
+    pub order: String,
+    /// conditional close order description (if conditional close set)
+    pub close: Option<String>,
+}
+
+impl AddOrderDescription {
+    /// Parses the human-readable `order` description.
+    pub fn parsed(&self) -> Result<ParsedOrder, String> {
+        self.order.parse()
+    }
+
+    /// Parses the human-readable `close` description, e.g. "close position @ limit 100.0".
+    pub fn parsed_close(&self) -> Result<Option<CloseOrder>, String> {
+        self.close.as_ref().map(|close| close.parse()).transpose()
+    }
+}
+
+/// Add order result
+#[derive(Deserialize, Serialize, Debug)]
+pub struct AddOrderResult {
+    pub descr: AddOrderDescription,
+    /// transaction ids of the placed order (empty when validating)
+    #[serde(default)]
+    pub txid: Vec<String>,
+}
+
+/// Result of a single order of a batch
+#[derive(Deserialize, Serialize, Debug)]
+pub struct BatchOrderResult {
+    /// order description (if placed)
+    pub descr: Option<AddOrderDescription>,
+    /// transaction id of the order (if placed and not validating)
+    pub txid: Option<String>,
+    /// reason why the order was not placed
+    pub error: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+struct BatchOrders {
+    orders: Vec<BatchOrderResult>,
+}
+
+///
+/// Changes to an open order, which is replaced by a new order.
+///
+/// Start and expiration times are not supported by `EditOrder` and carry over from the original order.
+///
+#[derive(Deserialize, Serialize, Debug)]
+pub struct EditOrder {
+    /// transaction id or user reference id of the order to edit
+    pub txid: String,
+    /// asset pair
+    pub pair: String,
+    /// new order volume in lots (optional)
+    pub volume: Option<String>,
+    /// new price (optional.  dependent upon ordertype)
+    pub price: Option<String>,
+    /// new secondary price (optional.  dependent upon ordertype)
+    pub price2: Option<String>,
+    /// order flags (optional), replacing those of the original order
+    pub oflags: Option<OrderFlags>,
+    /// new user reference id.  32-bit signed number.  (optional)
+    pub userref: Option<String>,
+    /// validate inputs only.  do not submit order (optional)
+    pub validate: Option<bool>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct EditOrderDescription {
+    /// order description
+    pub order: String,
+}
+
+/// Edit order result
+#[derive(Deserialize, Serialize, Debug)]
+pub struct EditOrderResult {
+    pub descr: Option<EditOrderDescription>,
+    /// transaction id of the new order (unless validating)
+    pub txid: Option<String>,
+    /// transaction id of the original order
+    pub originaltxid: String,
+    /// new order volume
+    pub volume: Option<String>,
+    /// new price
+    pub price: Option<String>,
+    /// new secondary price
+    pub price2: Option<String>,
+    /// number of orders canceled, either 0 or 1
+    pub orders_cancelled: Option<u32>,
+    /// success or error
+    pub status: String,
+    /// reason of the error, if any
+    pub error_message: Option<String>,
+}
+
+/// Changes to an open order, which keeps its transaction id and queue priority where possible
+#[derive(Deserialize, Serialize, Debug)]
+pub struct AmendOrder {
+    /// transaction id of the order to amend
+    pub txid: String,
+    /// new order quantity in base asset (optional)
+    pub order_qty: Option<String>,
+    /// new limit price (optional)
+    pub limit_price: Option<String>,
+    /// new trigger price for triggered order types (optional)
+    pub trigger_price: Option<String>,
+    /// reject the amend if the new limit price would take liquidity (optional)
+    pub post_only: Option<bool>,
+}
+
+/// Amend order result
+#[derive(Deserialize, Serialize, Debug)]
+pub struct AmendOrderResult {
+    /// id of the amend transaction
+    pub amend_id: String,
+}
+
+/// Token to authenticate private websocket subscriptions
+#[derive(Deserialize, Serialize, Debug)]
+pub struct WebSocketsToken {
+    pub token: String,
+    /// seconds until the token expires, unless it is used to subscribe before
+    pub expires: u64,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct KrakenResult<T> {
+    pub error: Vec<String>,
+    pub result: Option<T>,
+}
+
+///
+/// Converts kraken's unix timestamps in seconds, keeping sub-second precision down to microseconds.
+///
+/// Negative timestamps are taken as the unix epoch.
+///
+fn timestamp(secs: f64) -> SystemTime {
+    UNIX_EPOCH + Duration::from_micros((secs.max(0.0) * 1e6).round() as u64)
+}
+
+/// Like `timestamp`, but `0` means not set.
+fn optional_timestamp(secs: f64) -> Option<SystemTime> {
+    if secs > 0.0 {
+        Some(timestamp(secs))
+    } else {
+        None
+    }
+}
+
+fn optional_decimal(value: &Option<String>) -> Result<Option<Decimal>, String> {
+    value.as_ref().map(|value| value.parse()).transpose()
+}
+
+fn decimal_at(values: &[String], index: usize) -> Result<Decimal, String> {
+    values
+        .get(index)
+        .ok_or_else(|| format!("missing value at index {}", index))?
+        .parse()
+}
+
+fn string_or_false<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    match serde::Deserialize::deserialize(deserializer)? {
+        serde_json::Value::String(value) => Ok(Some(value)),
+        serde_json::Value::Bool(false) |
+        serde_json::Value::Null => Ok(None),
+        other => Err(serde::de::Error::custom(
+            format!("expected string or false, got {}", other),
+        )),
+    }
+}
+
+fn string_or_number<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    match serde::Deserialize::deserialize(deserializer)? {
+        serde_json::Value::String(value) => Ok(Some(value)),
+        serde_json::Value::Number(value) => Ok(Some(value.to_string())),
+        serde_json::Value::Null => Ok(None),
+        other => Err(serde::de::Error::custom(
+            format!("expected string or number, got {}", other),
+        )),
+    }
+}
+
+/// Takes a string or a number as string.
+fn value_string(value: &serde_json::Value) -> Result<String, String> {
+    match *value {
+        serde_json::Value::String(ref value) => Ok(value.clone()),
+        serde_json::Value::Number(ref value) => Ok(value.to_string()),
+        ref other => Err(format!("expected string or number, got {}", other)),
+    }
+}
+
+/// Takes a number or a string containing one.
+fn value_f64(value: &serde_json::Value) -> Result<f64, String> {
+    match *value {
+        serde_json::Value::String(ref value) => value.parse().map_err(|e| format!("{:?}", e)),
+        serde_json::Value::Number(ref value) => value.as_f64().ok_or_else(|| format!("invalid number {}", value)),
+        ref other => Err(format!("expected number, got {}", other)),
+    }
+}
+
+fn value_array(value: &serde_json::Value) -> Result<&Vec<serde_json::Value>, String> {
+    value.as_array().ok_or_else(|| format!("expected array, got {}", value))
+}
+
+/// Decodes `[price, volume, time, side, ordertype, misc, ..]`, as sent by the REST API and the websocket feed.
+fn decode_trade(value: &serde_json::Value) -> Result<Trade, String> {
+    let values = value_array(value)?;
+    let invalid = || format!("invalid trade: {}", value);
+
+    if values.len() < 6 {
+        return Err(invalid());
+    }
+
+    let kind = match values[3].as_str() {
+        Some("b") => TradeType::Buy,
+        Some("s") => TradeType::Sell,
+        _ => return Err(invalid()),
+    };
+
+    let ordertype = match values[4].as_str() {
+        Some("m") => OrderType::Market,
+        Some("l") => OrderType::Limit,
+        _ => return Err(invalid()),
+    };
+
+    Ok(Trade {
+        price: value_string(&values[0])?.parse()?,
+        volume: value_string(&values[1])?.parse()?,
+        time: value_f64(&values[2])?,
+        kind,
+        ordertype,
+        misc: value_string(&values[5])?,
+    })
+}
+
+/// Decodes the REST form `[time, bid, ask]`.
+fn decode_rest_spread(value: &serde_json::Value) -> Result<Spread, String> {
+    let values = value_array(value)?;
+
+    if values.len() < 3 {
+        return Err(format!("invalid spread: {}", value));
+    }
+
+    Ok(Spread {
+        time: value_f64(&values[0])?,
+        bid: value_string(&values[1])?.parse()?,
+        ask: value_string(&values[2])?.parse()?,
+        bid_volume: None,
+        ask_volume: None,
+    })
+}
+
+/// Splits the result of the `Trades` and `Spread` endpoints into the entries of the pair and `last`.
+fn recent_entries(data: &[u8]) -> Result<(Vec<serde_json::Value>, serde_json::Value), String> {
+    let result: KrakenResult<HashMap<String, serde_json::Value>> =
+        serde_json::from_slice(data).map_err(|e| format!("{:?}", e))?;
+
+    if !result.error.is_empty() {
+        return Err(format!("{:?}", result.error));
+    }
+
+    let mut data = result.result.unwrap_or_default();
+    let last = data.remove("last").ok_or_else(|| String::from("missing last id"))?;
+    let entries = data.into_iter()
+        .next()
+        .map(|(_, entries)| entries)
+        .ok_or_else(|| String::from("no data for pair"))?;
+
+    Ok((value_array(&entries)?.clone(), last))
+}
+
+fn parse_recent_trades(data: &[u8]) -> Result<RecentTrades, String> {
+    let (entries, last) = recent_entries(data)?;
+
+    Ok(RecentTrades {
+        trades: entries.iter().map(decode_trade).collect::<Result<Vec<Trade>, String>>()?,
+        last: value_string(&last)?,
+    })
+}
+
+fn parse_recent_spreads(data: &[u8]) -> Result<RecentSpreads, String> {
+    let (entries, last) = recent_entries(data)?;
+
+    Ok(RecentSpreads {
+        spreads: entries.iter().map(decode_rest_spread).collect::<Result<Vec<Spread>, String>>()?,
+        last: last.as_i64().ok_or_else(|| format!("invalid last id: {}", last))?,
+    })
+}
+
+fn public(url: &str) -> Result<Vec<u8>, String> {
+    let mut easy = Easy::new();
+    let mut dst = Vec::new();
+
+    easy.url(&format!("https://api.kraken.com/0/public/{}", url))
+        .unwrap();
+
+    let result = {
+        let mut transfer = easy.transfer();
+        transfer
+            .write_function(|data| {
+                dst.extend_from_slice(data);
+                Ok(data.len())
+            })
+            .unwrap();
+
+        transfer.perform()
+    };
+
+    result.map_err(|e| format!("{:?}", e)).map(|_x| dst)
+}
+
+///
+/// Server's time.
+///
+/// # Note
+///
+/// This is to aid in approximating the skew time between the server and client.
+///
+/// # Result
+///
+/// ```json
+/// {
+///     "error":[],
+///     "result": {
+///         "unixtime": 1507489778,"
+///         rfc1123":"Sun,  8 Oct 17 19:09:38 +0000"
+///     }
+/// }
+/// ```
+///
+pub fn time() -> Result<Time, String> {
+    public("Time").and_then(|dst| {
+        serde_json::from_slice(&dst)
+            .map_err(|e| format!("{:?}", e))
+            .and_then(|result: KrakenResult<Time>| if !result.error.is_empty() {
+                Err(format!("{:?}", result.error))
+            } else {
+                Ok(result.result.unwrap())
+            })
+    })
+}
+
+///
+/// Returns an array of asset names and their info.
+///
+pub fn assets() -> Result<HashMap<String, Asset>, String> {
+    public("Assets").and_then(|data| {
+        serde_json::from_slice(&data)
+            .map_err(|e| format!("{:?}", e))
+            .and_then(
+                |result: KrakenResult<HashMap<String, Asset>>| if !result.error.is_empty() {
+                    Err(format!("{:?}", result.error))
+                } else {
+                    Ok(result.result.unwrap())
+                },
+            )
+    })
+
+}
+
+///
+/// Returns an array of pair names and theif info.
+///
+/// # Note
+///
+/// If an asset pair is on a maker/taker fee schedule,
+/// the taker side is given in "fees" and maker side in "fees_maker".
+/// For pairs not on maker/taker, they will only be given in "fees".
+///
+/// # Result
+///
+/// ```json
+/// {
+///     "error": [],
+///     "result": {
+///         "XETHZEUR": {
+///             "altname": "ETHEUR",
+///             "aclass_base": "currency",
+///             "base": "XETH",
+///             "aclass_quote": "currency",
+///             "quote": "ZEUR",
+///             "lot": "unit",
+///             "pair_decimals": 5,
+///             "lot_decimals": 8,
+///             "cost_decimals": 5,
+///             "lot_multiplier": 1,
+///             "leverage_buy": [2, 3],
+///             "leverage_sell": [2, 3],
+///             "fees": [
+///                 [0, 0.26],
+///                 [50000, 0.24],
+///                 [100000, 0.22],
+///                 [250000, 0.2],
+///                 [500000, 0.18],
+///                 [1000000, 0.16],
+///                 [2500000, 0.14],
+///                 [5000000, 0.12],
+///                 [10000000, 0.1]
+///             ],
+///             "fees_maker": [
+///                 [0, 0.16],
+///                 [50000, 0.14],
+///                 [100000, 0.12],
+///                 [250000, 0.1],
+///                 [500000, 0.08],
+///                 [1000000, 0.06],
+///                 [2500000, 0.04],
+///                 [5000000, 0.02],
+///                 [10000000, 0]
+///             ],
+///             "fee_volume_currency": "ZUSD",
+///             "margin_call": 80,
+///             "margin_stop": 40
+///         }
+///     }
+/// }
+/// ```
+///
+pub fn asset_pairs() -> Result<HashMap<String, AssetPair>, String> {
+    public("AssetPairs").and_then(|data| {
+        serde_json::from_slice(&data)
+            .map_err(|e| format!("{:?}\n{:?}", e, data))
+            .and_then(
+                |result: KrakenResult<HashMap<String, AssetPair>>| if !result.error.is_empty() {
+                    Err(format!("{:?}", result.error))
+                } else {
+                    match result.result {
+                        Some(stuff) => Ok(stuff),
+                        None => Err(String::from("empty result")),
+                    }
+                },
+            )
+    })
+}
+
+///
+/// Returns an array of pair names and their ticker info.
+///
+/// # Arguments
+///
+/// + `pairs` - comma delimited list of asset pairs to get info on
+///
+/// # Result
+///
+/// + `a` ask array(<price>, <whole lot volume>, <lot volume>)
+/// + `b` bid array(<price>, <whole lot volume>, <lot volume>)
+/// + `c` last trade closed array(<price>, <lot volume>)
+/// + `v` volume array(<today>, <last 24 hours>)
+/// + `p` volume weighted average price array(<today>, <last 24 hours>)
+/// + `t` number of trades array(<today>, <last 24 hours>)
+/// + `l` low array(<today>, <last 24 hours>)
+/// + `h` high array(<today>, <last 24 hours>)
+/// + `o` today's opening price
+///
+/// ```json
+/// {
+///     "error": [],
+///     "result": {
+///         "XETHZEUR": {
+///             "a": ["10.27949", "9", "9.000"],
+///             "b": ["10.20800", "83", "83.000"],
+///             "c": ["10.27949", "2.91843272"],
+///             "v": ["32132.14651679", "155901.33932839"],
+///             "p": ["10.20578", "10.18520"],
+///             "t": [718, 4203],
+///             "l": ["10.11669", "9.87000"],
+///             "h": ["10.29992", "10.69000"],
+///             "o": "10.24950"
+///         }
+///     }
+/// }
+/// ```
+pub fn ticker(pairs: &str) -> Result<HashMap<String, Tick>, String> {
+    public(&format!("Ticker?pair={}", pairs)).and_then(|data| {
+        serde_json::from_slice(&data)
+            .map_err(|e| format!("{:?}", e))
+            .and_then(
+                |result: KrakenResult<HashMap<String, Tick>>| if !result.error.is_empty() {
+                    Err(format!("{:?}", result.error))
+                } else {
+                    match result.result {
+                        Some(stuff) => Ok(stuff),
+                        None => Err(String::from("empty result")),
+                    }
+                },
+            )
+    })
+}
+
+///
+/// # Arguments
+///
+/// + `pair` - asset pair to get OHLC data for
+/// + `interval` - time frame interval in minutes (optional):
+///   1 (default), 5, 15, 30, 60, 240, 1440, 10080, 21600
+/// + `since` - return committed OHLC data since given id (optional.  exclusive)
+///
+/// # Note
+///
+/// the last entry in the OHLC array is for the current, not-yet-committed frame and will always be present,
+/// regardless of the value of "since".
+///
+/// [time], [open], [high], [low], [close], [vwap], [volume], [count]
+///
+/// ```json
+/// {
+///     "error": [],
+///     "result": {
+///         "XETHZEUR": [
+///           [1506303540,"283.62","283.65","283.62","283.65","283.64","7.10086462",4],
+///           [1506303600,"283.64","284.09","283.64","284.09","283.97","8.14638417",8],
+///           ...
+///         ]
+///     }
+/// }
+/// ```
+///
+pub fn ohlc(pair: &str, interval: Option<u32>, since: Option<&str>) -> Result<OHLC, String> {
+    let mut url = format!("OHLC?pair={}", pair);
+
+    if let Some(interval) = interval {
+        url = format!("{}&interval={}", url, interval);
+    }
+
+    if let Some(since) = since {
+        url = format!("{}&since={}", url, since);
+    }
+
+    public(&url).and_then(|data| {
+        serde_json::from_slice(&data)
+            .map_err(|e| format!("{:?}", e))
+            .and_then(|result: KrakenResult<OHLC>| if !result.error.is_empty() {
+                Err(format!("{:?}", result.error))
+            } else {
+                Ok(result.result.unwrap())
+            })
+    })
+}
+
+///
+/// Get typed OHLC data.
+///
+/// # Arguments
+///
+/// + `pair` - asset pair to get OHLC data for
+/// + `interval` - time frame interval in minutes (optional), see `ohlc`
+/// + `since` - return committed OHLC data since given id (optional.  exclusive)
+///
+pub fn ohlc_frames(
+    pair: &str,
+    interval: Option<u32>,
+    since: Option<&str>,
+) -> Result<OhlcFrames, String> {
+    let mut data = ohlc(pair, interval, since)?;
+
+    let last = data.remove("last")
+        .and_then(|last| last.as_i64())
+        .ok_or_else(|| String::from("missing last id"))?;
+
+    let frames = data.into_iter()
+        .next()
+        .map(|(_, frames)| frames)
+        .ok_or_else(|| format!("no OHLC data for {}", pair))?;
+
+    serde_json::from_value(frames)
+        .map(|frames| OhlcFrames { frames, last })
+        .map_err(|e| format!("{:?}", e))
+}
+
+///
+/// Get the order depth.
+///
+/// # Arguments
+///
+/// + `pair` - asset pair to get market depth for
+/// + `count` - maximum number of asks/bids (optional)
+///
+/// ```json
+/// {
+///     "error":[],
+///     "result": {
+///         "XETHZEUR": {
+///             "asks":[["247.00000","45.273",1506366345], ...],
+///             "bids":[["247.00000","45.273",1506366345], ...]
+///         }
+///     }
+/// }
+/// ```
+///
+pub fn order_book(pair: &str, count: Option<u32>) -> Result<Depth, String> {
+    let mut url = format!("Depth?pair={}", pair);
+
+    if let Some(ct) = count {
+        url = format!("{}&count={}", url, ct);
+    }
+
+    public(&url).and_then(|data| {
+        serde_json::from_slice(&data)
+            .map_err(|e| format!("{:?}", e))
+            .and_then(|result: KrakenResult<Depth>| if !result.error.is_empty() {
+                Err(format!("{:?}", result.error))
+            } else {
+                Ok(result.result.unwrap())
+            })
+    })
+}
+
+///
+/// Get recent trades.
+///
+/// # Arguments
+///
+/// + `pair` - asset pair to get trade data for
+/// + `since` - return trade data since given id (optional.  exclusive)
+///
+/// ```json
+/// {
+///     "error":[],
+///     "result": {
+///         "XETHZEUR": [
+///             ["246.20000","0.86500000",1506362463.76,"b","l",""],
+///             ...
+///         ],
+///         "last":"1506367082091136113"
+///     }
+/// }
+/// ```
+///
+pub fn recent_trades(pair: &str, since: Option<&str>) -> Result<RecentTrades, String> {
+    let mut url = format!("Trades?pair={}", pair);
+
+    if let Some(ct) = since {
+        url = format!("{}&since={}", url, ct);
+    }
+
+    public(&url).and_then(|data| parse_recent_trades(&data))
+}
+
+///
+/// Get recent spread data.
+///
+/// # Arguments
+///
+/// + `pair` - asset pair to get spread data for.
+/// + `since` - return spread data since given id (optional.  inclusive).
+///
+/// ```json
+/// {
+///     "error":[],
+///     "result": {
+///         "XETHZEUR": [
+///             [1506368083,"247.47000","248.52000"],
+///             ...
+///         ],
+///         "last": 1506370285
+///     }
+/// }
+/// ```
+///
+pub fn recent_spread(pair: &str, since: Option<u32>) -> Result<RecentSpreads, String> {
+    let mut url = format!("Spread?pair={}", pair);
+
+    if let Some(ct) = since {
+        url = format!("{}&since={}", url, ct);
+    }
+
+    public(&url).and_then(|data| parse_recent_spreads(&data))
+}
+
+// ----
+
+///
+/// Returns the next nonce for the account's API key.
+///
+/// Nonces are nanoseconds since the unix epoch, but never repeat or decrease for the same key,
+/// even if several threads use the same account or the clock goes backwards.
+///
+fn next_nonce(account: &Account) -> String {
+    static NONCES: OnceLock<Mutex<HashMap<String, u64>>> = OnceLock::new();
+
+    let timestamp = ::std::time::UNIX_EPOCH.elapsed().unwrap();
+    let now = timestamp.as_secs() * 1_000_000_000 + u64::from(timestamp.subsec_nanos());
+
+    let mut nonces = NONCES
+        .get_or_init(|| Mutex::new(HashMap::new()))
+        .lock()
+        .unwrap_or_else(|e| e.into_inner());
+    let last = nonces.entry(account.key.clone()).or_insert(0);
+
+    *last = if now > *last { now } else { *last + 1 };
+
+    format!("{}", last)
+}
+
+fn private(
+    account: &Account,
+    method: &str,
+    params: &mut HashMap<String, String>,
+) -> Result<Vec<u8>, String> {
+    let nonce = next_nonce(account);
+    let mut easy = Easy::new();
+
+    params.insert("nonce".to_owned(), nonce.clone());
+
+    let mut body = params.iter().fold(
+        String::new(),
+        |data, item| {
+            data + &easy.url_encode(item.0.as_bytes()) + "=" + &easy.url_encode(item.1.as_bytes()) +
+                "&"
+        },
+    );
+    body.pop();
+
+    signed_post(account, method, &nonce, "application/x-www-form-urlencoded", &body)
+}
+
+/// Like `private`, for endpoints which take a json body.
+fn private_json(
+    account: &Account,
+    method: &str,
+    mut params: serde_json::Map<String, serde_json::Value>,
+) -> Result<Vec<u8>, String> {
+    let nonce = next_nonce(account);
+
+    params.insert("nonce".to_owned(), serde_json::Value::from(nonce.clone()));
+
+    let body = serde_json::to_string(&params).map_err(|e| format!("{:?}", e))?;
+
+    signed_post(account, method, &nonce, "application/json", &body)
+}
+
+fn signed_post(
+    account: &Account,
+    method: &str,
+    nonce: &str,
+    content_type: &str,
+    body: &str,
+) -> Result<Vec<u8>, String> {
+    let path = format!("/0/private/{}", method);
+    let url = format!("https://api.kraken.com{}", path);
+
+    let mut dst = Vec::new();
+    let mut easy = Easy::new();
+
+    easy.url(&url).unwrap();
+    easy.post(true).unwrap();
+
+    let mut body_bytes = body.as_bytes();
+    let secret = base64::decode(&account.secret).unwrap();
+    let mut hmac = Hmac::new(Sha512::new(), &secret);
+    let mut body_hasher = Sha256::new();
+
+    body_hasher.input(nonce.as_bytes());
+    body_hasher.input(body_bytes);
+
+    hmac.input(path.as_bytes());
+    let mut out: [u8; 32] = [0; 32];
+    body_hasher.result(&mut out);
+    hmac.input(&out);
+
+    let sign = base64::encode(hmac.result().code());
+
+    easy.post_field_size(body_bytes.len() as u64).unwrap();
+
+    let mut list = List::new();
+
+    list.append(&format!("Content-Type: {}", content_type))
+        .unwrap();
+    list.append(&format!("API-Key: {}", account.key)).unwrap();
+    list.append(&format!("API-Sign: {}", sign)).unwrap();
+
+    easy.http_headers(list).unwrap();
+
+    let result = {
+        let mut transfer = easy.transfer();
+
+        transfer
+            .read_function(|buf| Ok(body_bytes.read(buf).unwrap_or(0)))
+            .unwrap();
+
+        transfer
+            .write_function(|data| {
+                dst.extend_from_slice(data);
+                Ok(data.len())
+            })
+            .unwrap();
+
+        transfer.perform()
+    };
+
+    result.map_err(|e| format!("{:?}", e)).map(|_x| dst)
+}
+
+///
+/// Returns an array of asset names and balance amount.
+///
+/// # Arguments
+///
+/// + `account` - The account credentials to use.
+///
+pub fn balance(account: &Account) -> Result<HashMap<String, Decimal>, String> {
+    let mut params = HashMap::new();
+    private(account, "Balance", &mut params).and_then(|r| {
+        serde_json::from_slice(&r)
+            .map_err(|e| format!("{:?}", e))
+            .and_then(
+                |result: KrakenResult<HashMap<String, Decimal>>| if !result.error.is_empty() {
+                    Err(format!("{:?}", result.error))
+                } else {
+                    Ok(result.result.unwrap())
+                },
+            )
+    })
+}
+
+///
+/// Returns an array of asset names and their extended balances.
+///
+/// # Arguments
+///
+/// + `account` - The account credentials to use.
+///
+/// # Result
+///
+/// ```json
+/// {
+///     "error": [],
+///     "result": {
+///         "ZUSD": {
+///             "balance": "25435.21",
+///             "hold_trade": "8249.76"
+///         },
+///         "XXBT": {
+///             "balance": "1.2435",
+///             "hold_trade": "0.8423"
+///         }
+///     }
+/// }
+/// ```
+///
+pub fn balance_ex(account: &Account) -> Result<HashMap<String, ExtendedBalance>, String> {
+    let mut params = HashMap::new();
+    private(account, "BalanceEx", &mut params).and_then(|r| {
+        serde_json::from_slice(&r)
+            .map_err(|e| format!("{:?}", e))
+            .and_then(
+                |result: KrakenResult<HashMap<String, ExtendedBalance>>| if !result.error.is_empty() {
+                    Err(format!("{:?}", result.error))
+                } else {
+                    match result.result {
+                        Some(stuff) => Ok(stuff),
+                        None => Err(String::from("empty result")),
+                    }
+                },
+            )
+    })
+}
+
+///
+/// Returns the amount available to trade per asset.
+///
+/// # Arguments
+///
+/// + `account` - The account credentials to use.
+///
+pub fn available_balance(account: &Account) -> Result<HashMap<String, Decimal>, String> {
+    balance_ex(account).map(|balances| {
+        balances
+            .into_iter()
+            .map(|(asset, balance)| (asset, balance.available()))
+            .collect()
+    })
+}
+
+///
+/// Get trade balance.
+///
+/// # Arguments
+///
+/// + `account` - The account credentials to use.
+/// + `asset` - class (optional): currency (default).
+/// + `asset` = base asset used to determine balance (default = ZUSD).
+///
+pub fn trade_balance(
+    account: &Account,
+    aclass: Option<&str>,
+    asset: Option<&str>,
+) -> Result<TradeBalance, String> {
+    let mut params = HashMap::new();
+
+    if let Some(ct) = aclass {
+        params.insert("aclass".to_owned(), String::from(ct));
+    }
+
+    if let Some(ct) = asset {
+        params.insert("asset".to_owned(), String::from(ct));
+    }
+
+    private(account, "TradeBalance", &mut params).and_then(|r| {
+        serde_json::from_slice(&r)
+            .map_err(|e| format!("{:?}", e))
+            .and_then(|result: KrakenResult<TradeBalance>| if !result.error.is_empty() {
+                Err(format!("{:?}", result.error))
+            } else {
+                Ok(result.result.unwrap())
+            })
+    })
+}
+
+///
+/// Get trade volume.
+///
+/// # Arguments
+///
+/// + `account` - The account credentials to use.
+/// + `pairs` - list of asset pairs to get fee info on (optional).
+/// + `fee_info` - whether or not to include fee info in results (optional).
+///
+/// # Note
+///
+/// If an asset pair is on a maker/taker fee schedule,
+/// the taker side is given in "fees" and maker side in "fees_maker".
+/// For pairs not on maker/taker, they will only be given in "fees".
+///
+/// # Result
+///
+/// ```json
+/// {
+///     "error": [],
+///     "result": {
+///         "currency": "ZUSD",
+///         "volume": "200709587.4223",
+///         "fees": {
+///             "XXBTZUSD": {
+///                 "fee": "0.1000",
+///                 "minfee": "0.1000",
+///                 "maxfee": "0.2600",
+///                 "nextfee": null,
+///                 "nextvolume": null,
+///                 "tiervolume": "10000000.0000"
+///             }
+///         },
+///         "fees_maker": {
+///             "XXBTZUSD": {
+///                 "fee": "0.0000",
+///                 "minfee": "0.0000",
+///                 "maxfee": "0.1600",
+///                 "nextfee": null,
+///                 "nextvolume": null,
+///                 "tiervolume": "10000000.0000"
+///             }
+///         }
+///     }
+/// }
+/// ```
+///
+pub fn trade_volume(
+    account: &Account,
+    pairs: Option<&[&str]>,
+    fee_info: Option<bool>,
+) -> Result<TradeVolume, String> {
+    let mut params = HashMap::new();
+
+    if let Some(pairs) = pairs {
+        params.insert("pair".to_owned(), pairs.join(","));
+    }
+
+    if let Some(fee_info) = fee_info {
+        let val = if fee_info { "true" } else { "false" };
+
+        params.insert("fee-info".to_owned(), val.to_owned());
+    }
+
+    private(account, "TradeVolume", &mut params).and_then(|r| {
+        serde_json::from_slice(&r)
+            .map_err(|e| format!("{:?}", e))
+            .and_then(|result: KrakenResult<TradeVolume>| if !result.error.is_empty() {
+                Err(format!("{:?}", result.error))
+            } else {
+                match result.result {
+                    Some(stuff) => Ok(stuff),
+                    None => Err(String::from("empty result")),
+                }
+            })
+    })
+}
+
+///
+/// Get open orders.
+///
+/// # Arguments
+///
+/// + `trades` - whether or not to include trades in output (optional.  default = false).
+/// + `userref` - restrict results to given user reference id (optional).
+///
+/// # Note
+///
+/// Unless otherwise stated, costs, fees, prices, and volumes are in the asset pair's scale,
+/// not the currency's scale. For example, if the asset pair uses a lot size that has a scale of 8,
+/// the volume will use a scale of 8, even if the currency it represents only has a scale of 2.
+/// Similarly, if the asset pair's pricing scale is 5, the scale will remain as 5,
+/// even if the underlying currency has a scale of 8.
+///
+pub fn open_orders(
+    account: &Account,
+    trades: Option<bool>,
+    userref: Option<&str>,
+) -> Result<OpenOrders, String> {
+    let mut params = HashMap::new();
+
+    if let Some(ct) = trades {
+        params.insert(
+            "trades".to_owned(),
+            if ct {
+                String::from("true")
+            } else {
+                String::from("false")
+            },
+        );
+    }
+
+    if let Some(ct) = userref {
+        params.insert("userref".to_owned(), String::from(ct));
+    }
+
+    private(account, "OpenOrders", &mut params).and_then(|r| {
+        serde_json::from_slice(&r)
+            .map_err(|e| format!("{:?}", e))
+            .and_then(|result: KrakenResult<OpenOrders>| if !result.error.is_empty() {
+                Err(format!("{:?}", result.error))
+            } else {
+                match result.result {
+                    Some(stuff) => Ok(stuff),
+                    None => Err(String::from("empty result")),
+                }
+            })
+    })
+}
+
+///
+/// Get closed orders.
+///
+/// # Arguments
+///
+/// + `trades` - whether or not to include trades in output (optional.  default = false).
+/// + `userref` - restrict results to given user reference id (optional).
+/// + `start` - starting unix timestamp or order tx id of results (optional.  exclusive).
+/// + `end` - ending unix timestamp or order tx id of results (optional.  inclusive).
+/// + `ofs` - result offset.
+/// + `closetime` = which time to use (optional)
+///   open
+///   close
+///   both (default)
+///
+/// # Note
+///
+/// Times given by order tx ids are more accurate than unix timestamps.
+/// If an order tx id is given for the time, the order's open time is used.
+///
+pub fn closed_orders(
+    account: &Account,
+    cfg: Option<ClosedOrdersConfig>,
+) -> Result<ClosedOrders, String> {
+    let mut params = HashMap::new();
+
+    if let Some(cfg) = cfg {
+
+        if let Some(trades) = cfg.trades {
+            if trades {
+                params.insert("trades".to_owned(), "true".to_owned());
+            } else {
+                params.insert("trades".to_owned(), "false".to_owned());
+            }
+        }
+
+        if let Some(userref) = cfg.userref {
+            params.insert("userref".to_owned(), userref);
+        }
+
+        if let Some(start) = cfg.start {
+            params.insert("start".to_owned(), format!("{}", start));
+        }
+
+        if let Some(end) = cfg.end {
+            params.insert("end".to_owned(), format!("{}", end));
+        }
+
+        if let Some(ofs) = cfg.ofs {
+            params.insert("ofs".to_owned(), format!("{}", ofs));
+        }
+
+        if let Some(closetime) = cfg.closetime {
+            let value = match closetime {
+                ClosedOrdersConfigCloseTime::Open => "open",
+                ClosedOrdersConfigCloseTime::Close => "close",
+                ClosedOrdersConfigCloseTime::Both => "both",
+            };
+
+            params.insert("closetime".to_owned(), value.to_owned());
+        }
+    }
+
+    private(account, "ClosedOrders", &mut params).and_then(|r| {
+        serde_json::from_slice(&r)
+            .map_err(|e| format!("{:?}", e))
+            .and_then(|result: KrakenResult<ClosedOrders>| if !result.error.is_empty() {
+                Err(format!("{:?}", result.error))
+            } else {
+                Ok(result.result.unwrap())
+            })
+    })
+}
+
+///
+/// Iterator over all closed orders matching a query, see `closed_orders_iter`.
+///
+pub struct ClosedOrdersIter<'a> {
+    account: &'a Account,
+    cfg: ClosedOrdersConfig,
+    offset: u64,
+    seen: HashSet<String>,
+    buffer: VecDeque<(String, OrderInfo)>,
+    done: bool,
+}
+
+impl<'a> Iterator for ClosedOrdersIter<'a> {
+    type Item = Result<(String, OrderInfo), String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(order) = self.buffer.pop_front() {
+                return Some(Ok(order));
+            }
+
+            if self.done {
+                return None;
+            }
+
+            let mut cfg = self.cfg.clone();
+            cfg.ofs = Some(self.offset);
+
+            match closed_orders(self.account, Some(cfg)) {
+                Ok(page) => self.receive(page),
+                Err(e) => {
+                    self.done = true;
+                    return Some(Err(e));
+                }
+            }
+        }
+    }
+}
+
+impl<'a> ClosedOrdersIter<'a> {
+    /// Advances the offset by a received page and buffers the orders not seen before.
+    fn receive(&mut self, page: ClosedOrders) {
+        let received = page.closed.len() as u64;
+        self.offset += received;
+        self.done = received == 0 || self.offset >= u64::from(page.count);
+
+        let mut orders: Vec<(String, OrderInfo)> = page.closed
+            .into_iter()
+            .filter(|(txid, _)| self.seen.insert(txid.clone()))
+            .collect();
+
+        // newest first, like kraken pages them
+        orders.sort_by(|a, b| {
+            let a = a.1.closetm.unwrap_or(a.1.opentm);
+            let b = b.1.closetm.unwrap_or(b.1.opentm);
+            b.partial_cmp(&a).unwrap_or(::std::cmp::Ordering::Equal)
+        });
+
+        self.buffer.extend(orders);
+    }
+}
+
+///
+/// Iterates over all closed orders, following the result offset until `count` is exhausted.
+///
+/// # Arguments
+///
+/// + `account` - The account credentials to use.
+/// + `cfg` - query to run, `ofs` is used as starting offset (optional).
+///
+/// # Note
+///
+/// Orders which close while iterating shift the offsets of older orders,
+/// orders which were already returned are skipped.
+/// The iterator stops after the first error.
+///
+pub fn closed_orders_iter<'a>(
+    account: &'a Account,
+    cfg: Option<ClosedOrdersConfig>,
+) -> ClosedOrdersIter<'a> {
+    let cfg = cfg.unwrap_or_default();
+
+    ClosedOrdersIter {
+        account,
+        offset: cfg.ofs.unwrap_or(0),
+        cfg,
+        seen: HashSet::new(),
+        buffer: VecDeque::new(),
+        done: false,
+    }
+}
+
+/// Maximum number of transaction ids kraken accepts in a single `QueryOrders` request.
+const QUERY_ORDERS_MAX_TXIDS: usize = 50;
+
+///
+/// Query orders info.
+///
+/// # Arguments
+///
+/// + `trades` - whether or not to include trades in output (optional.  default = false).
+/// + `userref` - restrict results to given user reference id (optional).
+/// + `txids` - transaction ids to query info about.
+///
+/// # Note
+///
+/// Kraken accepts at most 50 transaction ids per request,
+/// longer lists are split into several requests and the results are merged.
+///
+/// # Result
+///
+/// ```json
+/// {
+///     "error": [],
+///     "result": {
+///         "OBCMZD-JIEE7-77TH3F": {
+///             "refid": null,
+///             "userref": 0,
+///             "status": "closed",
+///             ...
+///         }
+///     }
+/// }
+/// ```
+///
+pub fn query_orders(
+    account: &Account,
+    trades: Option<bool>,
+    userref: Option<String>,
+    txids: &[&str],
+) -> Result<HashMap<String, OrderInfo>, String> {
+    let mut orders = HashMap::new();
+
+    for txids in txid_batches(txids) {
+        orders.extend(query_orders_chunk(account, trades, userref.clone(), txids)?);
+    }
+
+    Ok(orders)
+}
+
+/// Comma delimited `txid` parameters for `QueryOrders`, a single `None` if no ids are given.
+fn txid_batches(txids: &[&str]) -> Vec<Option<String>> {
+    if txids.is_empty() {
+        return vec![None];
+    }
+
+    txids
+        .chunks(QUERY_ORDERS_MAX_TXIDS)
+        .map(|chunk| Some(chunk.join(",")))
+        .collect()
+}
+
+fn query_orders_chunk(
+    account: &Account,
+    trades: Option<bool>,
+    userref: Option<String>,
+    txids: Option<String>,
+) -> Result<HashMap<String, OrderInfo>, String> {
+    let mut params = HashMap::new();
+
+    if let Some(trades) = trades {
+        let val = if trades { "true" } else { "false" };
+
+        params.insert("trades".to_owned(), val.to_owned());
+    }
+
+    if let Some(userref) = userref {
+        params.insert("userref".to_owned(), userref);
+    }
+
+    if let Some(txids) = txids {
+        params.insert("txid".to_owned(), txids);
+    }
+
+    private(account, "QueryOrders", &mut params).and_then(|r| {
+        serde_json::from_slice(&r)
+            .map_err(|e| format!("{:?}", e))
+            .and_then(
+                |result: KrakenResult<HashMap<String, OrderInfo>>| if !result.error.is_empty() {
+                    Err(format!("{:?}", result.error))
+                } else {
+                    match result.result {
+                        Some(stuff) => Ok(stuff),
+                        None => Err(String::from("empty result")),
+                    }
+                },
+            )
+    })
+}
+
+///
+/// Get open positions.
+///
+/// # Arguments
+///
+/// + `account` - The account credentials to use.
+/// + `txids` - list of transaction ids to restrict output to (optional).
+/// + `docalcs` - whether or not to include profit/loss calculations (optional.  default = false).
+///
+/// # Note
+///
+/// Unless otherwise stated, costs, fees, prices, and volumes are in the asset pair's scale,
+/// not the currency's scale.
+///
+/// # Result
+///
+/// ```json
+/// {
+///     "error": [],
+///     "result": {
+///         "TF5GVO-T7ZZ2-6NBKBI": {
+///             "ordertxid": "OLWNFG-LLH4R-D6SFFP",
+///             "posstatus": "open",
+///             "pair": "XXBTZUSD",
+///             "time": 1605280097.8294,
+///             "type": "buy",
+///             "ordertype": "limit",
+///             "cost": "104610.52842",
+///             "fee": "289.06565",
+///             "vol": "8.82412861",
+///             "vol_closed": "0.20200000",
+///             "margin": "20922.10568",
+///             "value": "258797.5",
+///             "net": "+154186.9728",
+///             "terms": "0.0100% per 4 hours",
+///             "rollovertm": "1616672637",
+///             "misc": "",
+///             "oflags": ""
+///         }
+///     }
+/// }
+/// ```
+///
+pub fn open_positions(
+    account: &Account,
+    txids: Option<&[&str]>,
+    docalcs: Option<bool>,
+) -> Result<HashMap<String, Position>, String> {
+    let mut params = HashMap::new();
+
+    if let Some(txids) = txids {
+        params.insert("txid".to_owned(), txids.join(","));
+    }
+
+    if let Some(docalcs) = docalcs {
+        let val = if docalcs { "true" } else { "false" };
+
+        params.insert("docalcs".to_owned(), val.to_owned());
+    }
+
+    private(account, "OpenPositions", &mut params).and_then(|r| {
+        serde_json::from_slice(&r)
+            .map_err(|e| format!("{:?}", e))
+            .and_then(
+                |result: KrakenResult<HashMap<String, Position>>| if !result.error.is_empty() {
+                    Err(format!("{:?}", result.error))
+                } else {
+                    match result.result {
+                        Some(stuff) => Ok(stuff),
+                        None => Err(String::from("empty result")),
+                    }
+                },
+            )
+    })
+}
+
+
+///
+/// Create a new order.
+///
+/// # Note
+///
+/// + Prices can be preceded by +, -, or # to signify the price as a relative amount (with the exception of trailing stops,
+///   which are always relative).
+///   * + adds the amount to the current offered price.
+///   * - subtracts the amount from the current offered price.
+///   * # will either add or subtract the amount to the current offered price, depending on the type and order type used.
+///  Relative prices can be suffixed with a % to signify the relative amount as a percentage of the offered price.
+/// + For orders using leverage, 0 can be used for the volume to auto-fill the volume needed to close out your position.
+/// + If you receive the error "EOrder:Trading agreement required", refer to your API key management page for further details.
+///
+/// # Result
+///
+/// ```json
+/// {
+///     "error": [],
+///     "result": {
+///         "descr": {
+///             "order": "buy 1.25000000 XBTUSD @ limit 27500.0"
+///         },
+///         "txid": ["OU22CG-KLAF2-FWUDD7"]
+///     }
+/// }
+/// ```
+///
+/// When only validating, `txid` is missing and left empty.
+///
+pub fn add_order(account: &Account, order: NewOrder) -> Result<AddOrderResult, String> {
+    let mut params = order_params(order)?;
+
+    private(account, "AddOrder", &mut params).and_then(|r| {
+        serde_json::from_slice(&r)
+            .map_err(|e| format!("{:?}", e))
+            .and_then(|result: KrakenResult<AddOrderResult>| if !result.error.is_empty() {
+                Err(format!("{:?}", result.error))
+            } else {
+                match result.result {
+                    Some(stuff) => Ok(stuff),
+                    None => Err(String::from("empty result")),
+                }
+            })
+    })
+}
+
+/// Maps a new order to the request parameters of `AddOrder`.
+fn order_params(order: NewOrder) -> Result<HashMap<String, String>, String> {
+    let mut params = HashMap::new();
+
+    params.insert("pair".to_owned(), order.pair);
+    params.insert("volume".to_owned(), order.volume);
+
+    params.insert("type".to_owned(), String::from(order.kind.as_str()));
+
+    params.insert("ordertype".to_owned(), String::from(order.order_type.as_str()));
+
+    if let Some(price) = order.price {
+        params.insert("price".to_owned(), price);
+    }
+
+    if let Some(price) = order.price2 {
+        params.insert("price2".to_owned(), price);
+    }
+
+    if let Some(leverage) = order.leverage {
+        params.insert("leverage".to_owned(), leverage);
+    }
+
+    if let Some(oflags) = order.oflags {
+        params.insert("oflags".to_owned(), oflags.to_string());
+    }
+
+    if let Some(timeinforce) = order.timeinforce {
+        params.insert("timeinforce".to_owned(), String::from(timeinforce.as_str()));
+    }
+
+    if let Some(userref) = order.userref {
+        params.insert("userref".to_owned(), userref);
+    }
+
+    if let Some(starttm) = order.starttm {
+        starttm.check()?;
+        params.insert("starttm".to_owned(), starttm.to_string());
+    }
+
+    if let Some(expiretm) = order.expiretm {
+        expiretm.check()?;
+        params.insert("expiretm".to_owned(), expiretm.to_string());
+    }
+
+    if let Some(true) = order.validate {
+        params.insert("validate".to_owned(), String::from("1"));
+    }
+
+    if let Some(close) = order.close {
+        params.insert(
+            "close[ordertype]".to_owned(),
+            String::from(close.order_type.as_str()),
+        );
+        params.insert("close[price]".to_owned(), close.price);
+
+        if let Some(price) = close.price2 {
+            params.insert("close[price2]".to_owned(), price);
+        }
+    }
+
+    if let Some(reduce_only) = order.reduce_only {
+        let val = if reduce_only { "true" } else { "false" };
+        params.insert("reduce_only".to_owned(), String::from(val));
+    }
+
+    if let Some(cl_ord_id) = order.cl_ord_id {
+        params.insert("cl_ord_id".to_owned(), cl_ord_id);
+    }
+
+    if let Some(trigger) = order.trigger {
+        params.insert("trigger".to_owned(), String::from(trigger.as_str()));
+    }
+
+    if let Some(stptype) = order.stptype {
+        params.insert("stptype".to_owned(), String::from(stptype.as_str()));
+    }
+
+    Ok(params)
+}
+
+/// Minimum and maximum number of orders kraken accepts in a single `AddOrderBatch` request.
+const ORDER_BATCH_SIZE: (usize, usize) = (2, 15);
+
+///
+/// Create a batch of new orders for a single asset pair.
+///
+/// # Arguments
+///
+/// + `account` - The account credentials to use.
+/// + `pair` - asset pair of all orders.
+/// + `orders` - between 2 and 15 orders, all for `pair`.
+/// + `validate` - validate inputs only.  do not submit orders (optional).
+///
+/// # Note
+///
+/// The `validate` field of the single orders is ignored, the whole batch is validated or submitted.
+/// Orders which fail are reported in the result, the other orders are placed anyway.
+///
+/// # Result
+///
+/// ```json
+/// {
+///     "error": [],
+///     "result": {
+///         "orders": [
+///             {
+///                 "txid": "OUF4EM-FRGI2-MQMWZD",
+///                 "descr": {
+///                     "order": "buy 1.25000000 XBTUSD @ limit 27500.0"
+///                 }
+///             },
+///             {
+///                 "error": "EOrder:Insufficient funds"
+///             }
+///         ]
+///     }
+/// }
+/// ```
+///
+pub fn add_order_batch(
+    account: &Account,
+    pair: &str,
+    orders: Vec<NewOrder>,
+    validate: Option<bool>,
+) -> Result<Vec<BatchOrderResult>, String> {
+    if orders.len() < ORDER_BATCH_SIZE.0 || orders.len() > ORDER_BATCH_SIZE.1 {
+        return Err(format!(
+            "batch needs between {} and {} orders, got {}",
+            ORDER_BATCH_SIZE.0,
+            ORDER_BATCH_SIZE.1,
+            orders.len()
+        ));
+    }
+
+    let params = batch_params(pair, orders, validate)?;
+
+    private_json(account, "AddOrderBatch", params).and_then(|r| batch_order_results(&r))
+}
+
+/// Maps a batch of orders to the json body of `AddOrderBatch`.
+fn batch_params(
+    pair: &str,
+    orders: Vec<NewOrder>,
+    validate: Option<bool>,
+) -> Result<serde_json::Map<String, serde_json::Value>, String> {
+    let mut params = serde_json::Map::new();
+    let mut entries = Vec::new();
+
+    for (i, order) in orders.into_iter().enumerate() {
+        if order.pair != pair {
+            return Err(format!("order {} is for {}, not {}", i, order.pair, pair));
+        }
+
+        entries.push(serde_json::Value::Object(order_json(order)?));
+    }
+
+    params.insert("pair".to_owned(), serde_json::Value::from(pair));
+    params.insert("orders".to_owned(), serde_json::Value::from(entries));
+
+    if let Some(true) = validate {
+        params.insert("validate".to_owned(), serde_json::Value::from(true));
+    }
+
+    Ok(params)
+}
+
+/// A single order of `AddOrderBatch`, the close order becomes a nested object.
+fn order_json(order: NewOrder) -> Result<serde_json::Map<String, serde_json::Value>, String> {
+    let mut entry = serde_json::Map::new();
+    let mut close = serde_json::Map::new();
+
+    for (key, value) in order_params(order)? {
+        match key.as_str() {
+            "pair" | "validate" => {}
+            "reduce_only" => {
+                entry.insert(key, serde_json::Value::from(value == "true"));
+            }
+            "userref" => {
+                let userref = match value.parse::<i32>() {
+                    Ok(userref) => serde_json::Value::from(userref),
+                    Err(_) => serde_json::Value::from(value),
+                };
+
+                entry.insert(key, userref);
+            }
+            _ if key.starts_with("close[") => {
+                let name = key["close[".len()..key.len() - 1].to_owned();
+
+                close.insert(name, serde_json::Value::from(value));
+            }
+            _ => {
+                entry.insert(key, serde_json::Value::from(value));
+            }
+        }
+    }
+
+    if !close.is_empty() {
+        entry.insert("close".to_owned(), serde_json::Value::Object(close));
+    }
+
+    Ok(entry)
+}
+
+fn batch_order_results(response: &[u8]) -> Result<Vec<BatchOrderResult>, String> {
+    serde_json::from_slice(response)
+        .map_err(|e| format!("{:?}", e))
+        .and_then(|result: KrakenResult<BatchOrders>| if !result.error.is_empty() {
+            Err(format!("{:?}", result.error))
+        } else {
+            match result.result {
+                Some(stuff) => Ok(stuff.orders),
+                None => Err(String::from("empty result")),
+            }
+        })
+}
+
+///
+/// Edits an open order.
+///
+/// # Note
+///
+/// The original order is canceled and a new order with a new transaction id is placed,
+/// which loses the original order's queue priority. See `amend_order` to keep it.
+///
+/// # Result
+///
+/// ```json
+/// {
+///     "error": [],
+///     "result": {
+///         "status": "ok",
+///         "txid": "OFVXHJ-KPQ3B-VS7ELA",
+///         "originaltxid": "OHYO67-6LP66-HMQ437",
+///         "volume": "0.00030000",
+///         "price": "19500.0",
+///         "price2": "32500.0",
+///         "orders_cancelled": 1,
+///         "descr": {
+///             "order": "buy 0.00030000 XXBTZGBP @ limit 19500.0"
+///         }
+///     }
+/// }
+/// ```
+///
+pub fn edit_order(account: &Account, edit: EditOrder) -> Result<EditOrderResult, String> {
+    let mut params = edit_params(edit);
+
+    private(account, "EditOrder", &mut params).and_then(|r| {
+        serde_json::from_slice(&r)
+            .map_err(|e| format!("{:?}", e))
+            .and_then(|result: KrakenResult<EditOrderResult>| if !result.error.is_empty() {
+                Err(format!("{:?}", result.error))
+            } else {
+                match result.result {
+                    Some(stuff) => edit_order_result(stuff),
+                    None => Err(String::from("empty result")),
+                }
+            })
+    })
+}
+
+/// Kraken reports some failed edits as a result with status "error".
+fn edit_order_result(result: EditOrderResult) -> Result<EditOrderResult, String> {
+    if result.status == "error" {
+        let originaltxid = result.originaltxid;
+
+        Err(result.error_message.unwrap_or_else(
+            || format!("edit of {} failed", originaltxid),
+        ))
+    } else {
+        Ok(result)
+    }
+}
+
+/// Maps order changes to the request parameters of `EditOrder`.
+fn edit_params(edit: EditOrder) -> HashMap<String, String> {
+    let mut params = HashMap::new();
+
+    params.insert("txid".to_owned(), edit.txid);
+    params.insert("pair".to_owned(), edit.pair);
+
+    if let Some(volume) = edit.volume {
+        params.insert("volume".to_owned(), volume);
+    }
+
+    if let Some(price) = edit.price {
+        params.insert("price".to_owned(), price);
+    }
+
+    if let Some(price) = edit.price2 {
+        params.insert("price2".to_owned(), price);
+    }
+
+    if let Some(oflags) = edit.oflags {
+        params.insert("oflags".to_owned(), oflags.to_string());
+    }
+
+    if let Some(userref) = edit.userref {
+        params.insert("userref".to_owned(), userref);
+    }
+
+    if let Some(true) = edit.validate {
+        params.insert("validate".to_owned(), String::from("true"));
+    }
+
+    params
+}
+
+///
+/// Amends an open order in place.
+///
+/// # Note
+///
+/// The order keeps its transaction id. Queue priority is kept,
+/// unless the price changes or the quantity increases.
+///
+/// # Result
+///
+/// ```json
+/// {
+///     "error": [],
+///     "result": {
+///         "amend_id": "TNGJFU-5CD67-ZV3AEO"
+///     }
+/// }
+/// ```
+///
+pub fn amend_order(account: &Account, amend: AmendOrder) -> Result<AmendOrderResult, String> {
+    let mut params = HashMap::new();
+
+    params.insert("txid".to_owned(), amend.txid);
+
+    if let Some(qty) = amend.order_qty {
+        params.insert("order_qty".to_owned(), qty);
+    }
+
+    if let Some(price) = amend.limit_price {
+        params.insert("limit_price".to_owned(), price);
+    }
+
+    if let Some(price) = amend.trigger_price {
+        params.insert("trigger_price".to_owned(), price);
+    }
+
+    if let Some(post_only) = amend.post_only {
+        let val = if post_only { "true" } else { "false" };
+
+        params.insert("post_only".to_owned(), val.to_owned());
+    }
+
+    private(account, "AmendOrder", &mut params).and_then(|r| {
+        serde_json::from_slice(&r)
+            .map_err(|e| format!("{:?}", e))
+            .and_then(|result: KrakenResult<AmendOrderResult>| if !result.error.is_empty() {
+                Err(format!("{:?}", result.error))
+            } else {
+                match result.result {
+                    Some(stuff) => Ok(stuff),
+                    None => Err(String::from("empty result")),
+                }
+            })
+    })
+}
+
+///
+/// Cancels an order.
+///
+/// # Note
+///
+///  txid may be a user reference id.
+///
+pub fn cancel_order(account: &Account, txid: &str) -> Result<CanceldOrders, String> {
+    let mut params = HashMap::new();
+
+    params.insert("txid".to_owned(), String::from(txid));
+
+    private(account, "CancelOrder", &mut params).and_then(|r| {
+        serde_json::from_slice(&r)
+            .map_err(|e| format!("{:?}", e))
+            .and_then(
+                |result: KrakenResult<CanceldOrders>| if !result.error.is_empty() {
+                    Err(format!("{:?}", result.error))
+                } else {
+                    Ok(result.result.unwrap())
+                },
+            )
+    })
+}
+
+///
+/// Cancels all open orders.
+///
+/// # Result
+///
+/// ```json
+/// {
+///     "error": [],
+///     "result": {
+///         "count": 4
+///     }
+/// }
+/// ```
+///
+pub fn cancel_all(account: &Account) -> Result<CanceldOrders, String> {
+    let mut params = HashMap::new();
+
+    private(account, "CancelAll", &mut params).and_then(|r| {
+        serde_json::from_slice(&r)
+            .map_err(|e| format!("{:?}", e))
+            .and_then(
+                |result: KrakenResult<CanceldOrders>| if !result.error.is_empty() {
+                    Err(format!("{:?}", result.error))
+                } else {
+                    match result.result {
+                        Some(stuff) => Ok(stuff),
+                        None => Err(String::from("empty result")),
+                    }
+                },
+            )
+    })
+}
+
+///
+/// Dead man's switch: cancels all open orders after the timeout, unless called again before.
+///
+/// # Arguments
+///
+/// + `account` - The account credentials to use.
+/// + `timeout` - duration in seconds until all orders are canceled, `0` disables the timer.
+///
+/// # Note
+///
+/// See `DeadMansSwitch` for keeping the timer renewed in the background.
+///
+/// # Result
+///
+/// ```json
+/// {
+///     "error": [],
+///     "result": {
+///         "currentTime": "2023-03-24T17:41:56Z",
+///         "triggerTime": "2023-03-24T17:42:56Z"
+///     }
+/// }
+/// ```
+///
+pub fn cancel_all_orders_after(
+    account: &Account,
+    timeout: u32,
+) -> Result<CancelAllOrdersAfter, String> {
+    let mut params = HashMap::new();
+
+    params.insert("timeout".to_owned(), format!("{}", timeout));
+
+    private(account, "CancelAllOrdersAfter", &mut params).and_then(|r| {
+        serde_json::from_slice(&r)
+            .map_err(|e| format!("{:?}", e))
+            .and_then(
+                |result: KrakenResult<CancelAllOrdersAfter>| if !result.error.is_empty() {
+                    Err(format!("{:?}", result.error))
+                } else {
+                    match result.result {
+                        Some(stuff) => Ok(stuff),
+                        None => Err(String::from("empty result")),
+                    }
+                },
+            )
+    })
+}
+
+///
+/// Keeps renewing `cancel_all_orders_after` from a background thread while alive.
+///
+/// Dropping the guard only stops the renewal, so the timer fires and cancels all orders,
+/// just as it does when the process hangs or dies. Use `disarm` to stop the timer instead.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// extern crate kraken;
+///
+/// use std::time::Duration;
+///
+/// fn main() {
+///   let account = kraken::Account {
+///     key: String::from("<your-key>"),
+///     secret: String::from("<your-secret>"),
+///   };
+///
+///   let switch = kraken::DeadMansSwitch::arm(&account, 60, Duration::from_secs(15))
+///     .expect("could not arm dead man's switch");
+///
+///   // trade ...
+///
+///   switch.disarm().expect("could not disarm dead man's switch");
+/// }
+/// ```
+///
+pub struct DeadMansSwitch {
+    account: Account,
+    stop: Option<mpsc::Sender<()>>,
+    handle: Option<thread::JoinHandle<()>>,
+    last_error: Arc<Mutex<Option<String>>>,
+}
+
+impl DeadMansSwitch {
+    ///
+    /// Sets the timer to `timeout` seconds and renews it every `interval`.
+    ///
+    /// Fails if the first request fails or if `interval` is not shorter than `timeout`.
+    ///
+    pub fn arm(account: &Account, timeout: u32, interval: Duration) -> Result<DeadMansSwitch, String> {
+        if timeout == 0 || interval >= Duration::from_secs(u64::from(timeout)) {
+            return Err(format!(
+                "renewal interval {:?} must be shorter than timeout {}s",
+                interval,
+                timeout
+            ));
+        }
+
+        cancel_all_orders_after(account, timeout)?;
+
+        let (stop, stopped) = mpsc::channel::<()>();
+        let last_error = Arc::new(Mutex::new(None));
+        let renewal = account.clone();
+        let errors = last_error.clone();
+
+        let handle = thread::spawn(move || {
+            while let Err(mpsc::RecvTimeoutError::Timeout) = stopped.recv_timeout(interval) {
+                let result = cancel_all_orders_after(&renewal, timeout);
+                *errors.lock().unwrap_or_else(|e| e.into_inner()) = result.err();
+            }
+        });
+
+        Ok(DeadMansSwitch {
+            account: account.clone(),
+            stop: Some(stop),
+            handle: Some(handle),
+            last_error,
+        })
+    }
+
+    /// The error of the last renewal, if it failed.
+    pub fn last_error(&self) -> Option<String> {
+        self.last_error.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Stops the renewal and disables the timer.
+    pub fn disarm(mut self) -> Result<CancelAllOrdersAfter, String> {
+        self.stop_renewal();
+
+        cancel_all_orders_after(&self.account, 0)
+    }
+
+    fn stop_renewal(&mut self) {
+        // dropping the sender wakes up the renewal thread
+        self.stop.take();
+
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+impl Drop for DeadMansSwitch {
+    fn drop(&mut self) {
+        self.stop_renewal();
+    }
+}
+
+///
+/// Closes an open position with a market order.
+///
+/// # Note
+///
+/// See `Position::closing_order` for how the order is derived. Use it directly
+/// when the closing order needs further adjustments before submission.
+///
+pub fn close_position(account: &Account, position: &Position) -> Result<AddOrderResult, String> {
+    position.closing_order().and_then(
+        |order| add_order(account, order),
+    )
+}
+
+///
+/// Request export of trades or ledgers.
+///
+/// # Result
+///
+/// ```json
+/// {
+///     "error": [],
+///     "result": {
+///         "id": "TCJA"
+///     }
+/// }
+/// ```
+///
+pub fn add_export(account: &Account, export: NewExport) -> Result<String, String> {
+    let mut params = HashMap::new();
+
+    let report = match export.report {
+        ExportReport::Trades => "trades",
+        ExportReport::Ledgers => "ledgers",
+    };
+
+    params.insert("report".to_owned(), String::from(report));
+    params.insert("description".to_owned(), export.description);
+
+    if let Some(format) = export.format {
+        let value = match format {
+            ExportFormat::Csv => "CSV",
+            ExportFormat::Tsv => "TSV",
+        };
+
+        params.insert("format".to_owned(), String::from(value));
+    }
+
+    if let Some(fields) = export.fields {
+        params.insert("fields".to_owned(), fields);
+    }
+
+    if let Some(starttm) = export.starttm {
+        params.insert("starttm".to_owned(), format!("{}", starttm));
+    }
+
+    if let Some(endtm) = export.endtm {
+        params.insert("endtm".to_owned(), format!("{}", endtm));
+    }
+
+    private(account, "AddExport", &mut params).and_then(|r| {
+        serde_json::from_slice(&r)
+            .map_err(|e| format!("{:?}", e))
+            .and_then(
+                |result: KrakenResult<HashMap<String, String>>| if !result.error.is_empty() {
+                    Err(format!("{:?}", result.error))
+                } else {
+                    match result.result.and_then(|mut r| r.remove("id")) {
+                        Some(id) => Ok(id),
+                        None => Err(String::from("empty result")),
+                    }
+                },
+            )
+    })
+}
+
+///
+/// Get status of requested exports.
+///
+/// # Arguments
+///
+/// + `account` - The account credentials to use.
+/// + `report` - type of reports to inquire about.
+///
+pub fn export_status(
+    account: &Account,
+    report: ExportReport,
+) -> Result<Vec<ExportStatus>, String> {
+    let mut params = HashMap::new();
+
+    let report = match report {
+        ExportReport::Trades => "trades",
+        ExportReport::Ledgers => "ledgers",
+    };
+
+    params.insert("report".to_owned(), String::from(report));
+
+    private(account, "ExportStatus", &mut params).and_then(|r| {
+        serde_json::from_slice(&r)
+            .map_err(|e| format!("{:?}", e))
+            .and_then(
+                |result: KrakenResult<Vec<ExportStatus>>| if !result.error.is_empty() {
+                    Err(format!("{:?}", result.error))
+                } else {
+                    match result.result {
+                        Some(stuff) => Ok(stuff),
+                        None => Err(String::from("empty result")),
+                    }
+                },
+            )
+    })
+}
+
+///
+/// Retrieve a processed export.
+///
+/// # Note
+///
+/// The result is the raw zip archive containing the report.
+///
+pub fn retrieve_export(account: &Account, id: &str) -> Result<Vec<u8>, String> {
+    let mut params = HashMap::new();
+
+    params.insert("id".to_owned(), String::from(id));
+
+    private(account, "RetrieveExport", &mut params).and_then(|r| if r.starts_with(b"PK") {
+        Ok(r)
+    } else {
+        serde_json::from_slice(&r)
+            .map_err(|e| format!("{:?}", e))
+            .and_then(|result: KrakenResult<serde_json::Value>| {
+                Err(format!("{:?}", result.error))
+            })
+    })
+}
+
+///
+/// Cancel or delete an export.
+///
+/// # Note
+///
+/// Only reports which are queued or processing can be canceled,
+/// only processed reports can be deleted.
+///
+pub fn remove_export(
+    account: &Account,
+    id: &str,
+    kind: RemoveExportType,
+) -> Result<RemovedExport, String> {
+    let mut params = HashMap::new();
+
+    let kind = match kind {
+        RemoveExportType::Cancel => "cancel",
+        RemoveExportType::Delete => "delete",
+    };
+
+    params.insert("id".to_owned(), String::from(id));
+    params.insert("type".to_owned(), String::from(kind));
+
+    private(account, "RemoveExport", &mut params).and_then(|r| {
+        serde_json::from_slice(&r)
+            .map_err(|e| format!("{:?}", e))
+            .and_then(
+                |result: KrakenResult<RemovedExport>| if !result.error.is_empty() {
+                    Err(format!("{:?}", result.error))
+                } else {
+                    match result.result {
+                        Some(stuff) => Ok(stuff),
+                        None => Err(String::from("empty result")),
+                    }
+                },
+            )
+    })
+}
+
+#[derive(Deserialize)]
+struct ExportedTrade {
+    txid: String,
+    ordertxid: String,
+    #[serde(default)]
+    postxid: Option<String>,
+    pair: String,
+    time: String,
+    #[serde(rename = "type")]
+    kind: TradeType,
+    ordertype: OrderType,
+    price: String,
+    cost: String,
+    fee: String,
+    vol: String,
+    margin: String,
+    misc: String,
+}
+
+#[derive(Deserialize)]
+struct ExportedLedger {
+    txid: String,
+    refid: String,
+    time: String,
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    subtype: Option<String>,
+    aclass: String,
+    asset: String,
+    amount: String,
+    fee: String,
+    balance: String,
+}
+
+/// Days since 1970-01-01 of a proleptic gregorian date.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = (if year >= 0 { year } else { year - 399 }) / 400;
+    let yoe = year - era * 400;
+    let doy = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+
+    era * 146_097 + doe - 719_468
+}
+
+/// Parses the `YYYY-MM-DD hh:mm:ss.ffff` UTC times used in exports into unix timestamps.
+fn parse_export_time(value: &str) -> Result<f64, String> {
+    let invalid = || format!("invalid export time: {}", value);
+
+    let mut parts = value.trim().splitn(2, ' ');
+    let date = parts.next().ok_or_else(invalid)?;
+    let time = parts.next().ok_or_else(invalid)?;
+
+    let date = date.split('-')
+        .map(|x| x.parse::<i64>())
+        .collect::<Result<Vec<i64>, _>>()
+        .map_err(|_| invalid())?;
+    let time = time.split(':').collect::<Vec<&str>>();
+
+    if date.len() != 3 || time.len() != 3 {
+        return Err(invalid());
+    }
+
+    let hours = time[0].parse::<i64>().map_err(|_| invalid())?;
+    let minutes = time[1].parse::<i64>().map_err(|_| invalid())?;
+    let seconds = time[2].parse::<f64>().map_err(|_| invalid())?;
+    let days = days_from_civil(date[0], date[1], date[2]);
+
+    Ok((days * 86_400 + hours * 3_600 + minutes * 60) as f64 + seconds)
+}
+
+fn unzip_report(data: Vec<u8>) -> Result<Vec<u8>, String> {
+    let mut archive = zip::ZipArchive::new(Cursor::new(data)).map_err(
+        |e| format!("{:?}", e),
+    )?;
+
+    for i in 0..archive.len() {
+        let mut file = archive.by_index(i).map_err(|e| format!("{:?}", e))?;
+
+        if file.name().ends_with(".csv") {
+            let mut content = Vec::new();
+            file.read_to_end(&mut content).map_err(
+                |e| format!("{:?}", e),
+            )?;
+            return Ok(content);
+        }
+    }
+
+    Err(String::from("export contains no report"))
+}
+
+fn fetch_report(
+    account: &Account,
+    export: NewExport,
+    interval: Duration,
+    timeout: Duration,
+) -> Result<Vec<u8>, String> {
+    let report = export.report;
+    let id = add_export(account, export)?;
+    let started = Instant::now();
+
+    loop {
+        let state = export_status(account, report)?
+            .into_iter()
+            .find(|status| status.id == id)
+            .map(|status| status.status);
+
+        match state {
+            Some(ExportState::Processed) => break,
+            Some(ExportState::Queued) | Some(ExportState::Processing) => {}
+            Some(ExportState::Failed) => return Err(format!("export {} failed", id)),
+            None => return Err(format!("export {} was removed", id)),
+        }
+
+        if started.elapsed() >= timeout {
+            return Err(format!("export {} not processed after {:?}", id, timeout));
+        }
+
+        thread::sleep(interval);
+    }
+
+    retrieve_export(account, &id).and_then(unzip_report)
+}
+
+///
+/// Exports all trades in the given time range and parses them.
+///
+/// # Arguments
+///
+/// + `account` - The account credentials to use.
+/// + `description` - report description info.
+/// + `starttm` - unix timestamp of report start time (optional).
+/// + `endtm` - unix timestamp of report end time (optional).
+/// + `interval` - how long to wait between polling the export status.
+/// + `timeout` - how long to wait for the export to be processed at most.
+///
+/// # Note
+///
+/// The export is left on the server, use `remove_export` to delete it.
+///
+pub fn export_trades(
+    account: &Account,
+    description: &str,
+    starttm: Option<i64>,
+    endtm: Option<i64>,
+    interval: Duration,
+    timeout: Duration,
+) -> Result<HashMap<String, TradeInfo>, String> {
+    let export = NewExport {
+        report: ExportReport::Trades,
+        format: Some(ExportFormat::Csv),
+        description: String::from(description),
+        fields: None,
+        starttm,
+        endtm,
+    };
+
+    fetch_report(account, export, interval, timeout).and_then(|data| parse_exported_trades(&data))
+}
+
+/// Maps the rows of a trades export to `TradeInfo`, keyed by trade id.
+fn parse_exported_trades(data: &[u8]) -> Result<HashMap<String, TradeInfo>, String> {
+    let mut reader = csv::Reader::from_reader(data);
+    let mut trades = HashMap::new();
+
+    for record in reader.deserialize() {
+        let record: ExportedTrade = record.map_err(|e| format!("{:?}", e))?;
+
+        trades.insert(
+            record.txid,
+            TradeInfo {
+                ordertxid: record.ordertxid,
+                postxid: record.postxid,
+                pair: record.pair,
+                time: parse_export_time(&record.time)?,
+                kind: record.kind,
+                ordertype: record.ordertype,
+                price: record.price,
+                cost: record.cost,
+                fee: record.fee,
+                vol: record.vol,
+                margin: record.margin,
+                misc: record.misc,
+            },
+        );
+    }
+
+    Ok(trades)
+}
+
+///
+/// Exports all ledger entries in the given time range and parses them.
+///
+/// # Arguments
+///
+/// + `account` - The account credentials to use.
+/// + `description` - report description info.
+/// + `starttm` - unix timestamp of report start time (optional).
+/// + `endtm` - unix timestamp of report end time (optional).
+/// + `interval` - how long to wait between polling the export status.
+/// + `timeout` - how long to wait for the export to be processed at most.
+///
+/// # Note
+///
+/// The export is left on the server, use `remove_export` to delete it.
+///
+pub fn export_ledgers(
+    account: &Account,
+    description: &str,
+    starttm: Option<i64>,
+    endtm: Option<i64>,
+    interval: Duration,
+    timeout: Duration,
+) -> Result<HashMap<String, LedgerInfo>, String> {
+    let export = NewExport {
+        report: ExportReport::Ledgers,
+        format: Some(ExportFormat::Csv),
+        description: String::from(description),
+        fields: None,
+        starttm,
+        endtm,
+    };
+
+    fetch_report(account, export, interval, timeout).and_then(|data| parse_exported_ledgers(&data))
+}
+
+/// Maps the rows of a ledgers export to `LedgerInfo`, keyed by ledger id.
+fn parse_exported_ledgers(data: &[u8]) -> Result<HashMap<String, LedgerInfo>, String> {
+    let mut reader = csv::Reader::from_reader(data);
+    let mut ledgers = HashMap::new();
+
+    for record in reader.deserialize() {
+        let record: ExportedLedger = record.map_err(|e| format!("{:?}", e))?;
+
+        ledgers.insert(
+            record.txid,
+            LedgerInfo {
+                refid: record.refid,
+                time: parse_export_time(&record.time)?,
+                kind: record.kind,
+                subtype: record.subtype,
+                aclass: record.aclass,
+                asset: record.asset,
+                amount: record.amount,
+                fee: record.fee,
+                balance: record.balance,
+            },
+        );
+    }
+
+    Ok(ledgers)
+}
+
+///
+/// Get deposit methods.
+///
+/// # Arguments
+///
+/// + `account` - The account credentials to use.
+/// + `asset` - asset being deposited.
+///
+/// # Result
+///
+/// ```json
+/// {
+///     "error": [],
+///     "result": [
+///         {
+///             "method": "Bitcoin",
+///             "limit": false,
+///             "fee": "0.0000000000",
+///             "gen-address": true
+///         }
+///     ]
+/// }
+/// ```
+///
+pub fn deposit_methods(account: &Account, asset: &str) -> Result<Vec<DepositMethod>, String> {
+    let mut params = HashMap::new();
+
+    params.insert("asset".to_owned(), String::from(asset));
+
+    private(account, "DepositMethods", &mut params).and_then(|r| {
+        serde_json::from_slice(&r)
+            .map_err(|e| format!("{:?}", e))
+            .and_then(
+                |result: KrakenResult<Vec<DepositMethod>>| if !result.error.is_empty() {
+                    Err(format!("{:?}", result.error))
+                } else {
+                    match result.result {
+                        Some(stuff) => Ok(stuff),
+                        None => Err(String::from("empty result")),
+                    }
+                },
+            )
+    })
+}
+
+///
+/// Get deposit addresses.
+///
+/// # Arguments
+///
+/// + `account` - The account credentials to use.
+/// + `asset` - asset being deposited.
+/// + `method` - name of the deposit method.
+/// + `new` - whether or not to generate a new address (optional.  default = false).
+///
+/// # Result
+///
+/// ```json
+/// {
+///     "error": [],
+///     "result": [
+///         {
+///             "address": "2N9fRkx5JTWXWHmXzZtvhQsufvoYRMq9ExV",
+///             "expiretm": "0",
+///             "new": true
+///         }
+///     ]
+/// }
+/// ```
+///
+pub fn deposit_addresses(
+    account: &Account,
+    asset: &str,
+    method: &str,
+    new: Option<bool>,
+) -> Result<Vec<DepositAddress>, String> {
+    let mut params = HashMap::new();
+
+    params.insert("asset".to_owned(), String::from(asset));
+    params.insert("method".to_owned(), String::from(method));
+
+    if let Some(new) = new {
+        let val = if new { "true" } else { "false" };
+
+        params.insert("new".to_owned(), val.to_owned());
+    }
+
+    private(account, "DepositAddresses", &mut params).and_then(|r| {
+        serde_json::from_slice(&r)
+            .map_err(|e| format!("{:?}", e))
+            .and_then(
+                |result: KrakenResult<Vec<DepositAddress>>| if !result.error.is_empty() {
+                    Err(format!("{:?}", result.error))
+                } else {
+                    match result.result {
+                        Some(stuff) => Ok(stuff),
+                        None => Err(String::from("empty result")),
+                    }
+                },
+            )
+    })
+}
+
+///
+/// Get status of recent deposits.
+///
+/// # Arguments
+///
+/// + `account` - The account credentials to use.
+/// + `asset` - asset being deposited (optional).
+/// + `method` - name of the deposit method (optional).
+///
+/// # Result
+///
+/// ```json
+/// {
+///     "error": [],
+///     "result": [
+///         {
+///             "method": "Bitcoin",
+///             "aclass": "currency",
+///             "asset": "XXBT",
+///             "refid": "QSKZPKK-YESLV-FVUF7Q",
+///             "txid": "cb9e5f1fcbb3d0e4ab1d4e9f33b3a5a2b3b6f62a0c0b4c8b5b3a5a9a7b6e2d1c",
+///             "info": "2N9fRkx5JTWXWHmXzZtvhQsufvoYRMq9ExV",
+///             "amount": "0.78125000",
+///             "fee": "0.0000000000",
+///             "time": 1617014586,
+///             "status": "Success"
+///         }
+///     ]
+/// }
+/// ```
+///
+pub fn deposit_status(
+    account: &Account,
+    asset: Option<&str>,
+    method: Option<&str>,
+) -> Result<Vec<FundingStatus>, String> {
+    let mut params = HashMap::new();
+
+    if let Some(asset) = asset {
+        params.insert("asset".to_owned(), String::from(asset));
+    }
+
+    if let Some(method) = method {
+        params.insert("method".to_owned(), String::from(method));
+    }
+
+    private(account, "DepositStatus", &mut params).and_then(|r| {
+        serde_json::from_slice(&r)
+            .map_err(|e| format!("{:?}", e))
+            .and_then(
+                |result: KrakenResult<Vec<FundingStatus>>| if !result.error.is_empty() {
+                    Err(format!("{:?}", result.error))
+                } else {
+                    match result.result {
+                        Some(stuff) => Ok(stuff),
+                        None => Err(String::from("empty result")),
+                    }
+                },
+            )
+    })
+}
+
+///
+/// Waits until a deposit succeeded.
+///
+/// # Arguments
+///
+/// + `account` - The account credentials to use.
+/// + `asset` - asset being deposited.
+/// + `txid` - method transaction id or reference id of the deposit.
+/// + `interval` - how long to wait between polling the deposit status.
+/// + `timeout` - how long to wait for the deposit at most.
+///
+/// # Note
+///
+/// Returns an error as soon as the deposit failed.
+///
+pub fn wait_for_deposit(
+    account: &Account,
+    asset: &str,
+    txid: &str,
+    interval: Duration,
+    timeout: Duration,
+) -> Result<FundingStatus, String> {
+    let started = Instant::now();
 
-        transfer.perform()
-    };
+    loop {
+        let deposit = deposit_status(account, Some(asset), None)?
+            .into_iter()
+            .find(|status| status.txid == txid || status.refid == txid);
 
-    result.map_err(|e| format!("{:?}", e)).and_then(
-        |_x| Ok(dst),
-    )
+        if let Some(deposit) = deposit {
+            match deposit.status {
+                FundingState::Success => return Ok(deposit),
+                FundingState::Failure => return Err(format!("deposit {} failed", txid)),
+                _ => {}
+            }
+        }
+
+        if started.elapsed() >= timeout {
+            return Err(format!("deposit {} not successful after {:?}", txid, timeout));
+        }
+
+        thread::sleep(interval);
+    }
 }
 
 ///
-/// Server's time.
+/// Get withdrawal information.
 ///
-/// # Note
+/// # Arguments
 ///
-/// This is to aid in approximating the skew time between the server and client.
+/// + `account` - The account credentials to use.
+/// + `asset` - asset being withdrawn.
+/// + `key` - withdrawal key name, as set up on the account.
+/// + `amount` - amount to withdraw.
 ///
 /// # Result
 ///
 /// ```json
 /// {
-///     "error":[],
+///     "error": [],
 ///     "result": {
-///         "unixtime": 1507489778,"
-///         rfc1123":"Sun,  8 Oct 17 19:09:38 +0000"
+///         "method": "Bitcoin",
+///         "limit": "332.00956139",
+///         "amount": "0.72485000",
+///         "fee": "0.00015000"
 ///     }
 /// }
 /// ```
 ///
-pub fn time() -> Result<Time, String> {
-    public("Time").and_then(|dst| {
-        serde_json::from_slice(&dst)
+pub fn withdraw_info(
+    account: &Account,
+    asset: &str,
+    key: &str,
+    amount: &str,
+) -> Result<WithdrawInfo, String> {
+    let mut params = HashMap::new();
+
+    params.insert("asset".to_owned(), String::from(asset));
+    params.insert("key".to_owned(), String::from(key));
+    params.insert("amount".to_owned(), String::from(amount));
+
+    private(account, "WithdrawInfo", &mut params).and_then(|r| {
+        serde_json::from_slice(&r)
             .map_err(|e| format!("{:?}", e))
-            .and_then(|result: KrakenResult<Time>| if result.error.len() > 0 {
+            .and_then(|result: KrakenResult<WithdrawInfo>| if !result.error.is_empty() {
                 Err(format!("{:?}", result.error))
             } else {
-                Ok(result.result.unwrap())
+                match result.result {
+                    Some(stuff) => Ok(stuff),
+                    None => Err(String::from("empty result")),
+                }
             })
     })
 }
 
 ///
-/// Returns an array of asset names and their info.
+/// Checks a withdrawal against the policy and fetches its fee and limit preview.
 ///
-pub fn assets() -> Result<HashMap<String, Asset>, String> {
-    public("Assets").and_then(|data| {
-        serde_json::from_slice(&data)
-            .map_err(|e| format!("{:?}", e))
-            .and_then(
-                |result: KrakenResult<HashMap<String, Asset>>| if result.error.len() > 0 {
-                    Err(format!("{:?}", result.error))
-                } else {
-                    Ok(result.result.unwrap())
-                },
-            )
-    })
+/// # Arguments
+///
+/// + `account` - The account credentials to use.
+/// + `policy` - allowlist the withdrawal has to pass.
+/// + `asset` - asset being withdrawn.
+/// + `key` - withdrawal key name, as set up on the account.
+/// + `amount` - amount to withdraw.
+///
+/// # Note
+///
+/// Fails without contacting kraken if the policy rejects the withdrawal,
+/// and fails if the amount exceeds kraken's current withdrawal limit.
+///
+pub fn withdraw_preview(
+    account: &Account,
+    policy: &WithdrawPolicy,
+    asset: &str,
+    key: &str,
+    amount: &str,
+) -> Result<WithdrawPreview, String> {
+    let amount = amount.parse::<Decimal>()?;
+
+    policy.check(asset, key, &amount)?;
+
+    let info = withdraw_info(account, asset, key, &amount.to_string())?;
 
+    if amount > info.limit.parse::<Decimal>()? {
+        return Err(format!(
+            "withdrawal of {} {} exceeds limit of {}",
+            amount,
+            asset,
+            info.limit
+        ));
+    }
+
+    Ok(WithdrawPreview {
+        asset: String::from(asset),
+        key: String::from(key),
+        amount,
+        info,
+        created: Instant::now(),
+    })
 }
 
 ///
-/// Returns an array of pair names and theif info.
+/// Withdraw funds.
+///
+/// # Arguments
+///
+/// + `account` - The account credentials to use.
+/// + `preview` - the previewed withdrawal to execute.
 ///
 /// # Note
 ///
-/// If an asset pair is on a maker/taker fee schedule,
-/// the taker side is given in "fees" and maker side in "fees_maker".
-/// For pairs not on maker/taker, they will only be given in "fees".
+/// The previewed fee is passed as maximum fee, so the withdrawal fails
+/// if kraken would charge more than shown in the preview.
+/// Fails without contacting kraken if the preview expired.
 ///
 /// # Result
 ///
@@ -477,58 +5185,37 @@ pub fn assets() -> Result<HashMap<String, Asset>, String> {
 /// {
 ///     "error": [],
 ///     "result": {
-///         "XETHZEUR": {
-///             "altname": "ETHEUR",
-///             "aclass_base": "currency",
-///             "base": "XETH",
-///             "aclass_quote": "currency",
-///             "quote": "ZEUR",
-///             "lot": "unit",
-///             "pair_decimals": 5,
-///             "lot_decimals": 8,
-///             "lot_multiplier": 1,
-///             "leverage_buy": [2, 3],
-///             "leverage_sell": [2, 3],
-///             "fees": [
-///                 [0, 0.26],
-///                 [50000, 0.24],
-///                 [100000, 0.22],
-///                 [250000, 0.2],
-///                 [500000, 0.18],
-///                 [1000000, 0.16],
-///                 [2500000, 0.14],
-///                 [5000000, 0.12],
-///                 [10000000, 0.1]
-///             ],
-///             "fees_maker": [
-///                 [0, 0.16],
-///                 [50000, 0.14],
-///                 [100000, 0.12],
-///                 [250000, 0.1],
-///                 [500000, 0.08],
-///                 [1000000, 0.06],
-///                 [2500000, 0.04],
-///                 [5000000, 0.02],
-///                 [10000000, 0]
-///             ],
-///             "fee_volume_currency": "ZUSD",
-///             "margin_call": 80,
-///             "margin_stop": 40
-///         }
+///         "refid": "AGBSO6T-UFMTTQ-I7KGS6"
 ///     }
 /// }
 /// ```
 ///
-pub fn asset_pairs() -> Result<HashMap<String, AssetPair>, String> {
-    public("AssetPairs").and_then(|data| {
-        serde_json::from_slice(&data)
-            .map_err(|e| format!("{:?}\n{:?}", e, data))
+pub fn withdraw(account: &Account, preview: WithdrawPreview) -> Result<String, String> {
+    if preview.is_expired() {
+        return Err(format!(
+            "withdraw preview of {} {} expired after {:?}",
+            preview.amount,
+            preview.asset,
+            WITHDRAW_PREVIEW_MAX_AGE
+        ));
+    }
+
+    let mut params = HashMap::new();
+
+    params.insert("asset".to_owned(), preview.asset);
+    params.insert("key".to_owned(), preview.key);
+    params.insert("amount".to_owned(), preview.amount.to_string());
+    params.insert("max_fee".to_owned(), preview.info.fee);
+
+    private(account, "Withdraw", &mut params).and_then(|r| {
+        serde_json::from_slice(&r)
+            .map_err(|e| format!("{:?}", e))
             .and_then(
-                |result: KrakenResult<HashMap<String, AssetPair>>| if result.error.len() > 0 {
+                |result: KrakenResult<HashMap<String, String>>| if !result.error.is_empty() {
                     Err(format!("{:?}", result.error))
                 } else {
-                    match result.result {
-                        Some(stuff) => Ok(stuff),
+                    match result.result.and_then(|mut r| r.remove("refid")) {
+                        Some(refid) => Ok(refid),
                         None => Err(String::from("empty result")),
                     }
                 },
@@ -537,48 +5224,34 @@ pub fn asset_pairs() -> Result<HashMap<String, AssetPair>, String> {
 }
 
 ///
-/// Returns an array of pair names and their ticker info.
+/// Get status of recent withdrawals.
 ///
 /// # Arguments
 ///
-/// + `pairs` - comma delimited list of asset pairs to get info on
-///
-/// # Result
-///
-/// + `a` ask array(<price>, <whole lot volume>, <lot volume>)
-/// + `b` bid array(<price>, <whole lot volume>, <lot volume>)
-/// + `c` last trade closed array(<price>, <lot volume>)
-/// + `v` volume array(<today>, <last 24 hours>)
-/// + `p` volume weighted average price array(<today>, <last 24 hours>)
-/// + `t` number of trades array(<today>, <last 24 hours>)
-/// + `l` low array(<today>, <last 24 hours>)
-/// + `h` high array(<today>, <last 24 hours>)
-/// + `o` today's opening price
+/// + `account` - The account credentials to use.
+/// + `asset` - asset being withdrawn (optional).
+/// + `method` - name of the withdrawal method (optional).
 ///
-/// ```json
-/// {
-///     "error": [],
-///     "result": {
-///         "XETHZEUR": {
-///             "a": ["10.27949", "9", "9.000"],
-///             "b": ["10.20800", "83", "83.000"],
-///             "c": ["10.27949", "2.91843272"],
-///             "v": ["32132.14651679", "155901.33932839"],
-///             "p": ["10.20578", "10.18520"],
-///             "t": [718, 4203],
-///             "l": ["10.11669", "9.87000"],
-///             "h": ["10.29992", "10.69000"],
-///             "o": "10.24950"
-///         }
-///     }
-/// }
-/// ```
-pub fn ticker(pairs: &str) -> Result<HashMap<String, Tick>, String> {
-    public(&format!("Ticker?pair={}", pairs)).and_then(|data| {
-        serde_json::from_slice(&data)
+pub fn withdraw_status(
+    account: &Account,
+    asset: Option<&str>,
+    method: Option<&str>,
+) -> Result<Vec<FundingStatus>, String> {
+    let mut params = HashMap::new();
+
+    if let Some(asset) = asset {
+        params.insert("asset".to_owned(), String::from(asset));
+    }
+
+    if let Some(method) = method {
+        params.insert("method".to_owned(), String::from(method));
+    }
+
+    private(account, "WithdrawStatus", &mut params).and_then(|r| {
+        serde_json::from_slice(&r)
             .map_err(|e| format!("{:?}", e))
             .and_then(
-                |result: KrakenResult<HashMap<String, Tick>>| if result.error.len() > 0 {
+                |result: KrakenResult<Vec<FundingStatus>>| if !result.error.is_empty() {
                     Err(format!("{:?}", result.error))
                 } else {
                     match result.result {
@@ -590,365 +5263,270 @@ pub fn ticker(pairs: &str) -> Result<HashMap<String, Tick>, String> {
     })
 }
 
+///
+/// Request withdrawal cancelation.
 ///
 /// # Arguments
 ///
-/// + `pair` - asset pair to get OHLC data for
-/// + `interval` - time frame interval in minutes (optional):
-///     1 (default), 5, 15, 30, 60, 240, 1440, 10080, 21600
-/// + `since` - return committed OHLC data since given id (optional.  exclusive)
+/// + `account` - The account credentials to use.
+/// + `asset` - asset being withdrawn.
+/// + `refid` - withdrawal reference id.
 ///
 /// # Note
 ///
-/// the last entry in the OHLC array is for the current, not-yet-committed frame and will always be present,
-/// regardless of the value of "since".
-///
-/// [time], [open], [high], [low], [close], [vwap], [volume], [count]
-///
-/// ```json
-/// {
-///     "error": [],
-///     "result": {
-///         "XETHZEUR": [
-///           [1506303540,"283.62","283.65","283.62","283.65","283.64","7.10086462",4],
-///           [1506303600,"283.64","284.09","283.64","284.09","283.97","8.14638417",8],
-///           ...
-///         ]
-///     }
-/// }
-/// ```
+/// Cancelation cannot be guaranteed. This will put in a cancelation request.
+/// Depending upon how far along the withdrawal process is, it may not be possible to cancel the withdrawal.
 ///
-pub fn ohlc(pair: &str, interval: Option<u32>, since: Option<&str>) -> Result<OHLC, String> {
-    let mut url = format!("OHLC?pair={}", pair);
-
-    if let Some(interval) = interval {
-        url = format!("{}&interval={}", url, interval);
-    }
+pub fn withdraw_cancel(account: &Account, asset: &str, refid: &str) -> Result<bool, String> {
+    let mut params = HashMap::new();
 
-    if let Some(since) = since {
-        url = format!("{}&since={}", url, since);
-    }
+    params.insert("asset".to_owned(), String::from(asset));
+    params.insert("refid".to_owned(), String::from(refid));
 
-    public(&url).and_then(|data| {
-        serde_json::from_slice(&data)
+    private(account, "WithdrawCancel", &mut params).and_then(|r| {
+        serde_json::from_slice(&r)
             .map_err(|e| format!("{:?}", e))
-            .and_then(|result: KrakenResult<OHLC>| if result.error.len() > 0 {
+            .and_then(|result: KrakenResult<bool>| if !result.error.is_empty() {
                 Err(format!("{:?}", result.error))
             } else {
-                Ok(result.result.unwrap())
+                match result.result {
+                    Some(stuff) => Ok(stuff),
+                    None => Err(String::from("empty result")),
+                }
             })
     })
 }
 
 ///
-/// Get the order depth.
+/// Transfer funds between wallets of the same account.
 ///
 /// # Arguments
 ///
-/// + `pair` - asset pair to get market depth for
-/// + `count` - maximum number of asks/bids (optional)
+/// + `account` - The account credentials to use.
+/// + `asset` - asset being transfered.
+/// + `from` - source wallet, e.g. "Spot Wallet".
+/// + `to` - destination wallet, e.g. "Futures Wallet".
+/// + `amount` - amount to transfer.
+///
+/// # Result
 ///
 /// ```json
 /// {
-///     "error":[],
+///     "error": [],
 ///     "result": {
-///         "XETHZEUR": {
-///             "asks":[["247.00000","45.273",1506366345], ...],
-///             "bids":[["247.00000","45.273",1506366345], ...]
-///         }
+///         "refid": "BOG5AE5-KSCNR4-VPNPEV"
 ///     }
 /// }
 /// ```
 ///
-pub fn order_book(pair: &str, count: Option<u32>) -> Result<Depth, String> {
-    let mut url = format!("Depth?pair={}", pair);
+pub fn wallet_transfer(
+    account: &Account,
+    asset: &str,
+    from: &str,
+    to: &str,
+    amount: &str,
+) -> Result<String, String> {
+    let mut params = HashMap::new();
 
-    if let Some(ct) = count {
-        url = format!("{}&count={}", url, ct);
-    }
+    params.insert("asset".to_owned(), String::from(asset));
+    params.insert("from".to_owned(), String::from(from));
+    params.insert("to".to_owned(), String::from(to));
+    params.insert("amount".to_owned(), String::from(amount));
 
-    public(&url).and_then(|data| {
-        serde_json::from_slice(&data)
+    private(account, "WalletTransfer", &mut params).and_then(|r| {
+        serde_json::from_slice(&r)
             .map_err(|e| format!("{:?}", e))
-            .and_then(|result: KrakenResult<Depth>| if result.error.len() > 0 {
-                Err(format!("{:?}", result.error))
-            } else {
-                Ok(result.result.unwrap())
-            })
+            .and_then(
+                |result: KrakenResult<HashMap<String, String>>| if !result.error.is_empty() {
+                    Err(format!("{:?}", result.error))
+                } else {
+                    match result.result.and_then(|mut r| r.remove("refid")) {
+                        Some(refid) => Ok(refid),
+                        None => Err(String::from("empty result")),
+                    }
+                },
+            )
     })
 }
 
 ///
-/// Get recent trades.
-///
-/// # Arguments
-///
-/// + `pair` - asset pair to get trade data for
-/// + `since` - return trade data since given id (optional.  exclusive)
-///
-/// ```json
-/// {
-///     "error":[],
-///     "result": {
-///         "XETHZEUR": [
-///             ["246.20000","0.86500000",1506362463.76,"b","l",""],
-///             ...
-///         ],
-///         "last":"1506367082091136113"
-///     }
-/// }
-/// ```
-///
-pub fn recent_trades(
-    pair: &str,
-    since: Option<&str>,
-) -> Result<HashMap<String, serde_json::Value>, String> {
-    let mut url = format!("Trades?pair={}", pair);
+/// Create a trading subaccount.
+///
+/// # Arguments
+///
+/// + `account` - The master account credentials to use.
+/// + `username` - username for the subaccount.
+/// + `email` - email address for the subaccount.
+///
+pub fn create_subaccount(account: &Account, username: &str, email: &str) -> Result<bool, String> {
+    let mut params = HashMap::new();
 
-    if let Some(ct) = since {
-        url = format!("{}&since={}", url, ct);
-    }
+    params.insert("username".to_owned(), String::from(username));
+    params.insert("email".to_owned(), String::from(email));
 
-    public(&url).and_then(|data| {
-        serde_json::from_slice(&data)
+    private(account, "CreateSubaccount", &mut params).and_then(|r| {
+        serde_json::from_slice(&r)
             .map_err(|e| format!("{:?}", e))
-            .and_then(|result: KrakenResult<
-                HashMap<
-                    String,
-                    serde_json::Value,
-                >,
-            >| if result.error.len() > 0 {
+            .and_then(|result: KrakenResult<bool>| if !result.error.is_empty() {
                 Err(format!("{:?}", result.error))
             } else {
-                Ok(result.result.unwrap())
+                match result.result {
+                    Some(stuff) => Ok(stuff),
+                    None => Err(String::from("empty result")),
+                }
             })
     })
 }
 
 ///
-/// Get recent spread data.
+/// Transfer funds between master account and subaccounts.
 ///
 /// # Arguments
 ///
-/// + `pair` - asset pair to get spread data for.
-/// + `since` - return spread data since given id (optional.  inclusive).
+/// + `account` - The master account credentials to use.
+/// + `asset` - asset being transfered.
+/// + `amount` - amount to transfer.
+/// + `from` - IIBAN of the source account.
+/// + `to` - IIBAN of the destination account.
+///
+/// # Result
 ///
 /// ```json
 /// {
-///     "error":[],
+///     "error": [],
 ///     "result": {
-///         "XETHZEUR": [
-///             [1506368083,"247.47000","248.52000"],
-///             ...
-///         ],
-///         "last": 1506370285
+///         "transfer_id": "TOH3AS2-LPCWR8-JDQGEU",
+///         "status": "complete"
 ///     }
 /// }
 /// ```
 ///
-pub fn recent_spread(
-    pair: &str,
-    since: Option<u32>,
-) -> Result<HashMap<String, serde_json::Value>, String> {
-    let mut url = format!("Spread?pair={}", pair);
+pub fn account_transfer(
+    account: &Account,
+    asset: &str,
+    amount: &str,
+    from: &str,
+    to: &str,
+) -> Result<AccountTransfer, String> {
+    let mut params = HashMap::new();
 
-    if let Some(ct) = since {
-        url = format!("{}&since={}", url, ct);
-    }
+    params.insert("asset".to_owned(), String::from(asset));
+    params.insert("amount".to_owned(), String::from(amount));
+    params.insert("from".to_owned(), String::from(from));
+    params.insert("to".to_owned(), String::from(to));
 
-    public(&url).and_then(|data| {
-        serde_json::from_slice(&data)
+    private(account, "AccountTransfer", &mut params).and_then(|r| {
+        serde_json::from_slice(&r)
             .map_err(|e| format!("{:?}", e))
-            .and_then(|result: KrakenResult<
-                HashMap<
-                    String,
-                    serde_json::Value,
-                >,
-            >| if result.error.len() > 0 {
+            .and_then(|result: KrakenResult<AccountTransfer>| if !result.error.is_empty() {
                 Err(format!("{:?}", result.error))
             } else {
-                Ok(result.result.unwrap())
+                match result.result {
+                    Some(stuff) => Ok(stuff),
+                    None => Err(String::from("empty result")),
+                }
             })
     })
 }
 
-// ----
-
-fn private(
-    account: &Account,
-    method: &str,
-    params: &mut HashMap<String, String>,
-) -> Result<Vec<u8>, String> {
-    let path = format!("/0/private/{}", method);
-    let url = format!("https://api.kraken.com{}", path);
-    let timestamp = ::std::time::UNIX_EPOCH.elapsed().unwrap();
-    let nonce = format!("{}{:09}", timestamp.as_secs(), timestamp.subsec_nanos());
-
-    let mut dst = Vec::new();
-    let mut easy = Easy::new();
-
-    easy.url(&url).unwrap();
-    easy.post(true).unwrap();
-
-    params.insert("nonce".to_owned(), nonce.clone());
-
-    let mut body = params.iter().fold(
-        String::new(),
-        |data, item| data + item.0 + "=" + item.1 + "&",
-    );
-    body.pop();
-
-    let mut body_bytes = body.as_bytes();
-    let secret = base64::decode(&account.secret).unwrap();
-    let mut hmac = Hmac::new(Sha512::new(), &secret);
-    let mut body_hasher = Sha256::new();
-
-    body_hasher.input(nonce.as_bytes());
-    body_hasher.input(body_bytes);
-
-    hmac.input(path.as_bytes());
-    let mut out: [u8; 32] = [0; 32];
-    body_hasher.result(&mut out);
-    hmac.input(&out);
-
-    let sign = base64::encode(hmac.result().code());
-
-    easy.post_field_size(body_bytes.len() as u64).unwrap();
-
-    let mut list = List::new();
-
-    list.append("Content-Type: application/x-www-form-urlencoded")
-        .unwrap();
-    list.append(&format!("API-Key: {}", account.key)).unwrap();
-    list.append(&format!("API-Sign: {}", sign)).unwrap();
-
-    easy.http_headers(list).unwrap();
-
-    let result = {
-        let mut transfer = easy.transfer();
-
-        transfer
-            .read_function(|buf| Ok(body_bytes.read(buf).unwrap_or(0)))
-            .unwrap();
-
-        transfer
-            .write_function(|data| {
-                dst.extend_from_slice(data);
-                Ok(data.len())
-            })
-            .unwrap();
-
-        transfer.perform()
-    };
-
-    result.map_err(|e| format!("{:?}", e)).and_then(
-        |_x| Ok(dst),
-    )
-}
-
 ///
-/// Returns an array of asset names and balance amount.
+/// List earn strategies.
 ///
 /// # Arguments
 ///
 /// + `account` - The account credentials to use.
+/// + `asset` - restrict results to strategies of the given asset (optional).
+/// + `cursor` - cursor of the page to get (optional).
 ///
-pub fn balance(account: &Account) -> Result<HashMap<String, String>, String> {
-    let mut params = HashMap::new();
-    private(account, "Balance", &mut params).and_then(|r| {
-        serde_json::from_slice(&r)
-            .map_err(|e| format!("{:?}", e))
-            .and_then(
-                |result: KrakenResult<HashMap<String, String>>| if result.error.len() > 0 {
-                    Err(format!("{:?}", result.error))
-                } else {
-                    Ok(result.result.unwrap())
-                },
-            )
-    })
-}
-
-///
-/// Get trade balance.
-///
-/// # Arguments
+/// # Result
 ///
-/// + `account` - The account credentials to use.
-/// + `asset` - class (optional): currency (default).
-/// + `asset` = base asset used to determine balance (default = ZUSD).
+/// ```json
+/// {
+///     "error": [],
+///     "result": {
+///         "items": [
+///             {
+///                 "id": "ESRFUO3-Q62XD-WIOIL7",
+///                 "asset": "DOT",
+///                 "lock_type": {
+///                     "type": "instant",
+///                     "payout_frequency": 604800
+///                 },
+///                 "apr_estimate": {
+///                     "low": "8.0000",
+///                     "high": "12.0000"
+///                 },
+///                 "user_min_allocation": "0.01",
+///                 "allocation_fee": "0.0000",
+///                 "deallocation_fee": "0.0000",
+///                 "auto_compound": {
+///                     "type": "enabled"
+///                 },
+///                 "yield_source": {
+///                     "type": "staking"
+///                 },
+///                 "can_allocate": true,
+///                 "can_deallocate": true,
+///                 "allocation_restriction_info": []
+///             }
+///         ],
+///         "next_cursor": "2"
+///     }
+/// }
+/// ```
 ///
-pub fn trade_balance(
+pub fn earn_strategies(
     account: &Account,
-    aclass: Option<&str>,
     asset: Option<&str>,
-) -> Result<TradeBalance, String> {
+    cursor: Option<&str>,
+) -> Result<EarnStrategies, String> {
     let mut params = HashMap::new();
 
-    if let Some(ct) = aclass {
-        params.insert("aclass".to_owned(), String::from(ct));
+    if let Some(asset) = asset {
+        params.insert("asset".to_owned(), String::from(asset));
     }
 
-    if let Some(ct) = asset {
-        params.insert("asset".to_owned(), String::from(ct));
+    if let Some(cursor) = cursor {
+        params.insert("cursor".to_owned(), String::from(cursor));
     }
 
-    private(account, "TradeBalance", &mut params).and_then(|r| {
+    private(account, "Earn/Strategies", &mut params).and_then(|r| {
         serde_json::from_slice(&r)
             .map_err(|e| format!("{:?}", e))
-            .and_then(|result: KrakenResult<TradeBalance>| if result.error.len() >
-                0
-            {
+            .and_then(|result: KrakenResult<EarnStrategies>| if !result.error.is_empty() {
                 Err(format!("{:?}", result.error))
             } else {
-                Ok(result.result.unwrap())
+                match result.result {
+                    Some(stuff) => Ok(stuff),
+                    None => Err(String::from("empty result")),
+                }
             })
     })
 }
 
 ///
-/// Get open orders.
+/// Allocate funds to an earn strategy.
 ///
 /// # Arguments
 ///
-/// + `trades` - whether or not to include trades in output (optional.  default = false).
-/// + `userref` - restrict results to given user reference id (optional).
+/// + `account` - The account credentials to use.
+/// + `strategy_id` - id of the strategy.
+/// + `amount` - amount to allocate.
 ///
 /// # Note
 ///
-/// Unless otherwise stated, costs, fees, prices, and volumes are in the asset pair's scale,
-/// not the currency's scale. For example, if the asset pair uses a lot size that has a scale of 8,
-/// the volume will use a scale of 8, even if the currency it represents only has a scale of 2.
-/// Similarly, if the asset pair's pricing scale is 5, the scale will remain as 5,
-/// even if the underlying currency has a scale of 8.
+/// The request is processed asynchronously, use `earn_allocate_status` to check whether it is still pending.
 ///
-pub fn open_orders(
-    account: &Account,
-    trades: Option<bool>,
-    userref: Option<&str>,
-) -> Result<OpenOrders, String> {
+pub fn earn_allocate(account: &Account, strategy_id: &str, amount: &str) -> Result<bool, String> {
     let mut params = HashMap::new();
 
-    if let Some(ct) = trades {
-        params.insert(
-            "trades".to_owned(),
-            if ct {
-                String::from("true")
-            } else {
-                String::from("false")
-            },
-        );
-    }
-
-    if let Some(ct) = userref {
-        params.insert("userref".to_owned(), String::from(ct));
-    }
+    params.insert("strategy_id".to_owned(), String::from(strategy_id));
+    params.insert("amount".to_owned(), String::from(amount));
 
-    private(account, "OpenOrders", &mut params).and_then(|r| {
+    private(account, "Earn/Allocate", &mut params).and_then(|r| {
         serde_json::from_slice(&r)
             .map_err(|e| format!("{:?}", e))
-            .and_then(|result: KrakenResult<OpenOrders>| if result.error.len() >
-                0
-            {
+            .and_then(|result: KrakenResult<bool>| if !result.error.is_empty() {
                 Err(format!("{:?}", result.error))
             } else {
                 match result.result {
@@ -960,238 +5538,727 @@ pub fn open_orders(
 }
 
 ///
-/// Get closed orders.
+/// Whether the last allocate request for a strategy is still pending.
+///
+pub fn earn_allocate_status(account: &Account, strategy_id: &str) -> Result<bool, String> {
+    let mut params = HashMap::new();
+
+    params.insert("strategy_id".to_owned(), String::from(strategy_id));
+
+    private(account, "Earn/AllocateStatus", &mut params).and_then(|r| {
+        serde_json::from_slice(&r)
+            .map_err(|e| format!("{:?}", e))
+            .and_then(
+                |result: KrakenResult<HashMap<String, bool>>| if !result.error.is_empty() {
+                    Err(format!("{:?}", result.error))
+                } else {
+                    match result.result.and_then(|mut r| r.remove("pending")) {
+                        Some(pending) => Ok(pending),
+                        None => Err(String::from("empty result")),
+                    }
+                },
+            )
+    })
+}
+
+///
+/// Deallocate funds from an earn strategy.
 ///
 /// # Arguments
 ///
-/// + `trades` - whether or not to include trades in output (optional.  default = false).
-/// + `userref` - restrict results to given user reference id (optional).
-/// + `start` - starting unix timestamp or order tx id of results (optional.  exclusive).
-/// + `end` - ending unix timestamp or order tx id of results (optional.  inclusive).
-/// + `ofs` - result offset.
-/// + `closetime` = which time to use (optional)
-///     open
-///     close
-///     both (default)
+/// + `account` - The account credentials to use.
+/// + `strategy_id` - id of the strategy.
+/// + `amount` - amount to deallocate.
 ///
 /// # Note
 ///
-/// Times given by order tx ids are more accurate than unix timestamps.
-/// If an order tx id is given for the time, the order's open time is used.
+/// The request is processed asynchronously, use `earn_deallocate_status` to check whether it is still pending.
 ///
-pub fn closed_orders(
-    account: &Account,
-    cfg: Option<ClosedOrdersConfig>,
-) -> Result<ClosedOrders, String> {
+pub fn earn_deallocate(account: &Account, strategy_id: &str, amount: &str) -> Result<bool, String> {
     let mut params = HashMap::new();
 
-    if let Some(cfg) = cfg {
+    params.insert("strategy_id".to_owned(), String::from(strategy_id));
+    params.insert("amount".to_owned(), String::from(amount));
 
-        if let Some(trades) = cfg.trades {
-            if trades {
-                params.insert("trades".to_owned(), "true".to_owned());
+    private(account, "Earn/Deallocate", &mut params).and_then(|r| {
+        serde_json::from_slice(&r)
+            .map_err(|e| format!("{:?}", e))
+            .and_then(|result: KrakenResult<bool>| if !result.error.is_empty() {
+                Err(format!("{:?}", result.error))
             } else {
-                params.insert("trades".to_owned(), "false".to_owned());
-            }
-        }
-
-        if let Some(userref) = cfg.userref {
-            params.insert("userref".to_owned(), userref);
-        }
-
-        if let Some(start) = cfg.start {
-            params.insert("start".to_owned(), format!("{}", start));
-        }
-
-        if let Some(end) = cfg.end {
-            params.insert("end".to_owned(), format!("{}", end));
-        }
-
-        if let Some(ofs) = cfg.ofs {
-            params.insert("ofs".to_owned(), format!("{}", ofs));
-        }
-
-        if let Some(closetime) = cfg.closetime {
-            let value = match closetime {
-                ClosedOrdersConfigCloseTime::Open => "open",
-                ClosedOrdersConfigCloseTime::Close => "close",
-                ClosedOrdersConfigCloseTime::Both => "both",
-            };
+                match result.result {
+                    Some(stuff) => Ok(stuff),
+                    None => Err(String::from("empty result")),
+                }
+            })
+    })
+}
 
-            params.insert("closetime".to_owned(), value.to_owned());
-        }
-    }
+///
+/// Whether the last deallocate request for a strategy is still pending.
+///
+pub fn earn_deallocate_status(account: &Account, strategy_id: &str) -> Result<bool, String> {
+    let mut params = HashMap::new();
 
-    private(account, "ClosedOrders", &mut params).and_then(|r| {
+    params.insert("strategy_id".to_owned(), String::from(strategy_id));
+
+    private(account, "Earn/DeallocateStatus", &mut params).and_then(|r| {
         serde_json::from_slice(&r)
             .map_err(|e| format!("{:?}", e))
-            .and_then(|result: KrakenResult<ClosedOrders>| if result.error.len() >
-                0
-            {
-                Err(format!("{:?}", result.error))
-            } else {
-                Ok(result.result.unwrap())
-            })
+            .and_then(
+                |result: KrakenResult<HashMap<String, bool>>| if !result.error.is_empty() {
+                    Err(format!("{:?}", result.error))
+                } else {
+                    match result.result.and_then(|mut r| r.remove("pending")) {
+                        Some(pending) => Ok(pending),
+                        None => Err(String::from("empty result")),
+                    }
+                },
+            )
     })
 }
 
 ///
-/// Query orders info.
+/// List allocations to earn strategies.
 ///
 /// # Arguments
 ///
-/// + `trades` - whether or not to include trades in output (optional.  default = false).
-/// + `userref` - restrict results to given user reference id (optional).
-/// + `txid` - comma delimited list of transaction ids to query info about (20 maximum).
+/// + `account` - The account credentials to use.
+/// + `converted_asset` - asset to convert the amounts to (optional.  default = USD).
+/// + `hide_zero_allocations` - whether or not to omit strategies without allocation (optional).
 ///
-pub fn query_orders(
+/// # Result
+///
+/// ```json
+/// {
+///     "error": [],
+///     "result": {
+///         "converted_asset": "USD",
+///         "total_allocated": "49.2398",
+///         "total_rewarded": "0.0675",
+///         "next_cursor": "2",
+///         "items": [
+///             {
+///                 "strategy_id": "ESDQCOL-WTZEU-NU55QF",
+///                 "native_asset": "ETH",
+///                 "amount_allocated": {
+///                     "bonding": {
+///                         "native": "0.0210000000",
+///                         "converted": "39.0645",
+///                         "allocation_count": 2
+///                     },
+///                     "total": {
+///                         "native": "0.0210000000",
+///                         "converted": "39.0645"
+///                     }
+///                 },
+///                 "total_rewarded": {
+///                     "native": "0",
+///                     "converted": "0.0000"
+///                 }
+///             }
+///         ]
+///     }
+/// }
+/// ```
+///
+pub fn earn_allocations(
     account: &Account,
-    trades: Option<bool>,
-    userref: Option<String>,
-    txids: Option<String>,
-) -> Result<OpenOrders, String> {
+    converted_asset: Option<&str>,
+    hide_zero_allocations: Option<bool>,
+) -> Result<EarnAllocations, String> {
     let mut params = HashMap::new();
 
-    if let Some(trades) = trades {
-        let val = if trades { "true" } else { "false" };
-
-        params.insert("trades".to_owned(), val.to_owned());
+    if let Some(asset) = converted_asset {
+        params.insert("converted_asset".to_owned(), String::from(asset));
     }
 
-    if let Some(userref) = userref {
-        params.insert("userref".to_owned(), userref);
-    }
+    if let Some(hide) = hide_zero_allocations {
+        let val = if hide { "true" } else { "false" };
 
-    if let Some(txids) = txids {
-        params.insert("txids".to_owned(), txids);
+        params.insert("hide_zero_allocations".to_owned(), val.to_owned());
     }
 
-    private(account, "QueryOrders", &mut params).and_then(|r| {
+    private(account, "Earn/Allocations", &mut params).and_then(|r| {
         serde_json::from_slice(&r)
             .map_err(|e| format!("{:?}", e))
-            .and_then(|result: KrakenResult<OpenOrders>| if result.error.len() >
-                0
-            {
+            .and_then(|result: KrakenResult<EarnAllocations>| if !result.error.is_empty() {
                 Err(format!("{:?}", result.error))
             } else {
-                Ok(result.result.unwrap())
+                match result.result {
+                    Some(stuff) => Ok(stuff),
+                    None => Err(String::from("empty result")),
+                }
             })
     })
 }
 
-
 ///
-/// Create a new order.
+/// Get a token to subscribe to private websocket feeds, see `PrivateClient`.
+///
+/// # Arguments
+///
+/// + `account` - The account credentials to use.
 ///
 /// # Note
 ///
-/// + Prices can be preceded by +, -, or # to signify the price as a relative amount (with the exception of trailing stops,
-///   which are always relative).
-///   * + adds the amount to the current offered price.
-///   * - subtracts the amount from the current offered price.
-///   * # will either add or subtract the amount to the current offered price, depending on the type and order type used.
-///  Relative prices can be suffixed with a % to signify the relative amount as a percentage of the offered price.
-/// + For orders using leverage, 0 can be used for the volume to auto-fill the volume needed to close out your position.
-/// + If you receive the error "EOrder:Trading agreement required", refer to your API key management page for further details.
+/// The API key needs the "WebSocket interface" permission.
+/// A token stays valid as long as a connection it subscribed with is open.
+///
+/// # Result
+///
+/// ```json
+/// {
+///     "error": [],
+///     "result": {
+///         "token": "1Dwc4lzSwNWOAwkMdqhssNNFhs1ed606d1WcF3XfEMw",
+///         "expires": 900
+///     }
+/// }
+/// ```
 ///
-pub fn add_order(account: &Account, order: NewOrder) -> Result<HashMap<String, String>, String> {
+pub fn websockets_token(account: &Account) -> Result<WebSocketsToken, String> {
     let mut params = HashMap::new();
 
-    params.insert("pair".to_owned(), order.pair);
-    params.insert("volume".to_owned(), order.volume);
+    private(account, "GetWebSocketsToken", &mut params).and_then(|r| {
+        serde_json::from_slice(&r)
+            .map_err(|e| format!("{:?}", e))
+            .and_then(|result: KrakenResult<WebSocketsToken>| if !result.error.is_empty() {
+                Err(format!("{:?}", result.error))
+            } else {
+                match result.result {
+                    Some(stuff) => Ok(stuff),
+                    None => Err(String::from("empty result")),
+                }
+            })
+    })
+}
 
-    match order.kind {
-        TradeType::Sell => {
-            params.insert("type".to_owned(), String::from("sell"));
-        }
-        TradeType::Buy => {
-            params.insert("type".to_owned(), String::from("buy"));
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    const TRADES_CSV: &str = "\"txid\",\"ordertxid\",\"pair\",\"time\",\"type\",\"ordertype\",\"price\",\"cost\",\"fee\",\"vol\",\"margin\",\"misc\",\"ledgers\"
+\"TZX2WP-XSEOP-FP7WYR\",\"OLBHNE-4YBP2-ICKQX5\",\"XXBTZUSD\",\"2023-03-01 12:34:56.7891\",\"buy\",\"limit\",\"23000.0\",\"230.00000\",\"0.59800\",\"0.01000000\",\"0.00000\",\"\",\"LQA3DX-FG3NR-TAKLR4\"
+\"TJUW2K-FLX2N-AR2FLU\",\"OB2LFU-EGCVL-ESSZBM\",\"XETHZEUR\",\"1999-12-31 23:59:59.5\",\"sell\",\"market\",\"1500.00\",\"150.00000\",\"0.39000\",\"0.10000000\",\"30.00000\",\"closing\",\"\"
+";
+
+    const LEDGERS_CSV: &str = "\"txid\",\"refid\",\"time\",\"type\",\"subtype\",\"aclass\",\"asset\",\"amount\",\"fee\",\"balance\"
+\"LQA3DX-FG3NR-TAKLR4\",\"TZX2WP-XSEOP-FP7WYR\",\"2023-03-01 12:34:56.7891\",\"trade\",\"\",\"currency\",\"ZUSD\",\"-230.0000\",\"0.5980\",\"769.4020\"
+";
+
+    fn zipped(name: &str, content: &str) -> Vec<u8> {
+        let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+
+        writer.start_file(name, zip::write::FileOptions::default()).unwrap();
+        writer.write_all(content.as_bytes()).unwrap();
+        writer.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn days_since_epoch() {
+        assert_eq!(days_from_civil(1970, 1, 1), 0);
+        assert_eq!(days_from_civil(1969, 12, 31), -1);
+        assert_eq!(days_from_civil(2000, 2, 29), 11_016);
+        assert_eq!(days_from_civil(2000, 3, 1), 11_017);
+        assert_eq!(days_from_civil(2023, 3, 1), 19_417);
+    }
+
+    #[test]
+    fn export_time() {
+        assert_eq!(parse_export_time("1970-01-01 00:00:00").unwrap(), 0.0);
+        assert_eq!(parse_export_time("2023-03-01 12:34:56.7891").unwrap(), 1677674096.7891);
+        assert_eq!(parse_export_time("1999-12-31 23:59:59.5").unwrap(), 946684799.5);
+        assert!(parse_export_time("2023-03-01").is_err());
+        assert!(parse_export_time("2023-03-01 12:34").is_err());
+        assert!(parse_export_time("2023/03/01 12:34:56").is_err());
+    }
+
+    #[test]
+    fn unzip_export() {
+        let data = zipped("trades.csv", TRADES_CSV);
+
+        assert_eq!(unzip_report(data).unwrap(), TRADES_CSV.as_bytes());
+        assert!(unzip_report(zipped("readme.txt", "no report")).is_err());
+        assert!(unzip_report(b"not a zip".to_vec()).is_err());
+    }
+
+    #[test]
+    fn exported_trades() {
+        let trades = parse_exported_trades(TRADES_CSV.as_bytes()).unwrap();
+
+        assert_eq!(trades.len(), 2);
+
+        let trade = &trades["TZX2WP-XSEOP-FP7WYR"];
+
+        assert_eq!(trade.ordertxid, "OLBHNE-4YBP2-ICKQX5");
+        assert_eq!(trade.pair, "XXBTZUSD");
+        assert_eq!(trade.time, 1677674096.7891);
+        assert_eq!(trade.kind, TradeType::Buy);
+        assert_eq!(trade.ordertype, OrderType::Limit);
+        assert_eq!(trade.price, "23000.0");
+        assert_eq!(trade.vol, "0.01000000");
+        assert_eq!(trade.postxid, None);
+
+        let trade = &trades["TJUW2K-FLX2N-AR2FLU"];
+
+        assert_eq!(trade.kind, TradeType::Sell);
+        assert_eq!(trade.ordertype, OrderType::Market);
+        assert_eq!(trade.margin, "30.00000");
+        assert_eq!(trade.misc, "closing");
+    }
+
+    #[test]
+    fn exported_ledgers() {
+        let ledgers = parse_exported_ledgers(LEDGERS_CSV.as_bytes()).unwrap();
+        let ledger = &ledgers["LQA3DX-FG3NR-TAKLR4"];
+
+        assert_eq!(ledger.refid, "TZX2WP-XSEOP-FP7WYR");
+        assert_eq!(ledger.time, 1677674096.7891);
+        assert_eq!(ledger.kind, "trade");
+        assert_eq!(ledger.asset, "ZUSD");
+        assert_eq!(ledger.amount, "-230.0000");
+        assert_eq!(ledger.balance, "769.4020");
+    }
+
+    #[test]
+    fn exported_trades_with_invalid_time() {
+        let csv = TRADES_CSV.replace("2023-03-01 12:34:56.7891", "yesterday");
+
+        assert!(parse_exported_trades(csv.as_bytes()).is_err());
+    }
+
+    fn edit_response(fixture: &str) -> Result<EditOrderResult, String> {
+        let response: KrakenResult<EditOrderResult> = serde_json::from_str(fixture).unwrap();
+
+        assert!(response.error.is_empty());
+
+        edit_order_result(response.result.unwrap())
+    }
+
+    #[test]
+    fn edited_order() {
+        let result = edit_response(include_str!("../tests/fixtures/edit_order.json")).unwrap();
+
+        assert_eq!(result.txid.as_deref(), Some("OFVXHJ-KPQ3B-VS7ELA"));
+        assert_eq!(result.originaltxid, "OHYO67-6LP66-HMQ437");
+        assert_eq!(result.orders_cancelled, Some(1));
+        assert_eq!(result.descr.unwrap().order, "buy 0.00030000 XXBTZGBP @ limit 19500.0");
+    }
+
+    #[test]
+    fn failed_edit() {
+        let result = edit_response(include_str!("../tests/fixtures/edit_order_error.json"));
+
+        assert_eq!(result.unwrap_err(), "EOrder:Invalid order");
+    }
+
+    #[test]
+    fn edit_order_params() {
+        let params = edit_params(EditOrder {
+            txid: String::from("OHYO67-6LP66-HMQ437"),
+            pair: String::from("XXBTZGBP"),
+            volume: Some(String::from("0.0003")),
+            price: Some(String::from("19500.0")),
+            price2: None,
+            oflags: Some("post".parse().unwrap()),
+            userref: Some(String::from("42")),
+            validate: Some(true),
+        });
+
+        assert_eq!(params.len(), 7);
+        assert_eq!(params["txid"], "OHYO67-6LP66-HMQ437");
+        assert_eq!(params["pair"], "XXBTZGBP");
+        assert_eq!(params["volume"], "0.0003");
+        assert_eq!(params["price"], "19500.0");
+        assert_eq!(params["oflags"], "post");
+        assert_eq!(params["userref"], "42");
+        assert_eq!(params["validate"], "true");
+
+        let params = edit_params(EditOrder {
+            txid: String::from("OHYO67-6LP66-HMQ437"),
+            pair: String::from("XXBTZGBP"),
+            volume: None,
+            price: None,
+            price2: None,
+            oflags: None,
+            userref: None,
+            validate: Some(false),
+        });
+
+        assert_eq!(params.len(), 2);
+    }
+
+    fn limit_order(pair: &str, kind: TradeType, price: &str, volume: &str) -> NewOrder {
+        NewOrder {
+            pair: String::from(pair),
+            kind,
+            order_type: OrderType::Limit,
+            price: Some(String::from(price)),
+            price2: None,
+            volume: String::from(volume),
+            leverage: None,
+            oflags: None,
+            timeinforce: None,
+            starttm: None,
+            expiretm: None,
+            userref: None,
+            validate: None,
+            close: None,
+            reduce_only: None,
+            cl_ord_id: None,
+            trigger: None,
+            stptype: None,
         }
     }
 
-    let order_type = match order.order_type {
-        OrderType::Market => "market",
-        OrderType::Limit => "limit",
-        OrderType::StopLoss => "stop-loss",
-        OrderType::TakeProfit => "take-profit",
-        OrderType::StopLossProfit => "stop-loss-profit",
-        OrderType::StopLossProfitLimit => "stop-loss-profit-limit",
-        OrderType::StopLossLimit => "stop-loss-limit",
-        OrderType::TakeProfitLimit => "take-profit-limit",
-        OrderType::TrailingStop => "trailing-stop",
-        OrderType::TrailingStopLimit => "trailing-stop-limit",
-        OrderType::StopLossAndLimit => "stop-loss-and-limit",
-        OrderType::SettlePosition => "settle-position",
-    };
+    #[test]
+    fn batch_body() {
+        let mut first = limit_order("XXBTZUSD", TradeType::Buy, "27500.0", "1.25");
 
-    params.insert("ordertype".to_owned(), String::from(order_type));
+        first.userref = Some(String::from("42"));
+        first.reduce_only = Some(true);
+        first.validate = Some(true);
+        first.close = Some(CloseOrder {
+            order_type: OrderType::StopLossLimit,
+            price: String::from("22000.0"),
+            price2: Some(String::from("21000.0")),
+        });
 
-    if let Some(price) = order.price {
-        params.insert("price".to_owned(), price);
+        let second = limit_order("XXBTZUSD", TradeType::Sell, "31000.0", "0.5");
+
+        let params = batch_params("XXBTZUSD", vec![first, second], Some(true)).unwrap();
+        let body = serde_json::Value::Object(params);
+
+        assert_eq!(
+            body,
+            serde_json::json!({
+                "pair": "XXBTZUSD",
+                "validate": true,
+                "orders": [
+                    {
+                        "type": "buy",
+                        "ordertype": "limit",
+                        "price": "27500.0",
+                        "volume": "1.25",
+                        "userref": 42,
+                        "reduce_only": true,
+                        "close": {
+                            "ordertype": "stop-loss-limit",
+                            "price": "22000.0",
+                            "price2": "21000.0"
+                        }
+                    },
+                    {
+                        "type": "sell",
+                        "ordertype": "limit",
+                        "price": "31000.0",
+                        "volume": "0.5"
+                    }
+                ]
+            })
+        );
     }
 
-    if let Some(price) = order.price2 {
-        params.insert("price2".to_owned(), price);
+    #[test]
+    fn batch_of_mixed_pairs() {
+        let orders = vec![
+            limit_order("XXBTZUSD", TradeType::Buy, "27500.0", "1.25"),
+            limit_order("XETHZUSD", TradeType::Buy, "1800.0", "2"),
+        ];
+
+        assert!(batch_params("XXBTZUSD", orders, None).is_err());
     }
 
-    if let Some(leverage) = order.leverage {
-        params.insert("leverage".to_owned(), leverage);
+    #[test]
+    fn batch_results() {
+        let results = batch_order_results(include_bytes!("../tests/fixtures/add_order_batch.json")).unwrap();
+
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].txid.as_deref(), Some("OUF4EM-FRGI2-MQMWZD"));
+        assert_eq!(
+            results[0].descr.as_ref().map(|descr| descr.order.as_str()),
+            Some("buy 1.25000000 XBTUSD @ limit 27500.0")
+        );
+        assert_eq!(results[0].error, None);
+        assert_eq!(results[1].txid, None);
+        assert!(results[1].descr.is_none());
+        assert_eq!(results[1].error.as_deref(), Some("EOrder:Insufficient funds"));
+
+        let failed = batch_order_results(br#"{"error": ["EGeneral:Invalid arguments"]}"#);
+
+        assert_eq!(failed.unwrap_err(), r#"["EGeneral:Invalid arguments"]"#);
     }
 
-    if let Some(oflags) = order.oflags {
-        params.insert("oflags".to_owned(), oflags);
+    #[test]
+    fn order_params_with_invalid_time() {
+        let mut order = limit_order("XXBTZUSD", TradeType::Buy, "27500.0", "1.25");
+
+        order.expiretm = Some(OrderTime::In(Duration::from_millis(1500)));
+
+        assert!(order_params(order).is_err());
+
+        let mut order = limit_order("XXBTZUSD", TradeType::Buy, "27500.0", "1.25");
+
+        order.starttm = Some(OrderTime::In(Duration::from_secs(60)));
+        order.expiretm = Some(OrderTime::At(UNIX_EPOCH + Duration::from_secs(1_700_000_000)));
+
+        let params = order_params(order).unwrap();
+
+        assert_eq!(params["starttm"], "+60");
+        assert_eq!(params["expiretm"], "1700000000");
     }
 
-    if let Some(userref) = order.userref {
-        params.insert("userref".to_owned(), userref);
+    #[test]
+    fn order_params_with_close() {
+        let params = order_params(limit_order("XXBTZUSD", TradeType::Buy, "27500.0", "1.25")).unwrap();
+
+        assert!(!params.keys().any(|key| key.starts_with("close[")));
+
+        let mut order = limit_order("XXBTZUSD", TradeType::Buy, "27500.0", "1.25");
+
+        order.close = Some(CloseOrder {
+            order_type: OrderType::StopLossLimit,
+            price: String::from("22000.0"),
+            price2: Some(String::from("21000.0")),
+        });
+
+        let params = order_params(order).unwrap();
+
+        assert_eq!(params["ordertype"], "limit");
+        assert_eq!(params["close[ordertype]"], "stop-loss-limit");
+        assert_eq!(params["close[price]"], "22000.0");
+        assert_eq!(params["close[price2]"], "21000.0");
+
+        let mut order = limit_order("XXBTZUSD", TradeType::Sell, "27500.0", "1.25");
+
+        order.close = Some(CloseOrder {
+            order_type: OrderType::TakeProfit,
+            price: String::from("25000.0"),
+            price2: None,
+        });
+
+        let params = order_params(order).unwrap();
+
+        assert_eq!(params["close[ordertype]"], "take-profit");
+        assert_eq!(params["close[price]"], "25000.0");
+        assert!(!params.contains_key("close[price2]"));
     }
 
-    if let Some(starttm) = order.starttm {
-        params.insert("starttm".to_owned(), format!("{}", starttm));
+    #[test]
+    fn order_params_with_flags_and_options() {
+        let mut order = limit_order("XXBTZUSD", TradeType::Buy, "27500.0", "1.25");
+
+        order.oflags = Some(OrderFlags::new().with(OrderFlag::Post).with(OrderFlag::Fciq));
+        order.timeinforce = Some(TimeInForce::Gtd);
+        order.reduce_only = Some(false);
+        order.cl_ord_id = Some(String::from("my-order"));
+        order.trigger = Some(Trigger::Index);
+        order.stptype = Some(SelfTradePrevention::CancelBoth);
+
+        let params = order_params(order).unwrap();
+
+        assert_eq!(params["oflags"], "post,fciq");
+        assert_eq!(params["timeinforce"], "GTD");
+        assert_eq!(params["reduce_only"], "false");
+        assert_eq!(params["cl_ord_id"], "my-order");
+        assert_eq!(params["trigger"], "index");
+        assert_eq!(params["stptype"], "cancel-both");
+
+        let params = order_params(limit_order("XXBTZUSD", TradeType::Buy, "27500.0", "1.25")).unwrap();
+
+        for key in &["oflags", "timeinforce", "reduce_only", "cl_ord_id", "trigger", "stptype"] {
+            assert!(!params.contains_key(*key), "{}", key);
+        }
     }
 
-    if let Some(expiretm) = order.expiretm {
-        params.insert("expiretm".to_owned(), format!("{}", expiretm));
+    #[test]
+    fn wire_names_match_serde() {
+        let name = |value: serde_json::Value| value.as_str().map(String::from);
+
+        for tif in &[TimeInForce::Gtc, TimeInForce::Ioc, TimeInForce::Gtd] {
+            assert_eq!(name(serde_json::to_value(tif).unwrap()).as_deref(), Some(tif.as_str()));
+        }
+
+        for trigger in &[Trigger::Last, Trigger::Index] {
+            assert_eq!(name(serde_json::to_value(trigger).unwrap()).as_deref(), Some(trigger.as_str()));
+        }
+
+        for stp in &[
+            SelfTradePrevention::CancelNewest,
+            SelfTradePrevention::CancelOldest,
+            SelfTradePrevention::CancelBoth,
+        ]
+        {
+            assert_eq!(name(serde_json::to_value(stp).unwrap()).as_deref(), Some(stp.as_str()));
+        }
+
+        assert_eq!(serde_json::from_str::<TimeInForce>(r#""IOC""#).unwrap(), TimeInForce::Ioc);
+        assert!(serde_json::from_str::<TimeInForce>(r#""ioc""#).is_err());
+        assert_eq!(
+            serde_json::from_str::<SelfTradePrevention>(r#""cancel-oldest""#).unwrap(),
+            SelfTradePrevention::CancelOldest
+        );
     }
 
-    if order.validate.is_some() {
-        params.insert("validate".to_owned(), String::from("1"));
+    #[test]
+    fn recent_trades() {
+        let recent = parse_recent_trades(include_bytes!("../tests/fixtures/recent_trades.json")).unwrap();
+
+        assert_eq!(recent.last, "1688671969993150842");
+        assert_eq!(recent.trades.len(), 2);
+
+        let trade = &recent.trades[0];
+
+        assert_eq!(trade.price.to_string(), "30243.40000");
+        assert_eq!(trade.volume.to_string(), "0.34507674");
+        assert_eq!(trade.time(), UNIX_EPOCH + Duration::from_micros(1688669597827737));
+        assert_eq!(trade.kind, TradeType::Buy);
+        assert_eq!(trade.ordertype, OrderType::Market);
+        assert_eq!(recent.trades[1].kind, TradeType::Sell);
+        assert_eq!(recent.trades[1].ordertype, OrderType::Limit);
     }
 
-    private(account, "AddOrder", &mut params).and_then(|r| {
-        serde_json::from_slice(&r)
-            .map_err(|e| format!("{:?}", e))
-            .and_then(
-                |result: KrakenResult<HashMap<String, String>>| if result.error.len() > 0 {
-                    Err(format!("{:?}", result.error))
-                } else {
-                    Ok(result.result.unwrap())
-                },
-            )
-    })
-}
+    #[test]
+    fn recent_spreads() {
+        let recent = parse_recent_spreads(include_bytes!("../tests/fixtures/recent_spread.json")).unwrap();
 
-///
-/// Cancels an order.
-///
-/// # Note
-///
-///  txid may be a user reference id.
-///
-pub fn cancel_order(account: &Account, txid: &str) -> Result<CanceldOrders, String> {
-    let mut params = HashMap::new();
+        assert_eq!(recent.last, 1688672106);
+        assert_eq!(recent.spreads.len(), 2);
 
-    params.insert("txid".to_owned(), String::from(txid));
+        let spread = &recent.spreads[1];
 
-    private(account, "CancelOrder", &mut params).and_then(|r| {
-        serde_json::from_slice(&r)
-            .map_err(|e| format!("{:?}", e))
-            .and_then(
-                |result: KrakenResult<CanceldOrders>| if result.error.len() > 0 {
-                    Err(format!("{:?}", result.error))
-                } else {
-                    Ok(result.result.unwrap())
-                },
-            )
-    })
+        assert_eq!(spread.time(), UNIX_EPOCH + Duration::from_secs(1688671834));
+        assert_eq!(spread.bid.to_string(), "30292.10000");
+        assert_eq!(spread.ask.to_string(), "30296.70000");
+        assert!(spread.bid_volume.is_none());
+    }
+
+    #[test]
+    fn closed_orders_cursor() {
+        assert_eq!(ClosedOrdersCursor::from(1688148493), ClosedOrdersCursor::Timestamp(1688148493));
+        assert_eq!(
+            ClosedOrdersCursor::from("O37652-RJWRT-IMO74O"),
+            ClosedOrdersCursor::TxId(String::from("O37652-RJWRT-IMO74O"))
+        );
+        assert_eq!(
+            ClosedOrdersCursor::from(String::from("O37652-RJWRT-IMO74O")),
+            ClosedOrdersCursor::from("O37652-RJWRT-IMO74O")
+        );
+
+        assert_eq!(ClosedOrdersCursor::from(1688148493).to_string(), "1688148493");
+        assert_eq!(ClosedOrdersCursor::from(-1).to_string(), "-1");
+        assert_eq!(ClosedOrdersCursor::from("O37652-RJWRT-IMO74O").to_string(), "O37652-RJWRT-IMO74O");
+    }
+
+    #[test]
+    fn closed_orders_pages() {
+        let page = |count: u32| {
+            let response: KrakenResult<ClosedOrders> =
+                serde_json::from_str(include_str!("../tests/fixtures/closed_orders.json")).unwrap();
+            let mut page = response.result.unwrap();
+
+            page.count = count;
+            page
+        };
+        let account = Account {
+            key: String::new(),
+            secret: String::new(),
+        };
+        let cfg = ClosedOrdersConfig {
+            ofs: Some(10),
+            ..Default::default()
+        };
+        let mut iter = closed_orders_iter(&account, Some(cfg));
+
+        assert_eq!(iter.offset, 10);
+
+        iter.receive(page(15));
+
+        assert_eq!(iter.offset, 12);
+        assert!(!iter.done);
+
+        let txids: Vec<&str> = iter.buffer.iter().map(|order| order.0.as_str()).collect();
+
+        assert_eq!(txids, vec!["OBCMZD-JIEE7-77TH3F", "O37652-RJWRT-IMO74O"]);
+
+        // orders which were shifted into the next page are skipped
+        iter.buffer.clear();
+        iter.receive(page(15));
+
+        assert_eq!(iter.offset, 14);
+        assert!(!iter.done);
+        assert!(iter.buffer.is_empty());
+
+        iter.receive(page(14));
+
+        assert_eq!(iter.offset, 16);
+        assert!(iter.done);
+
+        let mut iter = closed_orders_iter(&account, None);
+
+        iter.receive(ClosedOrders {
+            closed: HashMap::new(),
+            count: 5,
+        });
+
+        assert_eq!(iter.offset, 0);
+        assert!(iter.done);
+    }
+
+    #[test]
+    fn expired_withdraw_preview() {
+        let account = Account {
+            key: String::new(),
+            secret: String::new(),
+        };
+        let preview = |age: Duration| WithdrawPreview {
+            asset: String::from("XXBT"),
+            key: String::from("cold storage"),
+            amount: "0.5".parse().unwrap(),
+            info: WithdrawInfo {
+                method: String::from("Bitcoin"),
+                limit: String::from("1.0"),
+                amount: String::from("0.4995"),
+                fee: String::from("0.0005"),
+            },
+            created: Instant::now().checked_sub(age).unwrap(),
+        };
+
+        assert!(!preview(Duration::from_secs(0)).is_expired());
+        assert!(preview(WITHDRAW_PREVIEW_MAX_AGE + Duration::from_secs(1)).is_expired());
+
+        assert_eq!(
+            withdraw(&account, preview(WITHDRAW_PREVIEW_MAX_AGE + Duration::from_secs(1))).unwrap_err(),
+            "withdraw preview of 0.5 XXBT expired after 60s"
+        );
+    }
+
+    #[test]
+    fn txid_batches_of_fifty() {
+        let ids: Vec<String> = (0..120).map(|i| format!("O{}", i)).collect();
+        let txids: Vec<&str> = ids.iter().map(|id| id.as_str()).collect();
+
+        assert_eq!(txid_batches(&[]), vec![None]);
+        assert_eq!(txid_batches(&txids[..1]), vec![Some(String::from("O0"))]);
+        assert_eq!(txid_batches(&txids[..50]), vec![Some(txids[..50].join(","))]);
+
+        let batches = txid_batches(&txids);
+
+        assert_eq!(batches.len(), 3);
+        assert_eq!(batches[0], Some(txids[..50].join(",")));
+        assert_eq!(batches[1], Some(txids[50..100].join(",")));
+        assert_eq!(batches[2], Some(txids[100..].join(",")));
+    }
+
+    #[test]
+    fn recent_trades_error() {
+        assert!(parse_recent_trades(br#"{"error": ["EQuery:Unknown asset pair"]}"#).is_err());
+        assert!(parse_recent_trades(br#"{"error": [], "result": {"last": "1"}}"#).is_err());
+        assert!(
+            parse_recent_trades(br#"{"error": [], "result": {"XXBTZUSD": [["1.0", "1.0", 1, "x", "l", ""]], "last": "1"}}"#)
+                .is_err()
+        );
+    }
 }
@@ -0,0 +1,269 @@
+//!
+//! Routing of API calls to several named accounts.
+//!
+
+use super::Account;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+///
+/// Verification tier of an account, which determines its API rate limit.
+///
+/// Every private call increases a counter by its cost, the counter decays over time.
+/// Calls are rejected by kraken while the counter exceeds the tier's maximum.
+///
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Tier {
+    /// maximum 15, decays by 0.33 per second
+    Starter,
+    /// maximum 20, decays by 0.5 per second
+    Intermediate,
+    /// maximum 20, decays by 1 per second
+    Pro,
+}
+
+impl Tier {
+    fn limits(&self) -> (f64, f64) {
+        match *self {
+            Tier::Starter => (15.0, 0.33),
+            Tier::Intermediate => (20.0, 0.5),
+            Tier::Pro => (20.0, 1.0),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct RateLimit {
+    counter: f64,
+    updated: Instant,
+    max: f64,
+    decay: f64,
+}
+
+impl RateLimit {
+    fn new(tier: Tier) -> RateLimit {
+        let (max, decay) = tier.limits();
+
+        RateLimit {
+            counter: 0.0,
+            updated: Instant::now(),
+            max,
+            decay,
+        }
+    }
+
+    /// Reserves `cost` and returns how long to wait before the call may be made.
+    fn reserve(&mut self, cost: f64) -> Duration {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.updated).as_secs_f64();
+
+        self.counter = (self.counter - elapsed * self.decay).max(0.0);
+        self.updated = now;
+        self.counter += cost;
+
+        if self.counter > self.max {
+            Duration::from_secs_f64((self.counter - self.max) / self.decay)
+        } else {
+            Duration::from_secs(0)
+        }
+    }
+}
+
+#[derive(Debug)]
+struct ManagedAccount {
+    account: Account,
+    limit: Mutex<RateLimit>,
+}
+
+///
+/// Keeps several named accounts, e.g. a master account and its subaccounts,
+/// and tracks the API rate limit of each one separately.
+///
+/// # Note
+///
+/// Nonces are always tracked per API key, so the managed accounts can be used
+/// from several threads at once.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// extern crate kraken;
+///
+/// use kraken::{Account, AccountManager, Tier};
+///
+/// fn main() {
+///   let mut manager = AccountManager::new();
+///
+///   manager.add("master", Account { key: String::from("<key>"), secret: String::from("<secret>") }, Tier::Pro);
+///   manager.add("bot", Account { key: String::from("<key>"), secret: String::from("<secret>") }, Tier::Pro);
+///
+///   let balances = manager.call("bot", 1, kraken::balance).expect("could not get balance");
+///
+///   println!("{:?}", balances);
+/// }
+/// ```
+///
+#[derive(Debug, Default)]
+pub struct AccountManager {
+    accounts: HashMap<String, ManagedAccount>,
+}
+
+impl AccountManager {
+    pub fn new() -> AccountManager {
+        AccountManager { accounts: HashMap::new() }
+    }
+
+    /// Adds or replaces the account registered under `name`.
+    pub fn add(&mut self, name: &str, account: Account, tier: Tier) {
+        self.accounts.insert(
+            String::from(name),
+            ManagedAccount {
+                account,
+                limit: Mutex::new(RateLimit::new(tier)),
+            },
+        );
+    }
+
+    /// Removes the account registered under `name`.
+    pub fn remove(&mut self, name: &str) -> Option<Account> {
+        self.accounts.remove(name).map(|managed| managed.account)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Account> {
+        self.accounts.get(name).map(|managed| &managed.account)
+    }
+
+    pub fn names(&self) -> Vec<&str> {
+        self.accounts.keys().map(|name| name.as_str()).collect()
+    }
+
+    ///
+    /// Calls `f` with the account registered under `name`,
+    /// after waiting until the account's rate limit allows a call of `cost`.
+    ///
+    /// # Note
+    ///
+    /// Most calls cost 1, ledger and trade history queries cost 2.
+    /// Order placement and cancelation have a separate limit on kraken's side and cost 0.
+    ///
+    pub fn call<T, F>(&self, name: &str, cost: u32, f: F) -> Result<T, String>
+    where
+        F: FnOnce(&Account) -> Result<T, String>,
+    {
+        let managed = self.accounts.get(name).ok_or_else(
+            || format!("unknown account: {}", name),
+        )?;
+
+        let wait = managed
+            .limit
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .reserve(f64::from(cost));
+
+        if wait > Duration::from_secs(0) {
+            thread::sleep(wait);
+        }
+
+        f(&managed.account)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn account(key: &str) -> Account {
+        Account {
+            key: String::from(key),
+            secret: String::from("secret"),
+        }
+    }
+
+    fn secs(duration: Duration) -> f64 {
+        duration.as_secs_f64()
+    }
+
+    #[test]
+    fn limits() {
+        assert_eq!(Tier::Starter.limits(), (15.0, 0.33));
+        assert_eq!(Tier::Intermediate.limits(), (20.0, 0.5));
+        assert_eq!(Tier::Pro.limits(), (20.0, 1.0));
+    }
+
+    #[test]
+    fn reserve_within_limit() {
+        let mut limit = RateLimit::new(Tier::Intermediate);
+
+        for _ in 0..20 {
+            assert_eq!(limit.reserve(1.0), Duration::from_secs(0));
+        }
+
+        assert!(limit.counter <= 20.0 && limit.counter > 19.9);
+    }
+
+    #[test]
+    fn reserve_beyond_limit() {
+        let mut limit = RateLimit::new(Tier::Intermediate);
+
+        assert_eq!(limit.reserve(20.0), Duration::from_secs(0));
+
+        // 2 over the maximum at a decay of 0.5 per second
+        let wait = secs(limit.reserve(2.0));
+
+        assert!(wait > 3.9 && wait <= 4.0, "{}", wait);
+
+        let wait = secs(limit.reserve(1.0));
+
+        assert!(wait > 5.9 && wait <= 6.0, "{}", wait);
+    }
+
+    #[test]
+    fn counter_decays() {
+        let mut limit = RateLimit::new(Tier::Pro);
+
+        limit.reserve(20.0);
+        limit.updated -= Duration::from_secs(5);
+
+        assert_eq!(limit.reserve(5.0), Duration::from_secs(0));
+        assert!(limit.counter <= 20.0 && limit.counter > 19.9);
+
+        // the counter does not go below zero
+        limit.updated -= Duration::from_secs(60);
+
+        assert_eq!(limit.reserve(0.0), Duration::from_secs(0));
+        assert_eq!(limit.counter, 0.0);
+
+        let wait = secs(limit.reserve(21.0));
+
+        assert!(wait > 0.9 && wait <= 1.0, "{}", wait);
+    }
+
+    #[test]
+    fn accounts() {
+        let mut manager = AccountManager::new();
+
+        manager.add("master", account("master"), Tier::Pro);
+        manager.add("bot", account("bot"), Tier::Starter);
+
+        let mut names = manager.names();
+        names.sort();
+
+        assert_eq!(names, vec!["bot", "master"]);
+        assert_eq!(manager.get("bot").map(|a| a.key.as_str()), Some("bot"));
+
+        assert_eq!(
+            manager.call("bot", 1, |a| Ok(a.key.clone())),
+            Ok(String::from("bot"))
+        );
+        assert_eq!(
+            manager.call::<(), _>("other", 1, |_| Ok(())),
+            Err(String::from("unknown account: other"))
+        );
+
+        assert_eq!(manager.remove("bot").map(|a| a.key), Some(String::from("bot")));
+        assert!(manager.get("bot").is_none());
+        assert!(manager.remove("bot").is_none());
+    }
+}
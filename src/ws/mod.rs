@@ -0,0 +1,170 @@
+//!
+//! WebSocket feeds of kraken.
+//!
+//! Connections are blocking, events are read by iterating over a client.
+//! Lost connections are re-established with a growing backoff and all subscriptions are renewed.
+//!
+
+mod private;
+mod public;
+
+pub use self::private::{OrderChange, OwnTrade, PrivateChannel, PrivateClient, PrivateEvent};
+pub use self::public::{Channel, PublicClient, PublicEvent, Subscription};
+
+use serde::Serialize;
+use serde_json::{self, Value};
+use std::io;
+use std::net::TcpStream;
+use std::thread;
+use std::time::Duration;
+use tungstenite::stream::MaybeTlsStream;
+use tungstenite::{self, Message, WebSocket};
+
+/// Kraken sends a heartbeat at least every second while subscribed, a silent connection is dead.
+const HEARTBEAT_TIMEOUT: Duration = Duration::from_secs(10);
+
+const MIN_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// Details of a subscription request
+#[derive(Serialize, Debug)]
+struct SubscriptionDetails {
+    name: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    interval: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    depth: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    token: Option<String>,
+}
+
+/// Subscribe or unsubscribe request
+#[derive(Serialize, Debug)]
+struct SubscribeMessage<'a> {
+    event: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pair: Option<&'a [String]>,
+    subscription: SubscriptionDetails,
+}
+
+/// A websocket connection exchanging json messages.
+#[derive(Debug)]
+struct Connection {
+    url: String,
+    socket: Option<WebSocket<MaybeTlsStream<TcpStream>>>,
+    backoff: Duration,
+}
+
+impl Connection {
+    fn new(url: &str) -> Connection {
+        Connection {
+            url: String::from(url),
+            socket: None,
+            backoff: MIN_BACKOFF,
+        }
+    }
+
+    fn is_connected(&self) -> bool {
+        self.socket.is_some()
+    }
+
+    fn connect(&mut self) -> Result<(), String> {
+        let (socket, _) = tungstenite::connect(self.url.as_str()).map_err(
+            |e| format!("{:?}", e),
+        )?;
+
+        let stream = match *socket.get_ref() {
+            MaybeTlsStream::Plain(ref stream) => Some(stream),
+            MaybeTlsStream::NativeTls(ref stream) => Some(stream.get_ref()),
+            _ => None,
+        };
+
+        if let Some(stream) = stream {
+            stream.set_read_timeout(Some(HEARTBEAT_TIMEOUT)).map_err(
+                |e| format!("{:?}", e),
+            )?;
+        }
+
+        self.socket = Some(socket);
+
+        Ok(())
+    }
+
+    /// Waits for the current backoff and connects again, the backoff doubles on every failed attempt.
+    fn reconnect(&mut self) -> Result<(), String> {
+        self.disconnect();
+
+        thread::sleep(self.backoff);
+
+        match self.connect() {
+            Ok(()) => {
+                self.backoff = MIN_BACKOFF;
+                Ok(())
+            }
+            Err(e) => {
+                self.backoff = (self.backoff * 2).min(MAX_BACKOFF);
+                Err(e)
+            }
+        }
+    }
+
+    fn disconnect(&mut self) {
+        if let Some(mut socket) = self.socket.take() {
+            let _ = socket.close(None);
+            let _ = socket.flush();
+        }
+    }
+
+    fn send<T: Serialize>(&mut self, message: &T) -> Result<(), String> {
+        let text = serde_json::to_string(message).map_err(|e| format!("{:?}", e))?;
+
+        let result = match self.socket {
+            Some(ref mut socket) => socket.send(Message::Text(text)),
+            None => return Err(String::from("not connected")),
+        };
+
+        result.map_err(|e| {
+            self.socket = None;
+            format!("{:?}", e)
+        })
+    }
+
+    /// Reads the next json message, control frames are handled by tungstenite.
+    fn read(&mut self) -> Result<Value, String> {
+        loop {
+            let result = match self.socket {
+                Some(ref mut socket) => socket.read(),
+                None => return Err(String::from("not connected")),
+            };
+
+            match result {
+                Ok(Message::Text(text)) => {
+                    return serde_json::from_str(&text).map_err(|e| format!("{:?}", e))
+                }
+                Ok(Message::Binary(data)) => {
+                    return serde_json::from_slice(&data).map_err(|e| format!("{:?}", e))
+                }
+                Ok(Message::Close(frame)) => {
+                    self.socket = None;
+                    return Err(format!("connection closed: {:?}", frame));
+                }
+                Ok(_) => continue,
+                Err(tungstenite::Error::Io(ref e))
+                    if e.kind() == io::ErrorKind::WouldBlock || e.kind() == io::ErrorKind::TimedOut => {
+                    self.disconnect();
+                    return Err(format!("no message within {:?}", HEARTBEAT_TIMEOUT));
+                }
+                Err(e) => {
+                    self.socket = None;
+                    return Err(format!("{:?}", e));
+                }
+            }
+        }
+    }
+}
+
+impl Drop for Connection {
+    fn drop(&mut self) {
+        self.disconnect();
+    }
+}
@@ -0,0 +1,1205 @@
+//!
+//! Private feeds of own trades and orders.
+//!
+
+use super::super::{
+    edit_params, order_params, string_or_number, timestamp, value_array, value_f64, value_string, websockets_token,
+    Account, AddOrderDescription, AddOrderResult, CancelAllOrdersAfter, CanceldOrders, Decimal, EditOrder,
+    EditOrderDescription, EditOrderResult, NewOrder, OrderInfo, OrderStatus, OrderType, TradeType,
+};
+use super::{Connection, SubscribeMessage, SubscriptionDetails};
+use serde_json::{self, Map, Value};
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant, SystemTime};
+
+const URL: &str = "wss://ws-auth.kraken.com";
+
+/// How long to wait for the response to a request.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Channel of the private feed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PrivateChannel {
+    /// trades of own orders, starting with the last 50 trades
+    OwnTrades,
+    /// own open orders and their changes, starting with all open orders
+    OpenOrders,
+}
+
+impl PrivateChannel {
+    fn name(&self) -> &'static str {
+        match *self {
+            PrivateChannel::OwnTrades => "ownTrades",
+            PrivateChannel::OpenOrders => "openOrders",
+        }
+    }
+}
+
+/// Trade of an own order, as sent by the `ownTrades` feed
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct OwnTrade {
+    /// order responsible for execution of trade
+    pub ordertxid: String,
+    /// position responsible for execution of trade (if any)
+    pub postxid: Option<String>,
+    /// asset pair, in the websocket form, e.g. "XBT/USD"
+    pub pair: String,
+    /// unix timestamp of trade
+    pub time: f64,
+    /// type of order (buy/sell)
+    #[serde(rename = "type")]
+    pub kind: TradeType,
+    pub ordertype: OrderType,
+    /// average price order was executed at (quote currency)
+    pub price: Decimal,
+    /// total cost of order (quote currency)
+    pub cost: Decimal,
+    /// total fee (quote currency)
+    pub fee: Decimal,
+    /// volume (base currency)
+    pub vol: Decimal,
+    /// initial margin (quote currency), for margin trades
+    #[serde(default)]
+    pub margin: Option<Decimal>,
+    /// user reference id of the order
+    #[serde(default, deserialize_with = "string_or_number")]
+    pub userref: Option<String>,
+    /// comma delimited list of miscellaneous info
+    #[serde(default)]
+    pub misc: String,
+}
+
+impl OwnTrade {
+    pub fn time(&self) -> SystemTime {
+        timestamp(self.time)
+    }
+}
+
+/// Change of an open order, only the changed values are set
+#[derive(Debug, Clone, Default)]
+pub struct OrderChange {
+    pub status: Option<OrderStatus>,
+    /// total volume executed so far
+    pub vol_exec: Option<Decimal>,
+    /// total cost so far
+    pub cost: Option<Decimal>,
+    /// total fee so far
+    pub fee: Option<Decimal>,
+    /// average price so far
+    pub avg_price: Option<Decimal>,
+    /// additional info on the status, e.g. why the order was canceled
+    pub reason: Option<String>,
+}
+
+/// Event of the private feed
+#[derive(Debug)]
+pub enum PrivateEvent {
+    /// trade of an own order, `snapshot` is set for the trades sent after subscribing
+    Trade {
+        txid: String,
+        trade: Box<OwnTrade>,
+        snapshot: bool,
+    },
+    /// a new order, `snapshot` is set for the open orders sent after subscribing
+    OrderOpened {
+        txid: String,
+        order: Box<OrderInfo>,
+        snapshot: bool,
+    },
+    /// fill or status change of an order
+    OrderChanged { txid: String, change: Box<OrderChange> },
+    Subscribed { channel: String },
+    Unsubscribed { channel: String },
+    SubscriptionError { message: String },
+    /// error message sent by kraken, not related to a subscription
+    Error { message: String },
+    /// status of the exchange: online, maintenance, cancel_only, limit_only or post_only
+    SystemStatus { status: String },
+    /// the connection was lost and is established again, all subscriptions are renewed
+    /// and start with a new snapshot
+    Reconnected,
+}
+
+///
+/// Client of the private websocket feeds.
+///
+/// Works like `PublicClient`, a new token is requested for every connection.
+/// Messages are checked for gaps in their sequence numbers, a gap drops the connection,
+/// so the subscriptions are renewed and start with a new snapshot.  A trade or order which
+/// can't be decoded is returned as error in its place, the others of the message are kept.
+///
+/// Orders can be placed and canceled over the same connection, see `add_order`.
+/// Requests don't reconnect, after a lost connection they fail with "not connected"
+/// until `next_event` is called.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// extern crate kraken;
+///
+/// use kraken::{Account, PrivateChannel, PrivateClient, PrivateEvent};
+///
+/// fn main() {
+///   let account = Account { key: String::from("<key>"), secret: String::from("<secret>") };
+///   let mut client = PrivateClient::connect(&account).expect("could not connect");
+///
+///   client.subscribe(PrivateChannel::OwnTrades).unwrap();
+///   client.subscribe(PrivateChannel::OpenOrders).unwrap();
+///
+///   for event in client {
+///     match event {
+///       Ok(PrivateEvent::Trade { txid, trade, snapshot: false }) => println!("fill {}: {:?}", txid, trade),
+///       Ok(PrivateEvent::OrderChanged { txid, change }) => println!("{}: {:?}", txid, change.status),
+///       Ok(_) => {}
+///       Err(e) => println!("feed error: {}", e),
+///     }
+///   }
+/// }
+/// ```
+///
+#[derive(Debug)]
+pub struct PrivateClient {
+    connection: Connection,
+    account: Account,
+    token: String,
+    subscriptions: Vec<PrivateChannel>,
+    /// last sequence number per channel, none until the snapshot arrived
+    sequences: HashMap<PrivateChannel, u64>,
+    /// events and errors decoded from a message carrying several trades or orders,
+    /// or received while waiting for the response to a request
+    pending: VecDeque<Result<PrivateEvent, String>>,
+    /// id of the next request, to match its response
+    reqid: u64,
+    /// set after a sequence gap, the connection is dropped on the next call of `next_event`
+    resync: bool,
+    /// how long to wait for the response to a request
+    request_timeout: Duration,
+}
+
+impl PrivateClient {
+    pub fn connect(account: &Account) -> Result<PrivateClient, String> {
+        let token = websockets_token(account)?.token;
+        let mut connection = Connection::new(URL);
+
+        connection.connect()?;
+
+        Ok(PrivateClient {
+            connection,
+            account: account.clone(),
+            token,
+            subscriptions: Vec::new(),
+            sequences: HashMap::new(),
+            pending: VecDeque::new(),
+            reqid: 0,
+            resync: false,
+            request_timeout: REQUEST_TIMEOUT,
+        })
+    }
+
+    /// Subscribes to a channel, the subscription is renewed after reconnects.
+    pub fn subscribe(&mut self, channel: PrivateChannel) -> Result<(), String> {
+        self.send("subscribe", channel)?;
+
+        if !self.subscriptions.contains(&channel) {
+            self.subscriptions.push(channel);
+        }
+
+        Ok(())
+    }
+
+    pub fn unsubscribe(&mut self, channel: PrivateChannel) -> Result<(), String> {
+        self.subscriptions.retain(|c| *c != channel);
+        self.sequences.remove(&channel);
+        self.send("unsubscribe", channel)
+    }
+
+    fn send(&mut self, event: &'static str, channel: PrivateChannel) -> Result<(), String> {
+        self.connection.send(&SubscribeMessage {
+            event,
+            pair: None,
+            subscription: SubscriptionDetails {
+                name: channel.name(),
+                interval: None,
+                depth: None,
+                token: Some(self.token.clone()),
+            },
+        })
+    }
+
+    /// Waits for the next event, reconnecting first if the connection was lost.
+    pub fn next_event(&mut self) -> Result<PrivateEvent, String> {
+        if let Some(event) = self.pending.pop_front() {
+            return event;
+        }
+
+        if self.resync {
+            self.resync = false;
+            self.connection.disconnect();
+        }
+
+        if !self.connection.is_connected() {
+            self.sequences.clear();
+
+            // the token first, a connection without its subscriptions would stay silent
+            self.token = websockets_token(&self.account)?.token;
+            self.connection.reconnect()?;
+
+            for channel in self.subscriptions.clone() {
+                if let Err(e) = self.send("subscribe", channel) {
+                    // retry the whole sequence on the next call
+                    self.connection.disconnect();
+                    return Err(e);
+                }
+            }
+
+            return Ok(PrivateEvent::Reconnected);
+        }
+
+        loop {
+            let message = self.connection.read()?;
+
+            self.queue(&message)?;
+
+            if let Some(event) = self.pending.pop_front() {
+                return event;
+            }
+        }
+    }
+
+    ///
+    /// Decodes the events of a message and appends them to the pending events.
+    ///
+    /// Each trade or order is decoded on its own, one which can't be decoded is queued as error
+    /// in its place.
+    ///
+    fn queue(&mut self, message: &Value) -> Result<(), String> {
+        if let Some(event) = message.get("event").and_then(Value::as_str) {
+            if let Some(event) = decode_event(event, message) {
+                self.pending.push_back(Ok(event));
+            }
+
+            return Ok(());
+        }
+
+        let (channel, snapshot) = self.check_sequence(message)?;
+
+        for (txid, value) in entries(message)? {
+            let event = decode_entry(channel, &txid, value, snapshot)
+                .map_err(|e| format!("{} {}: {}", channel.name(), txid, e));
+
+            self.pending.push_back(event);
+        }
+
+        Ok(())
+    }
+
+    /// Checks the sequence number of a channel message, returns its channel and whether it is a snapshot.
+    fn check_sequence(&mut self, message: &Value) -> Result<(PrivateChannel, bool), String> {
+        let values = value_array(message)?;
+
+        if values.len() < 3 {
+            return Err(format!("unexpected message: {}", message));
+        }
+
+        let channel = match values[1].as_str() {
+            Some("ownTrades") => PrivateChannel::OwnTrades,
+            Some("openOrders") => PrivateChannel::OpenOrders,
+            _ => return Err(format!("unknown channel: {}", values[1])),
+        };
+
+        let sequence = values[2]
+            .get("sequence")
+            .and_then(Value::as_u64)
+            .ok_or_else(|| format!("missing sequence: {}", message))?;
+
+        let snapshot = match self.sequences.insert(channel, sequence) {
+            Some(last) if sequence != last + 1 => {
+                self.resync = true;
+
+                return Err(format!(
+                    "{} sequence gap: {} after {}",
+                    channel.name(),
+                    sequence,
+                    last
+                ));
+            }
+            Some(_) => false,
+            None => true,
+        };
+
+        Ok((channel, snapshot))
+    }
+
+    ///
+    /// Sends a request and waits for its response, which is matched by `reqid`.
+    ///
+    /// Events arriving in the meantime are kept for `next_event`, as are errors of the feed, so only
+    /// an error response, a lost connection or no response within `request_timeout` fails the request.
+    /// The deadline is checked as messages arrive, which kraken's heartbeats do every second.
+    ///
+    /// Requests are not repeated after a lost connection, as they may have been executed,
+    /// nor is the connection restored: that is left to `next_event`.
+    ///
+    fn request(&mut self, event: &str, mut fields: Map<String, Value>) -> Result<Value, String> {
+        self.reqid += 1;
+
+        let reqid = self.reqid;
+        let status = format!("{}Status", event);
+        let deadline = Instant::now() + self.request_timeout;
+
+        fields.insert(String::from("event"), Value::from(event));
+        fields.insert(String::from("token"), Value::from(self.token.clone()));
+        fields.insert(String::from("reqid"), Value::from(reqid));
+
+        self.connection.send(&fields)?;
+
+        loop {
+            if Instant::now() >= deadline {
+                return Err(format!("no response to {} {} within {:?}", event, reqid, self.request_timeout));
+            }
+
+            let message = match self.connection.read() {
+                Ok(message) => message,
+                Err(e) if self.connection.is_connected() => {
+                    self.pending.push_back(Err(e));
+                    continue;
+                }
+                Err(e) => return Err(e),
+            };
+
+            // kraken answers requests it can't handle with a generic error event
+            let name = message.get("event").and_then(Value::as_str);
+            let is_response = (name == Some(status.as_str()) || name == Some("error"))
+                && message.get("reqid").and_then(Value::as_u64) == Some(reqid);
+
+            if !is_response {
+                if let Err(e) = self.queue(&message) {
+                    self.pending.push_back(Err(e));
+                }
+
+                continue;
+            }
+
+            return match (name, message.get("status").and_then(Value::as_str)) {
+                (Some("error"), _) | (_, Some("error")) => Err(
+                    message
+                        .get("errorMessage")
+                        .and_then(Value::as_str)
+                        .unwrap_or("unknown error")
+                        .to_owned(),
+                ),
+                _ => Ok(message),
+            };
+        }
+    }
+
+    ///
+    /// Places a new order, see `kraken::add_order`.
+    ///
+    /// # Note
+    ///
+    /// The pair has to be given in the websocket form, e.g. "XBT/USD".
+    /// Kraken doesn't describe the close order here, `descr.close` is built from the order sent.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// extern crate kraken;
+    ///
+    /// use kraken::{Account, NewOrder, OrderType, PrivateClient, TradeType};
+    ///
+    /// fn main() {
+    ///   let account = Account { key: String::from("<key>"), secret: String::from("<secret>") };
+    ///   let pairs = kraken::asset_pairs().expect("could not get asset pairs");
+    ///   let mut client = PrivateClient::connect(&account).expect("could not connect");
+    ///
+    ///   let order = NewOrder::builder("XBT/USD", &pairs["XXBTZUSD"], TradeType::Buy, OrderType::Limit)
+    ///     .price("27500.0")
+    ///     .volume("0.01")
+    ///     .build()
+    ///     .unwrap();
+    ///
+    ///   let result = client.add_order(order).expect("order not placed");
+    ///
+    ///   client.cancel_order(&[&result.txid[0]]).expect("order not canceled");
+    /// }
+    /// ```
+    ///
+    pub fn add_order(&mut self, order: NewOrder) -> Result<AddOrderResult, String> {
+        let close = order.close.as_ref().map(|close| close.to_string());
+        let fields = order_params(order)?
+            .into_iter()
+            .map(|(key, value)| {
+                let value = match key.as_str() {
+                    "reduce_only" => Value::from(value == "true"),
+                    "validate" => Value::from("true"),
+                    _ => Value::from(value),
+                };
+
+                (key, value)
+            })
+            .collect();
+
+        let response = self.request("addOrder", fields)?;
+
+        Ok(AddOrderResult {
+            descr: AddOrderDescription {
+                order: text(&response, "descr").unwrap_or_default(),
+                close,
+            },
+            txid: text(&response, "txid").into_iter().collect(),
+        })
+    }
+
+    ///
+    /// Edits an open order, see `kraken::edit_order`.
+    ///
+    /// # Note
+    ///
+    /// Only `txid`, `descr` and `originaltxid` of the result are set.
+    ///
+    pub fn edit_order(&mut self, edit: EditOrder) -> Result<EditOrderResult, String> {
+        let fields = edit_params(edit)
+            .into_iter()
+            .map(|(key, value)| {
+                let key = match key.as_str() {
+                    "txid" => String::from("orderid"),
+                    "userref" => String::from("newuserref"),
+                    _ => key,
+                };
+
+                (key, Value::from(value))
+            })
+            .collect();
+
+        let response = self.request("editOrder", fields)?;
+
+        Ok(EditOrderResult {
+            descr: text(&response, "descr").map(|order| EditOrderDescription { order }),
+            txid: text(&response, "txid"),
+            originaltxid: text(&response, "originaltxid").unwrap_or_default(),
+            volume: None,
+            price: None,
+            price2: None,
+            orders_cancelled: None,
+            status: text(&response, "status").unwrap_or_default(),
+            error_message: None,
+        })
+    }
+
+    ///
+    /// Cancels orders by transaction or user reference id.
+    ///
+    /// # Note
+    ///
+    /// Kraken only confirms the request, without the number of orders canceled.
+    /// A user reference id may cancel several orders.
+    ///
+    pub fn cancel_order(&mut self, txids: &[&str]) -> Result<(), String> {
+        let mut fields = Map::new();
+
+        fields.insert(String::from("txid"), Value::from(txids.to_vec()));
+
+        self.request("cancelOrder", fields).map(|_| ())
+    }
+
+    /// Cancels all open orders.
+    pub fn cancel_all(&mut self) -> Result<CanceldOrders, String> {
+        let response = self.request("cancelAll", Map::new())?;
+
+        serde_json::from_value(response).map_err(|e| format!("{:?}", e))
+    }
+
+    ///
+    /// Dead man's switch, see `kraken::cancel_all_orders_after`.
+    ///
+    /// # Arguments
+    ///
+    /// + `timeout` - duration in seconds until all orders are canceled, `0` disables the timer.
+    ///
+    pub fn cancel_all_orders_after(&mut self, timeout: u32) -> Result<CancelAllOrdersAfter, String> {
+        let mut fields = Map::new();
+
+        fields.insert(String::from("timeout"), Value::from(timeout));
+
+        let response = self.request("cancelAllOrdersAfter", fields)?;
+
+        serde_json::from_value(response).map_err(|e| format!("{:?}", e))
+    }
+}
+
+fn text(message: &Value, key: &str) -> Option<String> {
+    message.get(key).and_then(Value::as_str).map(String::from)
+}
+
+impl Iterator for PrivateClient {
+    type Item = Result<PrivateEvent, String>;
+
+    /// Never ends, errors are returned and the next call reconnects if needed.
+    fn next(&mut self) -> Option<Result<PrivateEvent, String>> {
+        Some(self.next_event())
+    }
+}
+
+fn decode_event(event: &str, message: &Value) -> Option<PrivateEvent> {
+    let text = |key: &str| message.get(key).and_then(Value::as_str).map(String::from);
+
+    match event {
+        "systemStatus" => Some(PrivateEvent::SystemStatus { status: text("status").unwrap_or_default() }),
+        "subscriptionStatus" => {
+            let channel = text("channelName").unwrap_or_default();
+
+            match message.get("status").and_then(Value::as_str) {
+                Some("subscribed") => Some(PrivateEvent::Subscribed { channel }),
+                Some("unsubscribed") => Some(PrivateEvent::Unsubscribed { channel }),
+                Some("error") => Some(PrivateEvent::SubscriptionError {
+                    message: text("errorMessage").unwrap_or_default(),
+                }),
+                _ => None,
+            }
+        }
+        "error" => Some(PrivateEvent::Error { message: text("errorMessage").unwrap_or_default() }),
+        // heartbeat, pong
+        _ => None,
+    }
+}
+
+/// The trades or orders of a channel message, keyed by their txid.
+fn entries(message: &Value) -> Result<Vec<(String, Value)>, String> {
+    let mut entries = Vec::new();
+
+    for entry in value_array(&message[0])? {
+        if let Value::Object(ref entry) = *entry {
+            for (txid, value) in entry {
+                entries.push((txid.clone(), value.clone()));
+            }
+        }
+    }
+
+    Ok(entries)
+}
+
+fn decode_entry(channel: PrivateChannel, txid: &str, value: Value, snapshot: bool) -> Result<PrivateEvent, String> {
+    let txid = String::from(txid);
+    let mut fields = match value {
+        Value::Object(fields) => fields,
+        other => return Err(format!("expected object, got {}", other)),
+    };
+
+    match channel {
+        PrivateChannel::OwnTrades => {
+            timestamps_to_f64(&mut fields, &["time"])?;
+
+            let trade = serde_json::from_value(Value::Object(fields)).map_err(|e| format!("{:?}", e))?;
+
+            Ok(PrivateEvent::Trade {
+                txid,
+                trade: Box::new(trade),
+                snapshot,
+            })
+        }
+        // only new orders come with their description, later messages carry the changed values
+        PrivateChannel::OpenOrders if fields.contains_key("descr") => {
+            if !fields.contains_key("price") {
+                let price = fields.get("avg_price").cloned().unwrap_or_else(|| Value::from("0"));
+                fields.insert(String::from("price"), price);
+            }
+
+            // not set, as for the REST API
+            for key in &["starttm", "expiretm"] {
+                if fields.get(*key).is_none_or(Value::is_null) {
+                    fields.insert(String::from(*key), Value::from(0));
+                }
+            }
+
+            timestamps_to_f64(&mut fields, &["opentm", "starttm", "expiretm", "closetm"])?;
+
+            let order = serde_json::from_value(Value::Object(fields)).map_err(|e| format!("{:?}", e))?;
+
+            Ok(PrivateEvent::OrderOpened {
+                txid,
+                order: Box::new(order),
+                snapshot,
+            })
+        }
+        PrivateChannel::OpenOrders => {
+            let decimal = |key: &str| -> Result<Option<Decimal>, String> {
+                match fields.get(key) {
+                    Some(value) if !value.is_null() => value_string(value)?.parse().map(Some),
+                    _ => Ok(None),
+                }
+            };
+
+            let change = OrderChange {
+                status: match fields.get("status") {
+                    Some(status) => Some(serde_json::from_value(status.clone()).map_err(|e| format!("{:?}", e))?),
+                    None => None,
+                },
+                vol_exec: decimal("vol_exec")?,
+                cost: decimal("cost")?,
+                fee: decimal("fee")?,
+                avg_price: decimal("avg_price")?,
+                reason: fields.get("cancel_reason").and_then(Value::as_str).map(String::from),
+            };
+
+            Ok(PrivateEvent::OrderChanged {
+                txid,
+                change: Box::new(change),
+            })
+        }
+    }
+}
+
+/// The feeds send timestamps as strings, the REST types expect numbers.
+fn timestamps_to_f64(fields: &mut Map<String, Value>, keys: &[&str]) -> Result<(), String> {
+    for key in keys {
+        let time = match fields.get(*key) {
+            Some(value) if !value.is_null() => value_f64(value)?,
+            _ => continue,
+        };
+
+        fields.insert(String::from(*key), Value::from(time));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::{CloseOrder, OrderFlag, OrderType, TradeType};
+    use super::*;
+    use std::net::TcpListener;
+    use std::thread;
+    use std::time::{Duration, UNIX_EPOCH};
+    use tungstenite::{self, Message};
+
+    fn fixture(name: &str) -> Value {
+        let fixtures: Value = serde_json::from_str(include_str!("../../tests/fixtures/ws_private.json")).unwrap();
+
+        fixtures[name].clone()
+    }
+
+    fn client() -> PrivateClient {
+        PrivateClient {
+            connection: Connection::new(URL),
+            account: Account {
+                key: String::from("key"),
+                secret: String::from("secret"),
+            },
+            token: String::from("token"),
+            subscriptions: Vec::new(),
+            sequences: HashMap::new(),
+            pending: VecDeque::new(),
+            reqid: 0,
+            resync: false,
+            request_timeout: REQUEST_TIMEOUT,
+        }
+    }
+
+    fn with_sequence(mut message: Value, sequence: u64) -> Value {
+        message[2]["sequence"] = Value::from(sequence);
+        message
+    }
+
+    #[test]
+    fn own_trades() {
+        let mut client = client();
+
+        client.queue(&fixture("own_trades")).unwrap();
+
+        let events = client.pending.drain(..).collect::<Vec<_>>();
+
+        assert_eq!(events.len(), 4);
+
+        match events[0] {
+            Ok(PrivateEvent::Trade { ref txid, ref trade, snapshot }) => {
+                assert_eq!(txid, "TDLH43-DVQXD-2KHVYY");
+                assert!(snapshot);
+                assert_eq!(trade.ordertxid, "TDLH43-DVQXD-2KHVYY");
+                assert_eq!(trade.postxid.as_deref(), Some("OGTT3Y-C6I3P-XRI6HX"));
+                assert_eq!(trade.pair, "XBT/EUR");
+                assert_eq!(trade.time(), UNIX_EPOCH + Duration::from_micros(1560516023070651));
+                assert_eq!(trade.kind, TradeType::Sell);
+                assert_eq!(trade.ordertype, OrderType::Limit);
+                assert_eq!(trade.price.to_string(), "100000.00000");
+                assert_eq!(trade.fee.to_string(), "1600.00000");
+                assert_eq!(trade.margin, Some(Decimal::zero()));
+                assert_eq!(trade.misc, "");
+            }
+            ref other => panic!("unexpected event: {:?}", other),
+        }
+
+        // omitted fields and unknown order types
+        match events[1] {
+            Ok(PrivateEvent::Trade { ref trade, .. }) => {
+                assert_eq!(trade.ordertype, OrderType::Other(String::from("iceberg")));
+                assert_eq!(trade.postxid, None);
+                assert_eq!(trade.margin, None);
+                assert_eq!(trade.userref.as_deref(), Some("42"));
+            }
+            ref other => panic!("unexpected event: {:?}", other),
+        }
+
+        // an invalid trade is an error in its place, the rest of the batch is kept
+        match events[2] {
+            Err(ref e) => assert!(e.starts_with("ownTrades TDLH43-DVQXD-2KHVZA: "), "{}", e),
+            ref other => panic!("unexpected event: {:?}", other),
+        }
+
+        match events[3] {
+            Ok(PrivateEvent::Trade { ref txid, .. }) => assert_eq!(txid, "TDLH43-DVQXD-2KHVZB"),
+            ref other => panic!("unexpected event: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn open_orders() {
+        let mut client = client();
+
+        client.queue(&fixture("open_orders")).unwrap();
+        client.queue(&fixture("order_changes")).unwrap();
+
+        let events = client.pending.drain(..).collect::<Vec<_>>();
+
+        assert_eq!(events.len(), 4);
+
+        match events[0] {
+            Ok(PrivateEvent::OrderOpened { ref txid, ref order, snapshot }) => {
+                assert_eq!(txid, "OGTT3Y-C6I3P-XRI6HX");
+                assert!(snapshot);
+                assert_eq!(order.status, OrderStatus::Open);
+                assert_eq!(order.price, "34.50000");
+                assert_eq!(order.descr.ordertype, OrderType::Limit);
+                assert_eq!(order.descr.parsed().unwrap().leverage, Some(0));
+                assert_eq!(order.opentm(), UNIX_EPOCH + Duration::from_micros(1560516023070651));
+                assert_eq!(order.starttm(), None);
+                assert_eq!(order.expiretm(), None);
+            }
+            ref other => panic!("unexpected event: {:?}", other),
+        }
+
+        // start and expire time may be null
+        match events[1] {
+            Ok(PrivateEvent::OrderOpened { ref order, .. }) => {
+                assert_eq!(order.status, OrderStatus::Pending);
+                assert_eq!(order.price, "0.00000");
+                assert!(order.oflags.contains(&OrderFlag::Post));
+                assert_eq!(order.starttm(), None);
+                assert_eq!(order.expiretm(), None);
+            }
+            ref other => panic!("unexpected event: {:?}", other),
+        }
+
+        match events[2] {
+            Ok(PrivateEvent::OrderChanged { ref txid, ref change }) => {
+                assert_eq!(txid, "OGTT3Y-C6I3P-XRI6HX");
+                assert_eq!(change.status, None);
+                assert_eq!(change.vol_exec.unwrap().to_string(), "5.00000000");
+                assert_eq!(change.cost.unwrap().to_string(), "172.50000");
+                assert_eq!(change.fee.unwrap().to_string(), "0.44850");
+                assert_eq!(change.avg_price.unwrap().to_string(), "34.50000");
+            }
+            ref other => panic!("unexpected event: {:?}", other),
+        }
+
+        match events[3] {
+            Ok(PrivateEvent::OrderChanged { ref change, .. }) => {
+                assert_eq!(change.status, Some(OrderStatus::Canceled));
+                assert_eq!(change.reason.as_deref(), Some("User requested"));
+                assert_eq!(change.vol_exec, None);
+            }
+            ref other => panic!("unexpected event: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn sequence_gap() {
+        let mut client = client();
+        let trades = fixture("own_trades");
+
+        client.queue(&with_sequence(trades.clone(), 10)).unwrap();
+        client.queue(&with_sequence(trades.clone(), 11)).unwrap();
+
+        // the sequences are counted per channel
+        client.queue(&with_sequence(fixture("open_orders"), 3)).unwrap();
+        client.queue(&with_sequence(fixture("order_changes"), 4)).unwrap();
+
+        let error = client.queue(&with_sequence(trades.clone(), 13)).unwrap_err();
+
+        assert_eq!(error, "ownTrades sequence gap: 13 after 11");
+
+        let snapshots = client
+            .pending
+            .iter()
+            .filter_map(|event| match *event {
+                Ok(PrivateEvent::Trade { snapshot, .. }) => Some(snapshot),
+                _ => None,
+            })
+            .collect::<Vec<bool>>();
+
+        assert_eq!(snapshots, vec![true, true, true, false, false, false]);
+    }
+
+    #[test]
+    fn invalid_messages() {
+        let mut client = client();
+
+        assert!(client.queue(&serde_json::json!([[], "ownTrades"])).is_err());
+        assert!(client.queue(&serde_json::json!([[], "unknown", {"sequence": 1}])).is_err());
+        assert!(client.queue(&serde_json::json!([[], "ownTrades", {}])).is_err());
+        assert!(client.pending.is_empty());
+    }
+
+    #[test]
+    fn events() {
+        let mut client = client();
+
+        client.queue(&serde_json::json!({"event": "heartbeat"})).unwrap();
+        client
+            .queue(&serde_json::json!({
+                "event": "subscriptionStatus",
+                "channelName": "ownTrades",
+                "status": "error",
+                "errorMessage": "EGeneral:Invalid token"
+            }))
+            .unwrap();
+
+        match client.pending.pop_front() {
+            Some(Ok(PrivateEvent::SubscriptionError { message })) => assert_eq!(message, "EGeneral:Invalid token"),
+            other => panic!("unexpected event: {:?}", other),
+        }
+
+        assert!(client.pending.is_empty());
+    }
+
+    ///
+    /// Connects a client to a local server, which answers the first request with `replies`.
+    ///
+    /// The server checks the request with `check` before replying, `{reqid}` in the replies is
+    /// replaced by the id of the request.
+    ///
+    fn serve<F>(replies: Vec<Value>, check: F) -> (PrivateClient, thread::JoinHandle<()>)
+    where
+        F: FnOnce(&Value) + Send + 'static,
+    {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+
+        let server = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut socket = tungstenite::accept(stream).unwrap();
+
+            let request: Value = match socket.read().unwrap() {
+                Message::Text(text) => serde_json::from_str(&text).unwrap(),
+                other => panic!("unexpected message: {:?}", other),
+            };
+
+            check(&request);
+
+            for reply in replies {
+                let text = match reply {
+                    Value::String(text) => text,
+                    reply => reply.to_string().replace("\"{reqid}\"", &request["reqid"].to_string()),
+                };
+
+                socket.send(Message::Text(text)).unwrap();
+            }
+
+            // waits for the client to hang up
+            while socket.read().is_ok() {}
+        });
+
+        let mut client = client();
+
+        client.connection = Connection::new(&url);
+        client.connection.connect().unwrap();
+
+        (client, server)
+    }
+
+    fn order() -> NewOrder {
+        NewOrder {
+            pair: String::from("XBT/USD"),
+            kind: TradeType::Buy,
+            order_type: OrderType::Limit,
+            price: Some(String::from("27500.0")),
+            price2: None,
+            volume: String::from("0.01"),
+            leverage: Some(String::from("2")),
+            oflags: None,
+            timeinforce: None,
+            starttm: None,
+            expiretm: None,
+            userref: None,
+            validate: None,
+            close: Some(CloseOrder {
+                order_type: OrderType::StopLossLimit,
+                price: String::from("22000.0"),
+                price2: Some(String::from("21000.0")),
+            }),
+            reduce_only: Some(true),
+            cl_ord_id: None,
+            trigger: None,
+            stptype: None,
+        }
+    }
+
+    #[test]
+    fn request_waits_for_its_response() {
+        let replies = vec![
+            serde_json::json!({"event": "heartbeat"}),
+            with_sequence(fixture("own_trades"), 1),
+            // the response to another request
+            serde_json::json!({"event": "addOrderStatus", "reqid": 99, "status": "error", "errorMessage": "EOrder:Unknown"}),
+            Value::from("not json"),
+            with_sequence(fixture("own_trades"), 3),
+            serde_json::json!({
+                "event": "addOrderStatus",
+                "reqid": "{reqid}",
+                "status": "ok",
+                "txid": "OUF4EM-FRGI2-MQMWZD",
+                "descr": "buy 0.01000000 XBTUSD @ limit 27500.0 with 2:1 leverage"
+            }),
+        ];
+
+        let (mut client, server) = serve(replies, |request| {
+            assert_eq!(request["event"], "addOrder");
+            assert_eq!(request["token"], "token");
+            assert_eq!(request["reqid"], 1);
+            assert_eq!(request["pair"], "XBT/USD");
+            assert_eq!(request["ordertype"], "limit");
+            assert_eq!(request["price"], "27500.0");
+            assert_eq!(request["leverage"], "2");
+            assert_eq!(request["reduce_only"], true);
+            assert_eq!(request["close[ordertype]"], "stop-loss-limit");
+            assert_eq!(request["close[price]"], "22000.0");
+            assert_eq!(request["close[price2]"], "21000.0");
+        });
+
+        let result = client.add_order(order()).unwrap();
+
+        assert_eq!(result.txid, vec!["OUF4EM-FRGI2-MQMWZD"]);
+        assert_eq!(result.descr.order, "buy 0.01000000 XBTUSD @ limit 27500.0 with 2:1 leverage");
+        assert_eq!(
+            result.descr.close.as_deref(),
+            Some("close position @ stop loss 22000.0 -> limit 21000.0")
+        );
+        assert_eq!(result.descr.parsed().unwrap().leverage, Some(2));
+
+        // the feed messages and errors received meanwhile are kept in order
+        let events = (0..6).map(|_| client.next_event()).collect::<Vec<_>>();
+
+        assert!(matches!(events[0], Ok(PrivateEvent::Trade { snapshot: true, .. })));
+        assert!(matches!(events[1], Ok(PrivateEvent::Trade { .. })));
+        assert!(events[2].as_ref().unwrap_err().starts_with("ownTrades TDLH43-DVQXD-2KHVZA: "));
+        assert!(matches!(events[3], Ok(PrivateEvent::Trade { .. })));
+        assert!(events[4].is_err());
+        assert_eq!(events[5].as_ref().unwrap_err(), "ownTrades sequence gap: 3 after 1");
+        assert!(client.pending.is_empty());
+
+        // the gap drops the connection on the next call, after the pending events
+        assert!(client.resync);
+        assert!(client.connection.is_connected());
+
+        client.connection.disconnect();
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn request_error() {
+        let replies = vec![
+            serde_json::json!({"event": "cancelOrderStatus", "reqid": "{reqid}", "status": "error", "errorMessage": "EOrder:Unknown order"}),
+        ];
+
+        let (mut client, server) = serve(replies, |request| {
+            assert_eq!(request["event"], "cancelOrder");
+            assert_eq!(request["txid"], serde_json::json!(["OUF4EM-FRGI2-MQMWZD"]));
+        });
+
+        assert_eq!(
+            client.cancel_order(&["OUF4EM-FRGI2-MQMWZD"]).unwrap_err(),
+            "EOrder:Unknown order"
+        );
+
+        client.connection.disconnect();
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn generic_error_response() {
+        let replies = vec![
+            // a generic error of another request
+            serde_json::json!({"event": "error", "reqid": 99, "errorMessage": "EGeneral:Invalid arguments"}),
+            serde_json::json!({"event": "error", "reqid": "{reqid}", "errorMessage": "Unsupported event"}),
+        ];
+
+        let (mut client, server) = serve(replies, |_| {});
+
+        assert_eq!(client.cancel_all().unwrap_err(), "Unsupported event");
+
+        match client.next_event() {
+            Ok(PrivateEvent::Error { message }) => assert_eq!(message, "EGeneral:Invalid arguments"),
+            other => panic!("unexpected event: {:?}", other),
+        }
+
+        client.connection.disconnect();
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn request_times_out() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+
+        let server = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut socket = tungstenite::accept(stream).unwrap();
+
+            socket.read().unwrap();
+
+            // heartbeats, but never a response
+            while socket.send(Message::Text(String::from(r#"{"event":"heartbeat"}"#))).is_ok() {
+                thread::sleep(Duration::from_millis(20));
+            }
+        });
+
+        let mut client = client();
+
+        client.connection = Connection::new(&url);
+        client.connection.connect().unwrap();
+        client.request_timeout = Duration::from_millis(200);
+
+        let started = Instant::now();
+
+        assert_eq!(
+            client.cancel_order(&["OUF4EM-FRGI2-MQMWZD"]).unwrap_err(),
+            "no response to cancelOrder 1 within 200ms"
+        );
+        assert!(started.elapsed() < Duration::from_secs(5));
+        assert!(client.connection.is_connected());
+
+        client.connection.disconnect();
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn cancel_order() {
+        let replies = vec![serde_json::json!({"event": "cancelOrderStatus", "reqid": "{reqid}", "status": "ok"})];
+
+        let (mut client, server) = serve(replies, |request| {
+            assert_eq!(request["txid"], serde_json::json!(["OUF4EM-FRGI2-MQMWZD", "OB5VMB-B4U2U-DK2WRW"]));
+        });
+
+        assert_eq!(client.cancel_order(&["OUF4EM-FRGI2-MQMWZD", "OB5VMB-B4U2U-DK2WRW"]), Ok(()));
+
+        client.connection.disconnect();
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn edit_order() {
+        let replies = vec![serde_json::json!({
+            "event": "editOrderStatus",
+            "reqid": "{reqid}",
+            "status": "ok",
+            "txid": "OFVXHJ-KPQ3B-VS7ELA",
+            "originaltxid": "OHYO67-6LP66-HMQ437",
+            "descr": "order edited price = 9000.00000000"
+        })];
+
+        let (mut client, server) = serve(replies, |request| {
+            assert_eq!(request["event"], "editOrder");
+            assert_eq!(request["orderid"], "OHYO67-6LP66-HMQ437");
+            assert_eq!(request["pair"], "XBT/USD");
+            assert_eq!(request["price"], "9000.0");
+            assert_eq!(request["newuserref"], "42");
+            assert!(request.get("txid").is_none());
+            assert!(request.get("userref").is_none());
+        });
+
+        let result = client
+            .edit_order(EditOrder {
+                txid: String::from("OHYO67-6LP66-HMQ437"),
+                pair: String::from("XBT/USD"),
+                volume: None,
+                price: Some(String::from("9000.0")),
+                price2: None,
+                oflags: None,
+                userref: Some(String::from("42")),
+                validate: None,
+            })
+            .unwrap();
+
+        assert_eq!(result.txid.as_deref(), Some("OFVXHJ-KPQ3B-VS7ELA"));
+        assert_eq!(result.originaltxid, "OHYO67-6LP66-HMQ437");
+        assert_eq!(result.descr.unwrap().order, "order edited price = 9000.00000000");
+        assert_eq!(result.status, "ok");
+
+        client.connection.disconnect();
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn failed_edit() {
+        let replies = vec![serde_json::json!({
+            "event": "editOrderStatus",
+            "reqid": "{reqid}",
+            "status": "error",
+            "errorMessage": "EOrder:Invalid order"
+        })];
+
+        let (mut client, server) = serve(replies, |_| {});
+
+        let edit = EditOrder {
+            txid: String::from("OHYO67-6LP66-HMQ437"),
+            pair: String::from("XBT/USD"),
+            volume: Some(String::from("1.0")),
+            price: None,
+            price2: None,
+            oflags: None,
+            userref: None,
+            validate: None,
+        };
+
+        assert_eq!(client.edit_order(edit).unwrap_err(), "EOrder:Invalid order");
+
+        client.connection.disconnect();
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn request_fails_on_lost_connection() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+
+        let server = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut socket = tungstenite::accept(stream).unwrap();
+
+            socket.read().unwrap();
+            socket.close(None).unwrap();
+            while socket.read().is_ok() {}
+        });
+
+        let mut client = client();
+
+        client.connection = Connection::new(&url);
+        client.connection.connect().unwrap();
+
+        assert!(client.cancel_all().is_err());
+        assert!(!client.connection.is_connected());
+
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn requests_are_numbered() {
+        let mut client = client();
+
+        client.reqid = 41;
+
+        // not connected, but the id is taken
+        assert!(client.cancel_all_orders_after(60).is_err());
+        assert_eq!(client.reqid, 42);
+    }
+}
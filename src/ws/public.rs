@@ -0,0 +1,627 @@
+//!
+//! Public market data feed.
+//!
+
+use super::super::{decode_trade, value_array, value_f64, value_string, Decimal, DepthPair, DepthPairTuple,
+                   OhlcFrame, Spread, Tick, Trade};
+use super::{Connection, SubscribeMessage, SubscriptionDetails};
+use serde_json::Value;
+
+const URL: &str = "wss://ws.kraken.com";
+
+/// Channel of the public feed
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Channel {
+    Ticker,
+    /// frames of the given interval in minutes: 1, 5, 15, 30, 60, 240, 1440, 10080, 21600
+    Ohlc(u32),
+    Trade,
+    Spread,
+    /// order book of the given depth: 10, 25, 100, 500, 1000
+    Book(u32),
+}
+
+impl Channel {
+    fn details(&self) -> SubscriptionDetails {
+        let (name, interval, depth) = match *self {
+            Channel::Ticker => ("ticker", None, None),
+            Channel::Ohlc(interval) => ("ohlc", Some(interval), None),
+            Channel::Trade => ("trade", None, None),
+            Channel::Spread => ("spread", None, None),
+            Channel::Book(depth) => ("book", None, Some(depth)),
+        };
+
+        SubscriptionDetails {
+            name,
+            interval,
+            depth,
+            token: None,
+        }
+    }
+}
+
+/// A channel subscribed for a set of pairs
+#[derive(Debug, Clone, PartialEq)]
+pub struct Subscription {
+    pub channel: Channel,
+    /// pair names in the websocket form, e.g. "XBT/USD"
+    pub pairs: Vec<String>,
+}
+
+impl Subscription {
+    pub fn new(channel: Channel, pairs: &[&str]) -> Subscription {
+        Subscription {
+            channel,
+            pairs: pairs.iter().map(|pair| String::from(*pair)).collect(),
+        }
+    }
+}
+
+/// Event of the public feed
+#[derive(Debug)]
+pub enum PublicEvent {
+    Ticker { pair: String, tick: Tick },
+    /// update of the current frame, `frame.time` is the start of the frame
+    Ohlc {
+        pair: String,
+        interval: u32,
+        frame: OhlcFrame,
+    },
+    Trades { pair: String, trades: Vec<Trade> },
+    Spread { pair: String, spread: Spread },
+    /// complete book up to `depth` levels, replaces all levels seen before
+    BookSnapshot {
+        pair: String,
+        depth: u32,
+        book: DepthPair,
+    },
+    /// changed levels, a volume of zero removes the level.  `checksum` is the CRC32 of the resulting book
+    BookUpdate {
+        pair: String,
+        depth: u32,
+        book: DepthPair,
+        checksum: Option<u32>,
+    },
+    Subscribed {
+        pair: Option<String>,
+        channel: String,
+    },
+    Unsubscribed {
+        pair: Option<String>,
+        channel: String,
+    },
+    /// the subscription was rejected and is not renewed after reconnects
+    SubscriptionError {
+        pair: Option<String>,
+        channel: Option<Channel>,
+        message: String,
+    },
+    /// error message sent by kraken, not related to a subscription
+    Error { message: String },
+    /// status of the exchange: online, maintenance, cancel_only, limit_only or post_only
+    SystemStatus { status: String },
+    /// the connection was lost and is established again, all subscriptions are renewed
+    Reconnected,
+}
+
+///
+/// Client of the public websocket feed.
+///
+/// Iterating yields the decoded events.  Heartbeats are consumed, and if the connection is lost
+/// or stays silent it is established again on the next call, which yields `PublicEvent::Reconnected`.
+/// Errors are yielded as well, iterating further retries with a growing backoff.  Subscriptions kraken
+/// rejects are not renewed.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// extern crate kraken;
+///
+/// use kraken::{Channel, PublicClient, PublicEvent, Subscription};
+///
+/// fn main() {
+///   let mut client = PublicClient::connect().expect("could not connect");
+///
+///   client.subscribe(Subscription::new(Channel::Ticker, &["XBT/USD", "ETH/EUR"])).unwrap();
+///   client.subscribe(Subscription::new(Channel::Ohlc(5), &["XBT/USD"])).unwrap();
+///
+///   for event in client {
+///     match event {
+///       Ok(PublicEvent::Ticker { pair, tick }) => println!("{}: {:?}", pair, tick.last_price()),
+///       Ok(other) => println!("{:?}", other),
+///       Err(e) => println!("feed error: {}", e),
+///     }
+///   }
+/// }
+/// ```
+///
+#[derive(Debug)]
+pub struct PublicClient {
+    connection: Connection,
+    subscriptions: Vec<Subscription>,
+}
+
+impl PublicClient {
+    pub fn connect() -> Result<PublicClient, String> {
+        let mut connection = Connection::new(URL);
+
+        connection.connect()?;
+
+        Ok(PublicClient {
+            connection,
+            subscriptions: Vec::new(),
+        })
+    }
+
+    /// Subscribes to a channel, the subscription is renewed after reconnects.
+    pub fn subscribe(&mut self, subscription: Subscription) -> Result<(), String> {
+        self.send("subscribe", &subscription)?;
+        self.subscriptions.push(subscription);
+
+        Ok(())
+    }
+
+    pub fn unsubscribe(&mut self, subscription: &Subscription) -> Result<(), String> {
+        self.subscriptions.retain(|s| s != subscription);
+        self.send("unsubscribe", subscription)
+    }
+
+    ///
+    /// Unsubscribes and subscribes again, without changing the subscriptions renewed after reconnects.
+    ///
+    /// Kraken sends a new snapshot for a resubscribed book.
+    ///
+    pub fn resubscribe(&mut self, subscription: &Subscription) -> Result<(), String> {
+        self.send("unsubscribe", subscription)?;
+        self.send("subscribe", subscription)
+    }
+
+    pub fn subscriptions(&self) -> &[Subscription] {
+        &self.subscriptions
+    }
+
+    /// Drops a rejected subscription of `pair`, or of all pairs, from the subscriptions renewed after reconnects.
+    fn forget(&mut self, channel: Channel, pair: Option<&str>) {
+        for subscription in self.subscriptions.iter_mut().filter(|s| s.channel == channel) {
+            match pair {
+                Some(pair) => subscription.pairs.retain(|p| p != pair),
+                None => subscription.pairs.clear(),
+            }
+        }
+
+        self.subscriptions.retain(|s| !s.pairs.is_empty());
+    }
+
+    fn send(&mut self, event: &'static str, subscription: &Subscription) -> Result<(), String> {
+        self.connection.send(&SubscribeMessage {
+            event,
+            pair: Some(&subscription.pairs),
+            subscription: subscription.channel.details(),
+        })
+    }
+
+    /// Waits for the next event, reconnecting first if the connection was lost.
+    pub fn next_event(&mut self) -> Result<PublicEvent, String> {
+        if !self.connection.is_connected() {
+            self.connection.reconnect()?;
+
+            for subscription in self.subscriptions.clone() {
+                self.send("subscribe", &subscription)?;
+            }
+
+            return Ok(PublicEvent::Reconnected);
+        }
+
+        loop {
+            let message = self.connection.read()?;
+
+            if let Some(event) = decode(&message)? {
+                if let PublicEvent::SubscriptionError { ref pair, channel: Some(channel), .. } = event {
+                    self.forget(channel, pair.as_ref().map(String::as_str));
+                }
+
+                return Ok(event);
+            }
+        }
+    }
+}
+
+impl Iterator for PublicClient {
+    type Item = Result<PublicEvent, String>;
+
+    fn next(&mut self) -> Option<Result<PublicEvent, String>> {
+        Some(self.next_event())
+    }
+}
+
+fn decode(message: &Value) -> Result<Option<PublicEvent>, String> {
+    if let Some(event) = message.get("event").and_then(Value::as_str) {
+        return Ok(decode_event(event, message));
+    }
+
+    let values = value_array(message)?;
+
+    if values.len() < 4 {
+        return Err(format!("unexpected message: {}", message));
+    }
+
+    let pair = value_string(&values[values.len() - 1])?;
+    let name = values[values.len() - 2].as_str().ok_or_else(|| {
+        format!("unexpected message: {}", message)
+    })?;
+    let payloads = &values[1..values.len() - 2];
+
+    let mut parts = name.splitn(2, '-');
+    let channel = parts.next().unwrap_or("");
+    let argument = parts.next().and_then(|argument| argument.parse::<u32>().ok());
+
+    let event = match (channel, argument) {
+        ("ticker", _) => PublicEvent::Ticker {
+            pair,
+            tick: decode_tick(&payloads[0])?,
+        },
+        ("ohlc", Some(interval)) => PublicEvent::Ohlc {
+            pair,
+            interval,
+            frame: decode_ohlc(&payloads[0], interval)?,
+        },
+        ("trade", _) => PublicEvent::Trades {
+            pair,
+            trades: value_array(&payloads[0])?
+                .iter()
+                .map(decode_trade)
+                .collect::<Result<Vec<Trade>, String>>()?,
+        },
+        ("spread", _) => PublicEvent::Spread {
+            pair,
+            spread: decode_spread(&payloads[0])?,
+        },
+        ("book", Some(depth)) => decode_book(pair, depth, payloads)?,
+        _ => return Err(format!("unknown channel: {}", name)),
+    };
+
+    Ok(Some(event))
+}
+
+fn decode_event(event: &str, message: &Value) -> Option<PublicEvent> {
+    let text = |key: &str| message.get(key).and_then(Value::as_str).map(String::from);
+
+    match event {
+        "systemStatus" => Some(PublicEvent::SystemStatus { status: text("status").unwrap_or_default() }),
+        "subscriptionStatus" => {
+            let pair = text("pair");
+            let channel = text("channelName").unwrap_or_default();
+
+            match message.get("status").and_then(Value::as_str) {
+                Some("subscribed") => Some(PublicEvent::Subscribed { pair, channel }),
+                Some("unsubscribed") => Some(PublicEvent::Unsubscribed { pair, channel }),
+                Some("error") => Some(PublicEvent::SubscriptionError {
+                    pair,
+                    channel: message.get("subscription").and_then(decode_channel),
+                    message: text("errorMessage").unwrap_or_default(),
+                }),
+                _ => None,
+            }
+        }
+        "error" => Some(PublicEvent::Error { message: text("errorMessage").unwrap_or_default() }),
+        // heartbeat, pong
+        _ => None,
+    }
+}
+
+/// Decodes the `subscription` details kraken echoes with a subscription status.
+fn decode_channel(subscription: &Value) -> Option<Channel> {
+    let number = |key: &str| subscription.get(key).and_then(Value::as_u64).map(|n| n as u32);
+
+    match subscription.get("name").and_then(Value::as_str)? {
+        "ticker" => Some(Channel::Ticker),
+        "ohlc" => Some(Channel::Ohlc(number("interval").unwrap_or(1))),
+        "trade" => Some(Channel::Trade),
+        "spread" => Some(Channel::Spread),
+        "book" => Some(Channel::Book(number("depth").unwrap_or(10))),
+        _ => None,
+    }
+}
+
+/// Converts the websocket ticker, which sends some numbers as json numbers and two opening prices.
+fn decode_tick(payload: &Value) -> Result<Tick, String> {
+    let strings = |key: &str| -> Result<Vec<String>, String> {
+        value_array(&payload[key])?.iter().map(value_string).collect()
+    };
+
+    let o = match payload["o"] {
+        Value::Array(ref values) => {
+            value_string(values.first().ok_or_else(|| String::from("missing opening price"))?)?
+        }
+        ref other => value_string(other)?,
+    };
+
+    let t = value_array(&payload["t"])?
+        .iter()
+        .map(|t| {
+            t.as_u64().map(|t| t as u32).ok_or_else(
+                || format!("invalid number of trades: {}", t),
+            )
+        })
+        .collect::<Result<Vec<u32>, String>>()?;
+
+    Ok(Tick {
+        a: strings("a")?,
+        b: strings("b")?,
+        c: strings("c")?,
+        v: strings("v")?,
+        p: strings("p")?,
+        t,
+        l: strings("l")?,
+        h: strings("h")?,
+        o,
+    })
+}
+
+/// Decodes `[time, etime, open, high, low, close, vwap, volume, count]`.
+fn decode_ohlc(payload: &Value, interval: u32) -> Result<OhlcFrame, String> {
+    let values = value_array(payload)?;
+
+    if values.len() < 9 {
+        return Err(format!("invalid ohlc frame: {}", payload));
+    }
+
+    let decimal = |i: usize| value_string(&values[i]).and_then(|value| value.parse::<Decimal>());
+    let end = value_f64(&values[1])?;
+
+    Ok(OhlcFrame {
+        time: end as i64 - i64::from(interval) * 60,
+        open: decimal(2)?,
+        high: decimal(3)?,
+        low: decimal(4)?,
+        close: decimal(5)?,
+        vwap: decimal(6)?,
+        volume: decimal(7)?,
+        count: values[8].as_u64().ok_or_else(|| format!("invalid ohlc frame: {}", payload))?,
+    })
+}
+
+/// Decodes `[bid, ask, time, bid volume, ask volume]`.
+fn decode_spread(value: &Value) -> Result<Spread, String> {
+    let values = value_array(value)?;
+
+    if values.len() < 3 {
+        return Err(format!("invalid spread: {}", value));
+    }
+
+    let volume = |i: usize| -> Result<Option<Decimal>, String> {
+        match values.get(i) {
+            Some(volume) => value_string(volume)?.parse().map(Some),
+            None => Ok(None),
+        }
+    };
+
+    Ok(Spread {
+        time: value_f64(&values[2])?,
+        bid: value_string(&values[0])?.parse()?,
+        ask: value_string(&values[1])?.parse()?,
+        bid_volume: volume(3)?,
+        ask_volume: volume(4)?,
+    })
+}
+
+/// Decodes snapshots `{"as": [..], "bs": [..]}` and updates `{"a": [..]}, {"b": [..], "c": ".."}`.
+fn decode_book(pair: String, depth: u32, payloads: &[Value]) -> Result<PublicEvent, String> {
+    let mut book = DepthPair {
+        asks: Vec::new(),
+        bids: Vec::new(),
+    };
+    let mut snapshot = false;
+    let mut checksum = None;
+
+    for payload in payloads {
+        let object = payload.as_object().ok_or_else(
+            || format!("invalid book message: {}", payload),
+        )?;
+
+        for (key, value) in object {
+            match key.as_str() {
+                "as" | "a" => book.asks.extend(decode_levels(value)?),
+                "bs" | "b" => book.bids.extend(decode_levels(value)?),
+                "c" => {
+                    checksum = Some(value_string(value)?.parse::<u32>().map_err(
+                        |e| format!("{:?}", e),
+                    )?)
+                }
+                _ => {}
+            }
+
+            snapshot |= key == "as" || key == "bs";
+        }
+    }
+
+    if snapshot {
+        Ok(PublicEvent::BookSnapshot { pair, depth, book })
+    } else {
+        Ok(PublicEvent::BookUpdate {
+            pair,
+            depth,
+            book,
+            checksum,
+        })
+    }
+}
+
+/// Decodes `[price, volume, time]` levels, republish markers are dropped.
+fn decode_levels(value: &Value) -> Result<Vec<DepthPairTuple>, String> {
+    value_array(value)?
+        .iter()
+        .map(|level| {
+            let values = value_array(level)?;
+
+            if values.len() < 3 {
+                return Err(format!("invalid book level: {}", level));
+            }
+
+            Ok(DepthPairTuple(
+                value_string(&values[0])?,
+                value_string(&values[1])?,
+                value_f64(&values[2])? as i64,
+            ))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::{OrderType, TradeType};
+    use super::*;
+    use serde_json;
+
+    fn fixture(name: &str) -> Value {
+        let fixtures: Value = serde_json::from_str(include_str!("../../tests/fixtures/ws_public.json")).unwrap();
+
+        fixtures[name].clone()
+    }
+
+    #[test]
+    fn ticker() {
+        match decode(&fixture("ticker")).unwrap() {
+            Some(PublicEvent::Ticker { pair, tick }) => {
+                assert_eq!(pair, "XBT/USD");
+                assert_eq!(tick.ask_price().unwrap().to_string(), "5525.40000");
+                assert_eq!(tick.bid_volume().unwrap().to_string(), "1.000");
+                assert_eq!(tick.last_price().unwrap().to_string(), "5525.10000");
+                assert_eq!(tick.t, vec![11493, 16267]);
+                assert_eq!(tick.o, "5760.70000");
+            }
+            other => panic!("unexpected event: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn ohlc() {
+        match decode(&fixture("ohlc")).unwrap() {
+            Some(PublicEvent::Ohlc { pair, interval, frame }) => {
+                assert_eq!(pair, "XBT/USD");
+                assert_eq!(interval, 5);
+                assert_eq!(frame.time, 1542057060);
+                assert_eq!(frame.open.to_string(), "3586.70000");
+                assert_eq!(frame.low.to_string(), "3586.60000");
+                assert_eq!(frame.vwap.to_string(), "3586.68894");
+                assert_eq!(frame.volume.to_string(), "0.03373000");
+                assert_eq!(frame.count, 2);
+            }
+            other => panic!("unexpected event: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn trades() {
+        match decode(&fixture("trade")).unwrap() {
+            Some(PublicEvent::Trades { pair, trades }) => {
+                assert_eq!(pair, "XBT/USD");
+                assert_eq!(trades.len(), 2);
+                assert_eq!(trades[0].price.to_string(), "5541.20000");
+                assert_eq!(trades[0].volume.to_string(), "0.15850568");
+                assert_eq!(trades[0].time, 1534614057.321597);
+                assert_eq!(trades[0].kind, TradeType::Sell);
+                assert_eq!(trades[0].ordertype, OrderType::Limit);
+                assert_eq!(trades[1].kind, TradeType::Buy);
+                assert_eq!(trades[1].ordertype, OrderType::Market);
+            }
+            other => panic!("unexpected event: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn spread() {
+        match decode(&fixture("spread")).unwrap() {
+            Some(PublicEvent::Spread { pair, spread }) => {
+                assert_eq!(pair, "XBT/USD");
+                assert_eq!(spread.bid.to_string(), "5698.40000");
+                assert_eq!(spread.ask.to_string(), "5700.00000");
+                assert_eq!(spread.time, 1542057299.545897);
+                assert_eq!(spread.bid_volume.unwrap().to_string(), "1.01234567");
+                assert_eq!(spread.ask_volume.unwrap().to_string(), "0.98765432");
+            }
+            other => panic!("unexpected event: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn book_snapshot() {
+        match decode(&fixture("book_snapshot")).unwrap() {
+            Some(PublicEvent::BookSnapshot { pair, depth, book }) => {
+                assert_eq!(pair, "XBT/USD");
+                assert_eq!(depth, 10);
+                assert_eq!(book.asks.len(), 2);
+                assert_eq!(book.asks[0].0, "5541.30000");
+                assert_eq!(book.asks[0].1, "2.50700000");
+                assert_eq!(book.asks[0].2, 1534614248);
+                assert_eq!(book.bids.len(), 2);
+                assert_eq!(book.bids[1].0, "5539.90000");
+            }
+            other => panic!("unexpected event: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn book_update() {
+        match decode(&fixture("book_update")).unwrap() {
+            Some(PublicEvent::BookUpdate { pair, depth, book, checksum }) => {
+                assert_eq!(pair, "XBT/USD");
+                assert_eq!(depth, 10);
+                assert_eq!(book.asks.len(), 2);
+                assert_eq!(book.asks[1].0, "5542.50000");
+                assert_eq!(book.bids.len(), 1);
+                assert_eq!(book.bids[0].1, "0.00000000");
+                assert_eq!(checksum, Some(974942666));
+            }
+            other => panic!("unexpected event: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn heartbeat() {
+        assert!(decode(&fixture("heartbeat")).unwrap().is_none());
+    }
+
+    #[test]
+    fn invalid_messages() {
+        let trade = serde_json::json!([0, [["5541.2", "0.1", "1534614057.3", "x", "l", ""]], "trade", "XBT/USD"]);
+
+        assert!(decode(&trade).is_err());
+        assert!(decode(&serde_json::json!([0, {}, "unknown", "XBT/USD"])).is_err());
+        assert!(decode(&serde_json::json!([0, "XBT/USD"])).is_err());
+    }
+
+    #[test]
+    fn rejected_subscriptions_are_not_renewed() {
+        let mut client = PublicClient {
+            connection: Connection::new(URL),
+            subscriptions: vec![
+                Subscription::new(Channel::Book(42), &["XBT/USD", "ETH/USD"]),
+                Subscription::new(Channel::Book(10), &["XBT/USD"]),
+            ],
+        };
+
+        match decode(&fixture("subscription_error")).unwrap() {
+            Some(PublicEvent::SubscriptionError { pair, channel, message }) => {
+                assert_eq!(pair.as_deref(), Some("XBT/USD"));
+                assert_eq!(channel, Some(Channel::Book(42)));
+                assert_eq!(message, "Subscription depth not supported");
+
+                client.forget(channel.unwrap(), pair.as_deref());
+            }
+            other => panic!("unexpected event: {:?}", other),
+        }
+
+        assert_eq!(
+            client.subscriptions(),
+            &[
+                Subscription::new(Channel::Book(42), &["ETH/USD"]),
+                Subscription::new(Channel::Book(10), &["XBT/USD"]),
+            ]
+        );
+
+        client.forget(Channel::Book(42), None);
+
+        assert_eq!(client.subscriptions(), &[Subscription::new(Channel::Book(10), &["XBT/USD"])]);
+    }
+}
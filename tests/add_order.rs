@@ -0,0 +1,38 @@
+extern crate kraken;
+extern crate serde_json;
+
+use kraken::{AddOrderResult, KrakenResult};
+
+#[test]
+fn add_order_result() {
+    let response: KrakenResult<AddOrderResult> =
+        serde_json::from_str(include_str!("fixtures/add_order.json")).unwrap();
+
+    assert!(response.error.is_empty());
+
+    let result = response.result.unwrap();
+
+    assert_eq!(
+        result.descr.order,
+        "buy 2.12340000 XBTUSD @ limit 25000.1 with 2:1 leverage"
+    );
+    assert_eq!(
+        result.descr.close.as_deref(),
+        Some("close position @ stop loss 22000.0 -> limit 21000.0")
+    );
+    assert_eq!(result.txid, vec!["OUF4EM-FRGI2-MQMWZD"]);
+}
+
+#[test]
+fn add_order_validate_result() {
+    let response: KrakenResult<AddOrderResult> =
+        serde_json::from_str(include_str!("fixtures/add_order_validate.json")).unwrap();
+
+    assert!(response.error.is_empty());
+
+    let result = response.result.unwrap();
+
+    assert_eq!(result.descr.order, "buy 1.25000000 XBTUSD @ limit 27500.0");
+    assert_eq!(result.descr.close, None);
+    assert!(result.txid.is_empty());
+}
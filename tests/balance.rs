@@ -0,0 +1,69 @@
+extern crate kraken;
+extern crate serde_json;
+
+use kraken::{Decimal, ExtendedBalance, KrakenResult};
+use std::collections::HashMap;
+
+fn balances() -> HashMap<String, ExtendedBalance> {
+    let response: KrakenResult<HashMap<String, ExtendedBalance>> =
+        serde_json::from_str(include_str!("fixtures/balance_ex.json")).unwrap();
+
+    assert!(response.error.is_empty());
+
+    response.result.unwrap()
+}
+
+fn amount(value: &str) -> Decimal {
+    value.parse().unwrap()
+}
+
+#[test]
+fn extended_balances() {
+    let balances = balances();
+
+    assert_eq!(balances.len(), 4);
+
+    assert_eq!(balances["ZUSD"].balance, amount("25435.21"));
+    assert_eq!(balances["ZUSD"].hold_trade, Some(amount("8249.76")));
+    assert_eq!(balances["ZUSD"].credit, None);
+
+    assert_eq!(balances["XETH"].credit, Some(amount("2.5")));
+    assert_eq!(balances["XETH"].credit_used, Some(amount("0.75")));
+    assert_eq!(balances["XETH"].hold_trade, None);
+
+    assert_eq!(balances["DOT.S"].balance, amount("12"));
+}
+
+#[test]
+fn available_balances() {
+    let balances = balances();
+
+    assert_eq!(balances["ZUSD"].available(), amount("17185.45"));
+    assert_eq!(balances["XXBT"].available(), amount("0.4012"));
+    assert_eq!(balances["XETH"].available(), amount("1.75"));
+    assert_eq!(balances["DOT.S"].available(), amount("12"));
+    assert_eq!(balances["XXBT"].available().to_string(), "0.4012");
+}
+
+#[test]
+fn available_balance_can_be_negative() {
+    let balance = ExtendedBalance {
+        balance: amount("1"),
+        hold_trade: Some(amount("0.5")),
+        credit: Some(amount("1")),
+        credit_used: Some(amount("2")),
+    };
+
+    assert_eq!(balance.available(), amount("-0.5"));
+}
+
+#[test]
+fn invalid_extended_balance() {
+    let json = r#"{"balance": "1.2.3"}"#;
+
+    assert!(serde_json::from_str::<ExtendedBalance>(json).is_err());
+
+    let json = r#"{"hold_trade": "1"}"#;
+
+    assert!(serde_json::from_str::<ExtendedBalance>(json).is_err());
+}
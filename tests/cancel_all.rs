@@ -0,0 +1,59 @@
+extern crate kraken;
+extern crate serde_json;
+
+use kraken::{Account, CancelAllOrdersAfter, CanceldOrders, DeadMansSwitch, KrakenResult};
+use std::time::Duration;
+
+#[test]
+fn cancel_all_orders_after() {
+    let json = r#"{
+        "error": [],
+        "result": {
+            "currentTime": "2023-03-24T17:41:56Z",
+            "triggerTime": "2023-03-24T17:42:56Z"
+        }
+    }"#;
+    let response: KrakenResult<CancelAllOrdersAfter> = serde_json::from_str(json).unwrap();
+    let result = response.result.unwrap();
+
+    assert_eq!(result.current_time, "2023-03-24T17:41:56Z");
+    assert_eq!(result.trigger_time, "2023-03-24T17:42:56Z");
+
+    let json = r#"{"currentTime": "2023-03-24T17:43:01Z", "triggerTime": "0"}"#;
+    let disabled: CancelAllOrdersAfter = serde_json::from_str(json).unwrap();
+
+    assert_eq!(disabled.trigger_time, "0");
+
+    assert!(serde_json::from_str::<CancelAllOrdersAfter>(r#"{"current_time": "0", "trigger_time": "0"}"#).is_err());
+}
+
+#[test]
+fn cancel_all() {
+    let response: KrakenResult<CanceldOrders> =
+        serde_json::from_str(r#"{"error": [], "result": {"count": 4}}"#).unwrap();
+    let result = response.result.unwrap();
+
+    assert_eq!(result.count, 4);
+    assert_eq!(result.pending, None);
+}
+
+#[test]
+fn dead_mans_switch_interval() {
+    let account = Account {
+        key: String::new(),
+        secret: String::new(),
+    };
+
+    for &(timeout, interval) in &[(60, 60), (60, 90), (0, 0), (0, 15)] {
+        let result = DeadMansSwitch::arm(&account, timeout, Duration::from_secs(interval));
+
+        assert_eq!(
+            result.err(),
+            Some(format!(
+                "renewal interval {:?} must be shorter than timeout {}s",
+                Duration::from_secs(interval),
+                timeout
+            ))
+        );
+    }
+}
@@ -0,0 +1,101 @@
+extern crate kraken;
+extern crate serde_json;
+
+use kraken::{Decimal, EarnAllocations, EarnStrategies, KrakenResult};
+
+fn strategies() -> EarnStrategies {
+    let response: KrakenResult<EarnStrategies> =
+        serde_json::from_str(include_str!("fixtures/earn_strategies.json")).unwrap();
+
+    assert!(response.error.is_empty());
+
+    response.result.unwrap()
+}
+
+fn allocations() -> EarnAllocations {
+    let response: KrakenResult<EarnAllocations> =
+        serde_json::from_str(include_str!("fixtures/earn_allocations.json")).unwrap();
+
+    assert!(response.error.is_empty());
+
+    response.result.unwrap()
+}
+
+fn amount(value: &str) -> Decimal {
+    value.parse().unwrap()
+}
+
+#[test]
+fn earn_strategies() {
+    let strategies = strategies();
+
+    assert_eq!(strategies.next_cursor.as_deref(), Some("2"));
+    assert_eq!(strategies.items.len(), 2);
+
+    let dot = &strategies.items[0];
+
+    assert_eq!(dot.asset, "DOT");
+    assert_eq!(dot.lock_type.kind, "instant");
+    assert_eq!(dot.lock_type.payout_frequency, Some(604800));
+    assert_eq!(dot.lock_type.bonding_period, None);
+    assert_eq!(dot.apr_estimate.as_ref().map(|apr| apr.high.as_str()), Some("12.0000"));
+    assert_eq!(dot.yield_source.as_ref().map(|source| source.kind.as_str()), Some("staking"));
+    assert!(dot.can_allocate);
+    assert!(dot.allocation_restriction_info.is_empty());
+
+    let eth = &strategies.items[1];
+
+    assert_eq!(eth.lock_type.kind, "bonded");
+    assert_eq!(eth.lock_type.unbonding_period, Some(2592000));
+    assert!(eth.apr_estimate.is_none());
+    assert!(eth.user_min_allocation.is_none());
+    assert_eq!(eth.auto_compound.as_ref().and_then(|compound| compound.default), Some(false));
+    assert!(!eth.can_allocate);
+    assert_eq!(eth.allocation_restriction_info, vec!["tier"]);
+}
+
+#[test]
+fn earn_allocations() {
+    let allocations = allocations();
+
+    assert_eq!(allocations.converted_asset, "USD");
+    assert_eq!(allocations.next_cursor, None);
+    assert_eq!(allocations.items.len(), 3);
+
+    let eth = &allocations.items[0].amount_allocated;
+
+    assert_eq!(eth.bonding.as_ref().map(|state| state.allocation_count), Some(2));
+    assert!(eth.pending.is_none() && eth.exit_queue.is_none() && eth.unbonding.is_none());
+
+    let dot = &allocations.items[1].amount_allocated;
+
+    assert_eq!(dot.pending.as_ref().map(|pending| pending.native.as_str()), Some("0.5"));
+    assert!(dot.bonding.is_none());
+
+    let exiting = &allocations.items[2].amount_allocated;
+
+    assert_eq!(exiting.exit_queue.as_ref().map(|state| state.native.as_str()), Some("0.4"));
+    assert_eq!(exiting.unbonding.as_ref().map(|state| state.allocation_count), Some(1));
+}
+
+#[test]
+fn allocation_totals() {
+    let totals = allocations().totals().unwrap();
+
+    assert_eq!(totals.len(), 2);
+    assert_eq!(totals["ETH"], amount("0.021"));
+    assert_eq!(totals["DOT"], amount("1.95"));
+}
+
+#[test]
+fn invalid_allocation_totals() {
+    let mut allocations = allocations();
+
+    allocations.items[1].amount_allocated.total.native = String::from("1.2.3");
+
+    assert!(allocations.totals().is_err());
+
+    allocations.items.clear();
+
+    assert!(allocations.totals().unwrap().is_empty());
+}
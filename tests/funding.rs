@@ -0,0 +1,105 @@
+extern crate kraken;
+extern crate serde;
+extern crate serde_json;
+
+use kraken::{Decimal, DepositAddress, DepositMethod, FundingState, FundingStatus, KrakenResult};
+use serde::de::DeserializeOwned;
+use std::time::{Duration, UNIX_EPOCH};
+
+fn result<T: DeserializeOwned>(json: &str) -> T {
+    let response: KrakenResult<T> = serde_json::from_str(json).unwrap();
+
+    assert!(response.error.is_empty());
+
+    response.result.unwrap()
+}
+
+fn amount(value: &str) -> Decimal {
+    value.parse().unwrap()
+}
+
+#[test]
+fn deposit_methods() {
+    let methods: Vec<DepositMethod> = result(include_str!("fixtures/deposit_methods.json"));
+
+    assert_eq!(methods.len(), 3);
+
+    assert_eq!(methods[0].method, "Bitcoin");
+    assert_eq!(methods[0].limit, None);
+    assert_eq!(methods[0].gen_address, Some(true));
+    assert_eq!(methods[0].minimum.as_deref(), Some("0.00010000"));
+
+    assert_eq!(methods[1].limit.as_deref(), Some("1.00000000"));
+    assert_eq!(methods[1].gen_address, None);
+
+    assert_eq!(methods[2].limit, None);
+    assert_eq!(methods[2].fee, None);
+    assert_eq!(methods[2].address_setup_fee.as_deref(), Some("0.00"));
+}
+
+#[test]
+fn invalid_deposit_limit() {
+    for limit in &["true", "1", "[]"] {
+        let json = format!(r#"{{"method": "Bitcoin", "limit": {}}}"#, limit);
+
+        assert!(serde_json::from_str::<DepositMethod>(&json).is_err(), "{}", limit);
+    }
+
+    let json = r#"{"method": "Bitcoin", "limit": null}"#;
+
+    assert_eq!(serde_json::from_str::<DepositMethod>(json).unwrap().limit, None);
+}
+
+#[test]
+fn deposit_addresses() {
+    let addresses: Vec<DepositAddress> = result(include_str!("fixtures/deposit_addresses.json"));
+
+    assert_eq!(addresses.len(), 2);
+
+    assert_eq!(addresses[0].address, "2N9fRkx5JTWXWHmXzZtvhQsufvoYRMq9ExV");
+    assert_eq!(addresses[0].expiretm, "0");
+    assert_eq!(addresses[0].new, Some(true));
+    assert_eq!(addresses[0].tag, None);
+
+    assert_eq!(addresses[1].new, None);
+    assert_eq!(addresses[1].tag.as_deref(), Some("1361101127"));
+    assert_eq!(addresses[1].memo, None);
+}
+
+#[test]
+fn deposit_status() {
+    let deposits: Vec<FundingStatus> = result(include_str!("fixtures/deposit_status.json"));
+
+    assert_eq!(deposits.len(), 2);
+
+    let bitcoin = &deposits[0];
+
+    assert_eq!(bitcoin.status, FundingState::Success);
+    assert_eq!(bitcoin.status_prop.as_deref(), Some("return"));
+    assert_eq!(bitcoin.time(), UNIX_EPOCH + Duration::from_secs(1688992722));
+    assert_eq!(bitcoin.amount(), Ok(amount("0.78125")));
+    assert_eq!(bitcoin.fee(), Ok(Some(amount("0"))));
+
+    let ether = &deposits[1];
+
+    assert_eq!(ether.status, FundingState::Pending);
+    assert_eq!(ether.status_prop, None);
+    assert_eq!(ether.amount(), Ok(amount("0.1383862742")));
+    assert_eq!(ether.fee(), Ok(None));
+}
+
+#[test]
+fn invalid_deposit_status() {
+    let json = r#"{
+        "method": "Bitcoin", "aclass": "currency", "asset": "XXBT", "refid": "R", "txid": "T",
+        "info": "I", "amount": "1", "time": 1688992722, "status": "Unknown"
+    }"#;
+
+    assert!(serde_json::from_str::<FundingStatus>(json).is_err());
+
+    let json = json.replace("\"Unknown\"", "\"Failure\"").replace("\"1\"", "\"one\"");
+    let deposit: FundingStatus = serde_json::from_str(&json).unwrap();
+
+    assert_eq!(deposit.status, FundingState::Failure);
+    assert!(deposit.amount().is_err());
+}
@@ -0,0 +1,223 @@
+extern crate kraken;
+extern crate serde_json;
+
+use kraken::{
+    AssetPair, CloseOrder, KrakenResult, NewOrder, OrderFlag, OrderTime, OrderType, TimeInForce,
+    TradeType,
+};
+use std::collections::HashMap;
+
+fn pair() -> AssetPair {
+    let response: KrakenResult<HashMap<String, AssetPair>> =
+        serde_json::from_str(include_str!("fixtures/asset_pairs.json")).unwrap();
+
+    response.result.unwrap().remove("XXBTZUSD").unwrap()
+}
+
+fn limit(pair: &AssetPair, price: &str, volume: &str) -> Result<NewOrder, String> {
+    NewOrder::builder("XXBTZUSD", pair, TradeType::Buy, OrderType::Limit)
+        .price(price)
+        .volume(volume)
+        .build()
+}
+
+#[test]
+fn prices_per_order_type() {
+    let pair = pair();
+    let builder = |order_type| NewOrder::builder("XXBTZUSD", &pair, TradeType::Sell, order_type).volume("1");
+
+    let market = builder(OrderType::Market).build().unwrap();
+
+    assert_eq!(market.price, None);
+    assert_eq!(market.price2, None);
+    assert!(builder(OrderType::Market).price("100").build().is_err());
+
+    assert!(builder(OrderType::Limit).build().is_err());
+    assert!(builder(OrderType::Limit).price("100").price2("90").build().is_err());
+
+    let stop = builder(OrderType::StopLossLimit).price("100").price2("90").build().unwrap();
+
+    assert_eq!(stop.price.as_deref(), Some("100"));
+    assert_eq!(stop.price2.as_deref(), Some("90"));
+    assert!(builder(OrderType::StopLossLimit).price("100").build().is_err());
+}
+
+#[test]
+fn prices_rounded_to_pair_decimals() {
+    let pair = pair();
+
+    assert_eq!(limit(&pair, "27500.16", "1").unwrap().price.as_deref(), Some("27500.2"));
+    assert_eq!(limit(&pair, "27500.14", "1").unwrap().price.as_deref(), Some("27500.1"));
+    assert_eq!(limit(&pair, "+50.05", "1").unwrap().price.as_deref(), Some("+50.1"));
+    assert_eq!(limit(&pair, "-50.04", "1").unwrap().price.as_deref(), Some("-50.0"));
+    assert_eq!(limit(&pair, "#12.345", "1").unwrap().price.as_deref(), Some("#12.3"));
+    assert_eq!(limit(&pair, "+1.25%", "1").unwrap().price.as_deref(), Some("+1.25%"));
+    assert_eq!(limit(&pair, "1.25%", "1").unwrap().price.as_deref(), Some("1.25%"));
+
+    assert!(limit(&pair, "abc", "1").is_err());
+    assert!(limit(&pair, "+-5", "1").is_err());
+    assert!(limit(&pair, "+x%", "1").is_err());
+}
+
+#[test]
+fn volume_rounded_towards_zero() {
+    let pair = pair();
+
+    assert_eq!(limit(&pair, "27500", "0.123456789").unwrap().volume, "0.12345678");
+    assert_eq!(limit(&pair, "27500", "1.999999999").unwrap().volume, "1.99999999");
+    assert_eq!(limit(&pair, "27500", "2").unwrap().volume, "2");
+}
+
+#[test]
+fn minimum_volume_and_cost() {
+    let pair = pair();
+
+    assert!(limit(&pair, "27500", "0.0001").is_ok());
+    assert!(limit(&pair, "27500", "0.00009999").is_err());
+    assert!(limit(&pair, "27500", "-1").is_err());
+    assert!(limit(&pair, "27500", "0").is_err());
+
+    // 0.0002 * 2000.0 = 0.4 is below the minimum cost of 0.5
+    assert!(limit(&pair, "2000", "0.0002").is_err());
+    assert!(limit(&pair, "2500", "0.0002").is_ok());
+
+    // the cost of relative prices is unknown
+    assert!(limit(&pair, "-26000", "0.0002").is_ok());
+
+    let in_quote = |volume| {
+        NewOrder::builder("XXBTZUSD", &pair, TradeType::Buy, OrderType::Market)
+            .volume(volume)
+            .flag(OrderFlag::Viqc)
+            .build()
+    };
+
+    assert!(in_quote("0.00001").is_err());
+    assert!(in_quote("0.5").is_ok());
+}
+
+#[test]
+fn volume_in_quote_currency() {
+    let pair = pair();
+    let in_quote = |pair: &AssetPair, volume| {
+        NewOrder::builder("XXBTZUSD", pair, TradeType::Buy, OrderType::Market)
+            .volume(volume)
+            .flag(OrderFlag::Viqc)
+            .build()
+    };
+
+    // rounded to cost_decimals, not lot_decimals
+    assert_eq!(in_quote(&pair, "100.123456789").unwrap().volume, "100.12345");
+
+    // ordermin is a base currency volume, only costmin applies
+    assert_eq!(in_quote(&pair, "0.5").unwrap().volume, "0.5");
+    assert!(in_quote(&pair, "0.499999").is_err());
+    assert!(in_quote(&pair, "0.000001").is_err());
+
+    let mut unknown = pair;
+    unknown.cost_decimals = None;
+
+    assert_eq!(in_quote(&unknown, "100.123456789").unwrap().volume, "100.123456789");
+}
+
+#[test]
+fn leverage() {
+    let pair = pair();
+    let order = |kind, leverage| {
+        NewOrder::builder("XXBTZUSD", &pair, kind, OrderType::Market)
+            .volume("1")
+            .leverage(leverage)
+            .build()
+    };
+
+    assert_eq!(order(TradeType::Buy, 5).unwrap().leverage.as_deref(), Some("5"));
+    assert!(order(TradeType::Sell, 3).is_ok());
+    assert!(order(TradeType::Sell, 5).is_err());
+    assert!(order(TradeType::Buy, 10).is_err());
+
+    // a volume of zero closes a position and needs a leverage
+    let close_out = NewOrder::builder("XXBTZUSD", &pair, TradeType::Sell, OrderType::Market)
+        .volume("0")
+        .leverage(2)
+        .build()
+        .unwrap();
+
+    assert_eq!(close_out.volume, "0");
+}
+
+#[test]
+fn close_orders() {
+    let pair = pair();
+    let order = |close| {
+        NewOrder::builder("XXBTZUSD", &pair, TradeType::Buy, OrderType::Limit)
+            .price("27500")
+            .volume("1")
+            .close(close)
+            .build()
+    };
+
+    let close = order(CloseOrder {
+        order_type: OrderType::StopLossLimit,
+        price: String::from("22000.05"),
+        price2: Some(String::from("21000")),
+    }).unwrap()
+        .close
+        .unwrap();
+
+    assert_eq!(close.price, "22000.1");
+    assert_eq!(close.price2.as_deref(), Some("21000"));
+
+    assert!(order(CloseOrder {
+        order_type: OrderType::Market,
+        price: String::from("22000"),
+        price2: None,
+    }).is_err());
+
+    assert!(order(CloseOrder {
+        order_type: OrderType::StopLossLimit,
+        price: String::from("22000"),
+        price2: None,
+    }).is_err());
+
+    assert!(order(CloseOrder {
+        order_type: OrderType::TakeProfit,
+        price: String::from("30000"),
+        price2: Some(String::from("31000")),
+    }).is_err());
+
+    let close = order(CloseOrder {
+        order_type: OrderType::TakeProfit,
+        price: String::from("30000.04"),
+        price2: None,
+    }).unwrap()
+        .close
+        .unwrap();
+
+    assert_eq!(close.order_type, OrderType::TakeProfit);
+    assert_eq!(close.price, "30000.0");
+    assert_eq!(close.price2, None);
+}
+
+#[test]
+fn time_in_force_and_ids() {
+    let pair = pair();
+    let builder = || {
+        NewOrder::builder("XXBTZUSD", &pair, TradeType::Buy, OrderType::Limit)
+            .price("27500")
+            .volume("1")
+    };
+
+    assert!(builder().timeinforce(TimeInForce::Gtd).build().is_err());
+    assert!(builder()
+        .timeinforce(TimeInForce::Gtd)
+        .expiretm(OrderTime::Now)
+        .build()
+        .is_err());
+    assert!(builder()
+        .timeinforce(TimeInForce::Gtd)
+        .expiretm("+3600".parse().unwrap())
+        .build()
+        .is_ok());
+
+    assert!(builder().userref("42").cl_ord_id("my-order").build().is_err());
+    assert!(builder().cl_ord_id("my-order").build().is_ok());
+}
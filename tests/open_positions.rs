@@ -0,0 +1,60 @@
+extern crate kraken;
+extern crate serde_json;
+
+use kraken::{KrakenResult, OrderType, Position, TradeType};
+use std::collections::HashMap;
+
+fn positions() -> HashMap<String, Position> {
+    let response: KrakenResult<HashMap<String, Position>> =
+        serde_json::from_str(include_str!("fixtures/open_positions.json")).unwrap();
+
+    assert!(response.error.is_empty());
+
+    response.result.unwrap()
+}
+
+#[test]
+fn closing_order_of_long_position() {
+    let order = positions()["TF5GVO-T7ZZ2-6NBKBI"].closing_order().unwrap();
+
+    assert_eq!(order.pair, "XXBTZUSD");
+    assert_eq!(order.kind, TradeType::Sell);
+    assert_eq!(order.order_type, OrderType::Market);
+    assert_eq!(order.volume, "0");
+    assert_eq!(order.leverage.as_deref(), Some("5"));
+    assert_eq!(order.price, None);
+}
+
+#[test]
+fn closing_order_of_short_position() {
+    let order = positions()["T6MYRR-DXOEJ-XSQ2JT"].closing_order().unwrap();
+
+    assert_eq!(order.pair, "XETHZEUR");
+    assert_eq!(order.kind, TradeType::Buy);
+    assert_eq!(order.leverage.as_deref(), Some("2"));
+}
+
+#[test]
+fn closing_order_without_margin() {
+    assert!(positions()["TQK3SA-QJ5CZ-FPHBQZ"].closing_order().is_err());
+}
+
+#[test]
+fn unrealised_profit_and_loss() {
+    let positions = positions();
+    let position = &positions["TF5GVO-T7ZZ2-6NBKBI"];
+
+    assert_eq!(position.cost().unwrap().to_string(), "104610.52842");
+    assert_eq!(position.fee().unwrap().to_string(), "289.06565");
+    assert_eq!(position.vol().unwrap().to_string(), "8.82412861");
+    assert_eq!(position.vol_closed().unwrap().to_string(), "0.20200000");
+    assert_eq!(position.margin().unwrap().to_string(), "20922.10568");
+    assert_eq!(position.value().unwrap().map(|value| value.to_string()), Some(String::from("258797.5")));
+    assert_eq!(position.net().unwrap().map(|net| net.to_string()), Some(String::from("154186.9728")));
+
+    // value and net are only sent with docalcs
+    let position = &positions["T6MYRR-DXOEJ-XSQ2JT"];
+
+    assert_eq!(position.value().unwrap(), None);
+    assert_eq!(position.net().unwrap(), None);
+}
@@ -0,0 +1,184 @@
+extern crate kraken;
+extern crate serde_json;
+
+use kraken::{AssetPair, CloseOrder, KrakenResult, NewOrder, OrderType, ParsedOrder, TradeType};
+use std::collections::HashMap;
+
+#[test]
+fn parse_order_descriptions() {
+    let table = [
+        ("buy 0.5 XBTUSD @ market", TradeType::Buy, "0.5", OrderType::Market, None, None, None),
+        (
+            "sell 2.12340000 XBTUSD @ limit 25000.1",
+            TradeType::Sell,
+            "2.12340000",
+            OrderType::Limit,
+            Some("25000.1"),
+            None,
+            None,
+        ),
+        (
+            "buy 1.0 XBTUSD @ stop loss 22000.0 -> take profit 28000.0",
+            TradeType::Buy,
+            "1.0",
+            OrderType::StopLossProfit,
+            Some("22000.0"),
+            Some("28000.0"),
+            None,
+        ),
+        (
+            "sell 1.5 XBTUSD @ trailing stop +50.0",
+            TradeType::Sell,
+            "1.5",
+            OrderType::TrailingStop,
+            Some("+50.0"),
+            None,
+            None,
+        ),
+        (
+            "sell 1.5 XBTUSD @ trailing stop +2% -> limit -50.0",
+            TradeType::Sell,
+            "1.5",
+            OrderType::TrailingStopLimit,
+            Some("+2%"),
+            Some("-50.0"),
+            None,
+        ),
+        (
+            "buy 2.12340000 XBTUSD @ limit 25000.1 with 2:1 leverage",
+            TradeType::Buy,
+            "2.12340000",
+            OrderType::Limit,
+            Some("25000.1"),
+            None,
+            Some(2),
+        ),
+        (
+            "sell 0.25 ETHEUR @ take profit 3000.0 -> limit 2990.0 with 5:1 leverage",
+            TradeType::Sell,
+            "0.25",
+            OrderType::TakeProfitLimit,
+            Some("3000.0"),
+            Some("2990.0"),
+            Some(5),
+        ),
+        (
+            "buy 0.5 XBTUSD @ settle position",
+            TradeType::Buy,
+            "0.5",
+            OrderType::SettlePosition,
+            None,
+            None,
+            None,
+        ),
+        (
+            "buy 0.5 XBTUSD @ iceberg 100.0",
+            TradeType::Buy,
+            "0.5",
+            OrderType::Other(String::from("iceberg")),
+            Some("100.0"),
+            None,
+            None,
+        ),
+        (
+            "sell 0.5 XBTUSD @ stop loss 90.0 -> market",
+            TradeType::Sell,
+            "0.5",
+            OrderType::Other(String::from("stop-loss-market")),
+            Some("90.0"),
+            None,
+            None,
+        ),
+    ];
+
+    for &(description, kind, volume, ref order_type, price, price2, leverage) in table.iter() {
+        let order: ParsedOrder = description.parse().unwrap();
+
+        assert_eq!(order.kind, kind, "{}", description);
+        assert_eq!(order.volume.to_string(), volume, "{}", description);
+        assert_eq!(&order.order_type, order_type, "{}", description);
+        assert_eq!(order.price.as_deref(), price, "{}", description);
+        assert_eq!(order.price2.as_deref(), price2, "{}", description);
+        assert_eq!(order.leverage, leverage, "{}", description);
+    }
+}
+
+#[test]
+fn parse_invalid_order_descriptions() {
+    for description in &[
+        "",
+        "buy 0.5 XBTUSD",
+        "buy 0.5 @ market",
+        "hold 0.5 XBTUSD @ market",
+        "buy abc XBTUSD @ market",
+        "buy 0.5 XBTUSD @ limit",
+        "buy 0.5 XBTUSD @ market 100.0",
+        "buy 0.5 XBTUSD @ stop loss 100.0 -> limit",
+        "buy 0.5 XBTUSD @ 100.0",
+        "buy 0.5 XBTUSD @ limit 100.0 with leverage",
+    ] {
+        assert!(description.parse::<ParsedOrder>().is_err(), "{:?}", description);
+    }
+}
+
+#[test]
+fn parse_close_order_descriptions() {
+    let close: CloseOrder = "close position @ stop loss 22000.0 -> limit 21000.0".parse().unwrap();
+
+    assert_eq!(close.order_type, OrderType::StopLossLimit);
+    assert_eq!(close.price, "22000.0");
+    assert_eq!(close.price2.as_deref(), Some("21000.0"));
+    assert_eq!(close.to_string(), "close position @ stop loss 22000.0 -> limit 21000.0");
+
+    let close: CloseOrder = "close position @ iceberg 100.0".parse().unwrap();
+
+    assert_eq!(close.order_type, OrderType::Other(String::from("iceberg")));
+    assert_eq!(close.to_string(), "close position @ iceberg 100.0");
+
+    for description in &[
+        "close position @ limit 100.0",
+        "close position @ trailing stop +50.0 -> limit -10.0",
+        "close position @ stop loss 90.0 -> take profit 120.0",
+    ] {
+        assert_eq!(description.parse::<CloseOrder>().unwrap().to_string(), *description);
+    }
+
+    assert!("close position @ market".parse::<CloseOrder>().is_err());
+    assert!("limit 100.0".parse::<CloseOrder>().is_err());
+}
+
+#[test]
+fn order_type_wire_names() {
+    let order_types: Vec<OrderType> =
+        serde_json::from_str(r#"["market", "stop-loss-profit", "trailing-stop-limit", "iceberg"]"#).unwrap();
+
+    assert_eq!(
+        order_types,
+        vec![
+            OrderType::Market,
+            OrderType::StopLossProfit,
+            OrderType::TrailingStopLimit,
+            OrderType::Other(String::from("iceberg")),
+        ]
+    );
+    assert_eq!(
+        serde_json::to_string(&order_types).unwrap(),
+        r#"["market","stop-loss-profit","trailing-stop-limit","iceberg"]"#
+    );
+    assert_eq!("take-profit-limit".parse::<OrderType>(), Ok(OrderType::TakeProfitLimit));
+    assert_eq!(OrderType::Other(String::from("iceberg")).to_string(), "iceberg");
+}
+
+#[test]
+fn unknown_order_types_are_not_placed() {
+    let response: KrakenResult<HashMap<String, AssetPair>> =
+        serde_json::from_str(include_str!("fixtures/asset_pairs.json")).unwrap();
+    let pair = &response.result.unwrap()["XXBTZUSD"];
+
+    let order = NewOrder::builder("XXBTZUSD", pair, TradeType::Buy, OrderType::Other(String::from("iceberg")))
+        .price("100")
+        .volume("1")
+        .build();
+
+    assert!(order.is_err());
+}
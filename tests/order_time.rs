@@ -0,0 +1,93 @@
+extern crate kraken;
+extern crate serde_json;
+
+use kraken::{KrakenResult, NewOrder, OrderTime, OrderType, TradeType};
+use std::collections::HashMap;
+use std::time::{Duration, UNIX_EPOCH};
+
+#[test]
+fn wire_forms() {
+    assert_eq!(OrderTime::Now.to_string(), "0");
+    assert_eq!(OrderTime::In(Duration::from_secs(3600)).to_string(), "+3600");
+    assert_eq!(OrderTime::In(Duration::from_secs(0)).to_string(), "+0");
+    assert_eq!(
+        OrderTime::At(UNIX_EPOCH + Duration::from_secs(1_700_000_000)).to_string(),
+        "1700000000"
+    );
+    assert_eq!(
+        OrderTime::At(UNIX_EPOCH + Duration::from_millis(1_700_000_000_900)).to_string(),
+        "1700000000"
+    );
+}
+
+#[test]
+fn parse_round_trip() {
+    for value in &["0", "+0", "+90", "+86400", "1", "1700000000"] {
+        let time = value.parse::<OrderTime>().unwrap();
+
+        assert_eq!(time.to_string(), *value);
+        assert_eq!(time.to_string().parse::<OrderTime>().unwrap(), time);
+    }
+
+    assert_eq!("0".parse::<OrderTime>().unwrap(), OrderTime::Now);
+    assert_eq!(
+        "+90".parse::<OrderTime>().unwrap(),
+        OrderTime::In(Duration::from_secs(90))
+    );
+    assert_eq!(
+        "1700000000".parse::<OrderTime>().unwrap(),
+        OrderTime::At(UNIX_EPOCH + Duration::from_secs(1_700_000_000))
+    );
+
+    for value in &["", "+", "-5", "+1.5", "now", "1e9"] {
+        assert!(value.parse::<OrderTime>().is_err(), "{:?}", value);
+    }
+}
+
+#[test]
+fn times_without_wire_form() {
+    assert!(OrderTime::Now.check().is_ok());
+    assert!(OrderTime::In(Duration::from_secs(1)).check().is_ok());
+    assert!(OrderTime::At(UNIX_EPOCH + Duration::from_secs(1)).check().is_ok());
+
+    assert!(OrderTime::In(Duration::from_millis(500)).check().is_err());
+    assert!(OrderTime::In(Duration::from_millis(60_001)).check().is_err());
+    assert!(OrderTime::At(UNIX_EPOCH).check().is_err());
+    assert!(OrderTime::At(UNIX_EPOCH + Duration::from_millis(999)).check().is_err());
+    assert!(OrderTime::At(UNIX_EPOCH - Duration::from_secs(60)).check().is_err());
+}
+
+#[test]
+fn serde() {
+    let time = OrderTime::In(Duration::from_secs(90));
+
+    assert_eq!(serde_json::to_string(&time).unwrap(), r#""+90""#);
+    assert!(serde_json::to_string(&OrderTime::In(Duration::from_millis(500))).is_err());
+    assert!(serde_json::to_string(&OrderTime::At(UNIX_EPOCH - Duration::from_secs(1))).is_err());
+
+    let times: Vec<OrderTime> = serde_json::from_str(r#"["+90", "0", 1700000000, "1700000000"]"#).unwrap();
+
+    assert_eq!(time, times[0]);
+    assert_eq!(OrderTime::Now, times[1]);
+    assert_eq!(times[2], times[3]);
+}
+
+#[test]
+fn builder_rejects_times_without_wire_form() {
+    let response: KrakenResult<HashMap<String, kraken::AssetPair>> =
+        serde_json::from_str(include_str!("fixtures/asset_pairs.json")).unwrap();
+    let pair = &response.result.unwrap()["XXBTZUSD"];
+
+    let order = |starttm, expiretm| {
+        NewOrder::builder("XXBTZUSD", pair, TradeType::Buy, OrderType::Limit)
+            .price("27500")
+            .volume("1")
+            .starttm(starttm)
+            .expiretm(expiretm)
+            .build()
+    };
+
+    assert!(order(OrderTime::Now, OrderTime::In(Duration::from_secs(60))).is_ok());
+    assert!(order(OrderTime::In(Duration::from_millis(500)), OrderTime::Now).is_err());
+    assert!(order(OrderTime::Now, OrderTime::At(UNIX_EPOCH)).is_err());
+}
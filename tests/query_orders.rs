@@ -0,0 +1,47 @@
+extern crate kraken;
+extern crate serde_json;
+
+use kraken::{KrakenResult, OrderInfo, OrderStatus};
+use std::collections::HashMap;
+
+fn orders() -> HashMap<String, OrderInfo> {
+    let response: KrakenResult<HashMap<String, OrderInfo>> =
+        serde_json::from_str(include_str!("fixtures/query_orders.json")).unwrap();
+
+    assert!(response.error.is_empty());
+
+    response.result.unwrap()
+}
+
+#[test]
+fn queried_orders() {
+    let orders = orders();
+
+    assert_eq!(orders.len(), 3);
+
+    let order = &orders["O37652-RJWRT-IMO74O"];
+
+    assert_eq!(order.status, OrderStatus::Closed);
+    assert_eq!(order.refid, None);
+    assert_eq!(order.descr.pair, "XBTGBP");
+
+    assert_eq!(orders["OBCMZD-JIEE7-77TH3F"].status, OrderStatus::Canceled);
+    assert_eq!(orders["OQCLML-BW3P3-BUCMWZ"].status, OrderStatus::Open);
+}
+
+#[test]
+fn numeric_and_string_userrefs() {
+    let orders = orders();
+
+    assert_eq!(orders["O37652-RJWRT-IMO74O"].userref.as_deref(), Some("1"));
+    assert_eq!(orders["OBCMZD-JIEE7-77TH3F"].userref, None);
+    assert_eq!(orders["OQCLML-BW3P3-BUCMWZ"].userref.as_deref(), Some("abc"));
+}
+
+#[test]
+fn invalid_userref() {
+    let json = include_str!("fixtures/query_orders.json").replace("\"userref\": 1,", "\"userref\": [1],");
+    let response: Result<KrakenResult<HashMap<String, OrderInfo>>, _> = serde_json::from_str(&json);
+
+    assert!(response.is_err());
+}
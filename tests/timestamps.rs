@@ -0,0 +1,110 @@
+extern crate kraken;
+extern crate serde_json;
+
+use kraken::{ClosedOrders, KrakenResult, Position, Time};
+use std::collections::HashMap;
+use std::time::{Duration, UNIX_EPOCH};
+
+fn time(rfc1123: &str) -> Time {
+    Time {
+        unixtime: 0,
+        rfc1123: rfc1123.to_string(),
+    }
+}
+
+#[test]
+fn parse_rfc1123() {
+    let secs = |rfc1123: &str| {
+        time(rfc1123)
+            .parse_rfc1123()
+            .map(|time| time.duration_since(UNIX_EPOCH).unwrap().as_secs())
+    };
+
+    assert_eq!(secs("Sun,  8 Oct 17 19:09:38 +0000"), Ok(1507489778));
+    assert_eq!(secs("Sun, 08 Oct 2017 19:09:38 +0000"), Ok(1507489778));
+    assert_eq!(secs("Sun, 08 Oct 2017 19:09:38 GMT"), Ok(1507489778));
+    assert_eq!(secs("Sun, 08 Oct 2017 21:09:38 +0200"), Ok(1507489778));
+    assert_eq!(secs("Sun, 08 Oct 2017 14:39:38 -0430"), Ok(1507489778));
+    assert_eq!(secs("Thu, 29 Feb 24 00:00:00 +0000"), Ok(1709164800));
+    assert_eq!(secs("Thu, 01 Jan 1970 00:00:00 +0000"), Ok(0));
+}
+
+#[test]
+fn parse_invalid_rfc1123() {
+    for rfc1123 in &[
+        "",
+        "Sun, 08 Oct 2017 19:09:38",
+        "Sun, 08 Okt 2017 19:09:38 +0000",
+        "Sun, xx Oct 2017 19:09:38 +0000",
+        "Sun, 08 Oct 2017 19:09 +0000",
+        "Sun, 08 Oct 2017 19:09:38 CEST",
+        "Sun, 08 Oct 2017 19:09:38 *0100",
+        "Wed, 31 Dec 1969 23:59:59 +0000",
+    ] {
+        assert!(time(rfc1123).parse_rfc1123().is_err(), "{:?}", rfc1123);
+    }
+}
+
+#[test]
+fn unixtime() {
+    let time = Time {
+        unixtime: 1507489778,
+        rfc1123: String::new(),
+    };
+
+    assert_eq!(time.unixtime(), UNIX_EPOCH + Duration::from_secs(1507489778));
+}
+
+#[test]
+fn order_timestamps() {
+    let response: KrakenResult<ClosedOrders> =
+        serde_json::from_str(include_str!("fixtures/closed_orders.json")).unwrap();
+    let orders = response.result.unwrap().closed;
+
+    let filled = &orders["O37652-RJWRT-IMO74O"];
+
+    assert_eq!(
+        filled.opentm(),
+        UNIX_EPOCH + Duration::from_micros(1688148493770800)
+    );
+    assert_eq!(
+        filled.closetm(),
+        Some(UNIX_EPOCH + Duration::from_micros(1688148610048200))
+    );
+    assert_eq!(filled.starttm(), None);
+    assert_eq!(filled.expiretm(), None);
+
+    let canceled = &orders["OBCMZD-JIEE7-77TH3F"];
+
+    assert_eq!(
+        canceled.closetm(),
+        Some(UNIX_EPOCH + Duration::from_millis(1688148610500))
+    );
+    assert_eq!(
+        canceled.starttm(),
+        Some(UNIX_EPOCH + Duration::from_millis(1688148500250))
+    );
+    assert_eq!(
+        canceled.expiretm(),
+        Some(UNIX_EPOCH + Duration::from_secs(1688152093))
+    );
+}
+
+#[test]
+fn position_timestamps() {
+    let response: KrakenResult<HashMap<String, Position>> =
+        serde_json::from_str(include_str!("fixtures/open_positions.json")).unwrap();
+    let positions = response.result.unwrap();
+
+    let position = &positions["TF5GVO-T7ZZ2-6NBKBI"];
+
+    assert_eq!(
+        position.time(),
+        UNIX_EPOCH + Duration::from_micros(1605280097829400)
+    );
+    assert_eq!(
+        position.rollovertm(),
+        Ok(Some(UNIX_EPOCH + Duration::from_secs(1616672637)))
+    );
+    assert_eq!(positions["T6MYRR-DXOEJ-XSQ2JT"].rollovertm(), Ok(None));
+}
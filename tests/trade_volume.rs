@@ -0,0 +1,30 @@
+extern crate kraken;
+extern crate serde_json;
+
+use kraken::{Decimal, KrakenResult, TradeVolume};
+
+#[test]
+fn trade_volume_result() {
+    let response: KrakenResult<TradeVolume> =
+        serde_json::from_str(include_str!("fixtures/trade_volume.json")).unwrap();
+
+    assert!(response.error.is_empty());
+
+    let result = response.result.unwrap();
+    let fees = result.fees.unwrap();
+
+    let tiered = &fees["XXBTZUSD"];
+
+    assert_eq!(tiered.fee().unwrap(), "0.1".parse::<Decimal>().unwrap());
+    assert_eq!(tiered.minfee().unwrap(), Some("0.1".parse().unwrap()));
+    assert_eq!(tiered.maxfee().unwrap(), Some("0.26".parse().unwrap()));
+    assert_eq!(tiered.nextfee().unwrap(), None);
+
+    let fixed = &fees["USDTZUSD"];
+
+    assert_eq!(fixed.fee().unwrap(), "0.2".parse::<Decimal>().unwrap());
+    assert_eq!(fixed.minfee().unwrap(), None);
+    assert_eq!(fixed.maxfee().unwrap(), None);
+    assert_eq!(fixed.tiervolume().unwrap(), None);
+    assert!(result.fees_maker.is_none());
+}
@@ -0,0 +1,56 @@
+extern crate kraken;
+
+use kraken::{Decimal, WithdrawPolicy, WithdrawRule};
+
+fn policy() -> WithdrawPolicy {
+    let mut policy = WithdrawPolicy::default();
+
+    policy.assets.insert(
+        String::from("XXBT"),
+        WithdrawRule {
+            keys: vec![String::from("cold storage")],
+            max_amount: Some("0.5".parse().unwrap()),
+        },
+    );
+    policy.assets.insert(
+        String::from("ZEUR"),
+        WithdrawRule {
+            keys: vec![String::from("bank")],
+            max_amount: None,
+        },
+    );
+
+    policy
+}
+
+fn amount(value: &str) -> Decimal {
+    value.parse().unwrap()
+}
+
+#[test]
+fn permitted_withdrawals() {
+    let policy = policy();
+
+    assert!(policy.check("XXBT", "cold storage", &amount("0.5")).is_ok());
+    assert!(policy.check("XXBT", "cold storage", &amount("0.00000001")).is_ok());
+    assert!(policy.check("ZEUR", "bank", &amount("100000")).is_ok());
+}
+
+#[test]
+fn rejected_withdrawals() {
+    let policy = policy();
+
+    assert!(policy.check("XETH", "cold storage", &amount("0.1")).is_err());
+    assert!(policy.check("XXBT", "", &amount("0.1")).is_err());
+    assert!(policy.check("XXBT", "bank", &amount("0.1")).is_err());
+    assert!(policy.check("XXBT", "cold storage", &amount("0.50000001")).is_err());
+}
+
+#[test]
+fn rejected_non_positive_amounts() {
+    let policy = policy();
+
+    assert!(policy.check("XXBT", "cold storage", &amount("0")).is_err());
+    assert!(policy.check("XXBT", "cold storage", &amount("-0.1")).is_err());
+    assert!(policy.check("ZEUR", "bank", &amount("-100")).is_err());
+}